@@ -2,7 +2,7 @@ use std::{collections::BTreeMap, fmt, io::Write};
 
 use clap::{Subcommand, Args};
 use anyhow::{Result, anyhow};
-use cloudru::{*, blocking::{*, obs::{ListObjectsContents, ListObjectsRequest}}};
+use cloudru::{*, blocking::{*, obs::{ListEntry, ListObjectsContents, ListObjectsRequest, SyncAction, SyncDirection, SyncOptions}}};
 
 #[derive(Args, Debug)]
 pub struct Obs {
@@ -15,6 +15,7 @@ enum ObsCommand {
     Get(ObsGet),
     Put(ObsPut),
     PutStr(ObsPutStr),
+    Sync(ObsSync),
     Ls(ObsLs),
     LsVersions(ObsLsVersions),
     Du(ObsDu),
@@ -35,6 +36,18 @@ struct ObsGet {
 struct ObsPut {
     local: String,
     remote: String, 
+
+    /// Upload by parts of this size (multipart upload)
+    #[clap(long, short='p')]
+    part_size: Option<u64>,
+
+    /// Max number of parts uploaded simultaneously (multipart upload)
+    #[clap(long, short='j', default_value_t=4)]
+    concurrency: usize,
+
+    /// Checkpoint file to save progress to and resume from (multipart upload)
+    #[clap(long, short='k')]
+    checkpoint: Option<String>,
 }
 
 #[derive(Args, Debug)]
@@ -43,6 +56,24 @@ struct ObsPutStr {
     remote: String, 
 }
 
+/// Sync a local directory with a bucket prefix; one of `source`, `target` must be `obs://bucket/prefix`
+#[derive(Args, Debug)]
+struct ObsSync {
+    source: String,
+    target: String,
+
+    /// Delete files (objects) at the target that do not exist at the source
+    #[clap(long)]
+    delete: bool,
+
+    /// Print what would be done without transferring anything
+    #[clap(long, short='n')]
+    dry_run: bool,
+
+    /// Compare files of the same size by MD5/ETag rather than by modification time
+    #[clap(long, short='c')]
+    checksum: bool,
+}
 
 #[derive(Args, Debug)]
 struct ObsLs {
@@ -71,6 +102,10 @@ struct ObsLs {
     /// Print non-file entries
     #[clap(long, short='r')]
     raw: bool,
+
+    /// Group objects by `/`, listing subdirectories instead of the objects below them
+    #[clap(long, short='d')]
+    dirs: bool,
 }
 
 #[derive(Args, Debug)]
@@ -305,8 +340,17 @@ pub fn handle_obs(client: obs::ObsClient, obs: Obs) -> Result<JsonValue> {
             let source_path = put.local;
             let target_path = force_file_name(target_path, &source_path)?;
             let bucket = client.bucket(bucket_name.to_owned())?;
-            let input_file = std::fs::File::open(&source_path)?;
-            bucket.put_object(&target_path, input_file)?;
+            if let Some(part_size) = put.part_size {
+                let options = obs::MultipartUploadOptions { part_size, concurrency: put.concurrency };
+                if let Some(checkpoint) = put.checkpoint {
+                    bucket.upload_file_resumable(&target_path, &source_path, checkpoint, &options)?;
+                } else {
+                    bucket.upload_file(&target_path, &source_path, &options)?;
+                }
+            } else {
+                let input_file = std::fs::File::open(&source_path)?;
+                bucket.put_object(&target_path, input_file)?;
+            }
             Ok(JsonValue::Bool(true))
         }
         ObsCommand::PutStr(put) => {
@@ -316,6 +360,27 @@ pub fn handle_obs(client: obs::ObsClient, obs: Obs) -> Result<JsonValue> {
             bucket.put_object(target_path, source)?;
             Ok(JsonValue::Bool(true))
         }
+        ObsCommand::Sync(sync) => {
+            let (direction, local, remote) = match (sync.source.starts_with("obs://"), sync.target.starts_with("obs://")) {
+                (false, true) => (SyncDirection::Upload, &sync.source, &sync.target),
+                (true, false) => (SyncDirection::Download, &sync.target, &sync.source),
+                _ => return Err(anyhow!("exactly one of source and target must be obs://bucket/prefix")),
+            };
+            let (bucket_name, prefix) = split_bucket(remote);
+            let bucket = client.bucket(bucket_name.to_owned())?;
+            let options = SyncOptions { delete: sync.delete, dry_run: sync.dry_run, checksum: sync.checksum };
+            let report = bucket.sync(local, prefix, direction, &options)?;
+            for action in &report.actions {
+                match action {
+                    SyncAction::Upload { path, size } => println!("upload\t{}\t{path}", HrSize(*size)),
+                    SyncAction::Download { path, size } => println!("download\t{}\t{path}", HrSize(*size)),
+                    SyncAction::DeleteObject { path } | SyncAction::DeleteFile { path } => println!("delete\t-\t{path}"),
+                }
+            }
+            let dry_run = if report.dry_run { " (dry run)" } else { "" };
+            println!("\n{} actions, {} transferred, {} unchanged{dry_run}", report.actions.len(), HrSize(report.transferred_bytes()), report.unchanged);
+            Ok(JsonValue::Bool(true))
+        }
         ObsCommand::Ls(ls) => {
             let (bucket_name, bucket_path) = split_bucket(&ls.remote);
             let bucket = client.bucket(bucket_name.to_owned())?;
@@ -327,28 +392,12 @@ pub fn handle_obs(client: obs::ObsClient, obs: Obs) -> Result<JsonValue> {
                 println!("----\t----\t-------------\t---");
             }
 
-            let mut marker = ls.marker;
-            let pages = if let Some(pages) = ls.pages { 
-                pages 
-            } else if ls.all {
-                usize::MAX
-            } else {
-                1
-            };
-
             let mut s = Statistics { do_median: true, ..Default::default() };
 
-            for _ in 0..pages {
-                let list_request = ListObjectsRequest {
-                    prefix: Some(bucket_path),
-                    marker: marker.as_deref(),
-                    max_keys: ls.max_keys,
-                    ..Default::default()
-                };
-                let list = bucket.list_objects(list_request)?;
-                let Some(contents) = list.contents else { break };
+            let delimiter = if ls.dirs { Some("/") } else { None };
 
-                for ListObjectsContents { 
+            let mut print_entry = |entry| {
+                let ListObjectsContents { 
                     key, 
                     last_modified, 
                     etag, 
@@ -357,27 +406,72 @@ pub fn handle_obs(client: obs::ObsClient, obs: Obs) -> Result<JsonValue> {
                     type_, 
                     owner,
                     ..
-                } in contents {
-                    let is_std_entry = !key.ends_with('/');
-
-                    if ls.raw || is_std_entry {
-                        let type_ = type_.as_deref().unwrap_or("-");
-                        let owner = owner.as_ref().map(|s| &s.id as &str).unwrap_or("-");
+                } = match entry {
+                    ListEntry::Object(contents) => contents,
+                    ListEntry::Prefix(prefix) => {
                         if ls.long {
-                            println!("{etag}\t{storage_class}\t{owner}\t{type_}\t{size}\t{last_modified}\t{key}")
+                            println!("-\t-\t-\tDIR\t-\t-\t{prefix}")
                         } else {
-                            println!("{type_}\t{size}\t{last_modified}\t{key}")
+                            println!("DIR\t-\t-\t{prefix}")
                         }
+                        return
                     }
-
-                    if is_std_entry {
-                        s.apply(size);
+                };
+                let is_std_entry = !key.ends_with('/');
+
+                if ls.raw || is_std_entry {
+                    let type_ = type_.as_deref().unwrap_or("-");
+                    let owner = owner.as_ref().map(|s| &s.id as &str).unwrap_or("-");
+                    if ls.long {
+                        println!("{etag}\t{storage_class}\t{owner}\t{type_}\t{size}\t{last_modified}\t{key}")
+                    } else {
+                        println!("{type_}\t{size}\t{last_modified}\t{key}")
                     }
                 }
 
-                marker = list.next_marker;
+                if is_std_entry {
+                    s.apply(size);
+                }
+            };
 
-                if marker.is_none() { break }
+            let mut marker = ls.marker;
+
+            if ls.all && ls.pages.is_none() {
+                let list_request = ListObjectsRequest {
+                    prefix: Some(bucket_path),
+                    marker: marker.as_deref(),
+                    max_keys: ls.max_keys,
+                    delimiter,
+                    ..Default::default()
+                };
+                for entry in bucket.iter_entries(list_request) {
+                    print_entry(entry?);
+                }
+                marker = None;
+            } else {
+                let pages = ls.pages.unwrap_or(1);
+
+                for _ in 0..pages {
+                    let list_request = ListObjectsRequest {
+                        prefix: Some(bucket_path),
+                        marker: marker.as_deref(),
+                        max_keys: ls.max_keys,
+                        delimiter,
+                        ..Default::default()
+                    };
+                    let list = bucket.list_objects(list_request)?;
+                    let mut entries: Vec<ListEntry> = list.contents.unwrap_or_default().into_iter().map(ListEntry::Object)
+                        .chain(list.common_prefixes.unwrap_or_default().into_iter().map(|p| ListEntry::Prefix(p.prefix)))
+                        .collect();
+                    if entries.is_empty() { break }
+                    entries.sort_by(|a, b| a.name().cmp(b.name()));
+
+                    entries.into_iter().for_each(&mut print_entry);
+
+                    marker = list.next_marker;
+
+                    if marker.is_none() { break }
+                }
             }
             if ls.long {
                 println!("----\t-------------\t-----\t----\t----\t-------------\t---");
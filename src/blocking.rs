@@ -9,6 +9,7 @@ pub mod smn;
 pub mod obs;
 pub mod apig;
 pub mod dli;
+pub mod iam;
 
 pub mod client;
 
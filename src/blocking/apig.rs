@@ -6,12 +6,12 @@ use crate::*;
 
 pub struct ApigClient {
     endpoint: String,
-    credentials: Credentials,
+    credentials: SharedCredentialsProvider,
     http_client: Arc<HttpClient>,
 }
 
 impl ApigClient {
-    pub fn new(endpoint: String, credentials: Credentials, http_client: Arc<HttpClient>) -> Self { Self { endpoint, credentials, http_client } }
+    pub fn new(endpoint: String, credentials: impl Into<SharedCredentialsProvider>, http_client: Arc<HttpClient>) -> Self { Self { endpoint, credentials: credentials.into(), http_client } }
     #[inline]
     pub fn add_certificate(&self, 
         group_id: &str, 
@@ -60,7 +60,7 @@ pub fn add_certificate(
     cert_name: &str,
     cert_content: &str,
     private_key: &str,
-    credentials: &Credentials,
+    credentials: &dyn CredentialsProvider,
     client: &HttpClient,
 ) -> Result<JsonValue> {
     api_call!(POST /"{apig_endpoint}/v1.0/apigw/api-groups/{group_id}/domains/{domain_id}/certificate" ;
@@ -75,7 +75,7 @@ pub fn get_certificate(
     group_id: &str, 
     domain_id: &str,
     cert_id: &str,
-    credentials: &Credentials,
+    credentials: &dyn CredentialsProvider,
     client: &HttpClient
 ) -> Result<JsonValue> {
     api_call!(GET /"{apig_endpoint}/v1.0/apigw/api-groups/{group_id}/domains/{domain_id}/certificate/{cert_id}"; credentials, client)
@@ -86,7 +86,7 @@ pub fn delete_certificate(
     group_id: &str, 
     domain_id: &str,
     cert_id: &str,
-    credentials: &Credentials,
+    credentials: &dyn CredentialsProvider,
     client: &HttpClient
 ) -> Result<JsonValue> {
     api_call!(DELETE /"{apig_endpoint}/v1.0/apigw/api-groups/{group_id}/domains/{domain_id}/certificate/{cert_id}"; credentials, client)
@@ -95,7 +95,7 @@ pub fn delete_certificate(
 pub fn get_api_group_detail(    
     apig_endpoint: &str, 
     group_id: &str, 
-    credentials: &Credentials,
+    credentials: &dyn CredentialsProvider,
     client: &HttpClient)  -> Result<JsonValue> {       
     api_call!(GET /"{apig_endpoint}/v1.0/apigw/api-groups/{group_id}"; credentials, client)
 }
\ No newline at end of file
@@ -17,7 +17,8 @@ impl ServiceClientBuild for Client {
     fn obs(&self) -> Result<super::obs::ObsClient> { Ok(super::obs::ObsClient::new(
         self.resolve_endpoint(svc_id::obs)?,
         self.credentials.clone(),
-        self.http_client.clone()))
+        self.http_client.clone())
+        .with_addressing_style(self.config.obs_addressing_style))
     }
     fn apig(&self) -> Result<super::apig::ApigClient> { Ok(super::apig::ApigClient::new(
         self.resolve_endpoint(svc_id::apig)?, 
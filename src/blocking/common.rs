@@ -1,7 +1,8 @@
 use tracing::debug;
 use reqwest::{Method, blocking::Request};
 
-use super::mauth;
+use super::{iam::IamClient, mauth};
+use crate::shared::iam::{auth_token_header, X_AUTH_TOKEN};
 use super::*;
 use crate::*;
 
@@ -33,37 +34,81 @@ macro_rules! api_call {
     };
 }
 
+/// Authentication of an API call
+#[derive(Debug, Clone, Copy)]
+pub enum AuthMode<'a> {
+    /// AK/SK request signature, see [mauth::time_stamp_and_sign]
+    Signature(&'a dyn CredentialsProvider),
+    /// IAM token in the `X-Auth-Token` header
+    Token(&'a IamClient),
+}
+
+impl<'a, P: CredentialsProvider> From<&'a P> for AuthMode<'a> {
+    fn from(credentials: &'a P) -> Self { Self::Signature(credentials) }
+}
+
+impl<'a> From<&'a dyn CredentialsProvider> for AuthMode<'a> {
+    fn from(credentials: &'a dyn CredentialsProvider) -> Self { Self::Signature(credentials) }
+}
+
+impl<'a> From<&'a IamClient> for AuthMode<'a> {
+    fn from(iam: &'a IamClient) -> Self { Self::Token(iam) }
+}
+
 /// Authenticated API call
-pub fn auth_api_call_explicit<R: for<'d> serde::Deserialize<'d> + Default>(
+pub fn auth_api_call_explicit<'a, R: for<'d> serde::Deserialize<'d> + Default>(
     mut request: Request, 
-    credentials: &Credentials,
+    auth: impl Into<AuthMode<'a>>,
     client: &HttpClient,
 ) -> Result<R> {
-    let dt = time::OffsetDateTime::now_utc();
-    mauth::time_stamp_and_sign(&mut request, dt, &credentials.ak, &credentials.sk)?;
+    let auth = auth.into();
+    let mut retry = None;
+    match auth {
+        AuthMode::Signature(credentials) => {
+            let dt = time::OffsetDateTime::now_utc();
+            let credentials = credentials.credentials()?;
+            mauth::time_stamp_and_sign(&mut request, dt, &credentials.ak, &credentials.sk, credentials.security_token.as_deref())?;
+        }
+        AuthMode::Token(iam) => {
+            let token = iam.token()?;
+            retry = request.try_clone().map(|r| (r, token.clone()));
+            request.headers_mut().insert(X_AUTH_TOKEN, auth_token_header(&token)?);
+        }
+    }
     debug!("Request-Full: {request:?}");
-    let resp = client.execute(request)?;
+    let mut resp = client.execute(request)?;
+    // a token may be revoked before its expiration: it is dropped and the call is retried once with a fresh one
+    if let (reqwest::StatusCode::UNAUTHORIZED, AuthMode::Token(iam), Some((mut request, rejected))) = (resp.status(), auth, retry) {
+        debug!("token rejected, retrying with a fresh one");
+        iam.reject(&rejected);
+        request.headers_mut().insert(X_AUTH_TOKEN, auth_token_header(&iam.token()?)?);
+        resp = client.execute(request)?;
+    }
     let status = resp.status();
     debug!("Response: status={} len={:?}", status, resp.content_length());
     match status {
         reqwest::StatusCode::NO_CONTENT => Ok(R::default()),
         s if s.is_success() => Ok(resp.json()?),
-        s => Err(CloudRuError::API(s, resp.text()?))
+        s => {
+            // a rejected token is not reused
+            if let (reqwest::StatusCode::UNAUTHORIZED, AuthMode::Token(iam)) = (s, auth) { iam.invalidate(); }
+            Err(CloudRuError::API(s, resp.text()?))
+        }
     }
 }
 
 /// Authenticated API call
-pub fn auth_api_call<R: for<'d> serde::Deserialize<'d> + Default, Q: serde::Serialize>(
-    m: Method, url: &str, q: &Q, credentials: &Credentials, client: &HttpClient
+pub fn auth_api_call<'a, R: for<'d> serde::Deserialize<'d> + Default, Q: serde::Serialize>(
+    m: Method, url: &str, q: &Q, auth: impl Into<AuthMode<'a>>, client: &HttpClient
 ) -> Result<R> {
     debug!("Request: {m} {url}");
     let r = client.request(m, url).json(q).build()?;
-    auth_api_call_explicit(r, credentials, client)
+    auth_api_call_explicit(r, auth, client)
 }
 
 /// Authenticated API call w/o request body
-pub fn auth_api_call_noq<R: for<'d> serde::Deserialize<'d> + Default>(m: Method, url: &str, credentials: &Credentials, client: &HttpClient) -> Result<R> {
+pub fn auth_api_call_noq<'a, R: for<'d> serde::Deserialize<'d> + Default>(m: Method, url: &str, auth: impl Into<AuthMode<'a>>, client: &HttpClient) -> Result<R> {
     debug!("Request: {m} {url}");
     let r = client.request(m, url).build()?;
-    auth_api_call_explicit(r, credentials, client)
+    auth_api_call_explicit(r, auth, client)
 }
\ No newline at end of file
@@ -14,7 +14,7 @@ use crate::*;
 pub struct DliClient {
     endpoint: String,
     project_id: String,
-    credentials: Credentials,
+    credentials: SharedCredentialsProvider,
     http_client: Arc<HttpClient>,
 }
 
@@ -22,14 +22,14 @@ impl DliClient {
     pub fn new(
         endpoint: String,
         project_id: String,
-        credentials: Credentials,
+        credentials: impl Into<SharedCredentialsProvider>,
         http_client: Arc<HttpClient>,
     ) -> Self {
         Self {
             endpoint,
             project_id,
             http_client,
-            credentials,
+            credentials: credentials.into(),
         }
     }
 
@@ -9,12 +9,12 @@ pub use crate::model::fg as model;
 pub struct FgClient {
     endpoint: String,
     project_id: String,
-    credentials: Credentials,
+    credentials: SharedCredentialsProvider,
     http_client: Arc<HttpClient>,
 }
 
 impl FgClient {
-    pub fn new(endpoint: String, project_id: String, credentials: Credentials, http_client: Arc<HttpClient>) -> Self { Self { endpoint, project_id, credentials, http_client } }
+    pub fn new(endpoint: String, project_id: String, credentials: impl Into<SharedCredentialsProvider>, http_client: Arc<HttpClient>) -> Self { Self { endpoint, project_id, credentials: credentials.into(), http_client } }
     pub fn logging_to_lts_enable(&self) -> Result<JsonValue> { 
         logging_to_lts_enable(&self.endpoint, &self.project_id, &self.credentials, &self.http_client) 
     }
@@ -27,7 +27,7 @@ impl FgClient {
 pub fn logging_to_lts_enable(
     fg_endpoint: &str,
     project_id: &str,
-    credentials: &Credentials,
+    credentials: &dyn CredentialsProvider,
     client: &HttpClient
 ) -> Result<JsonValue> {
     //POST /v2/{project_id}/fgs/functions/enable-lts-logs
@@ -41,7 +41,7 @@ pub fn logging_to_lts_detail(
     fg_endpoint: &str,
     project_id: &str,
     urn: &str,
-    credentials: &Credentials,
+    credentials: &dyn CredentialsProvider,
     client: &HttpClient
 ) -> Result<JsonValue> {
     //GET /v2/{project_id}/fgs/functions/{urn}/lts-log-detail
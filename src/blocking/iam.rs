@@ -0,0 +1,121 @@
+//! IAM tokens
+use std::{sync::{Arc, Mutex, PoisonError}, time::Duration};
+
+use reqwest::Method;
+use tracing::debug;
+
+use crate::config::svc_id;
+use crate::shared::iam::*;
+pub use crate::model::iam::*;
+pub use crate::shared::iam::DEFAULT_TOKEN_REFRESH_AHEAD;
+use super::*;
+use crate::*;
+
+/// Obtains IAM tokens from `POST /v3/auth/tokens` and caches them until `refresh_ahead` before their expiration
+#[derive(Clone)]
+pub struct IamClient {
+    endpoint: String,
+    identity: IamIdentity,
+    scope: TokenScope,
+    credentials: Option<SharedCredentialsProvider>,
+    http_client: Arc<HttpClient>,
+    cache: Arc<TokenCache>,
+    /// held while a token is issued, so that concurrent callers wait for it rather than issue their own
+    refreshing: Arc<Mutex<()>>,
+}
+
+impl std::fmt::Debug for IamClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IamClient")
+            .field("endpoint", &self.endpoint)
+            .field("identity", &self.identity)
+            .field("scope", &self.scope)
+            .field("cache", &self.cache)
+            .finish_non_exhaustive()
+    }
+}
+
+impl IamClient {
+    pub fn new(endpoint: String, identity: IamIdentity, scope: TokenScope, http_client: Arc<HttpClient>) -> Self {
+        Self { endpoint, identity, scope, credentials: None, http_client, cache: Arc::new(TokenCache::new(DEFAULT_TOKEN_REFRESH_AHEAD)), refreshing: Arc::new(Mutex::new(())) }
+    }
+
+    /// Signs the token requests with AK/SK, which is required for [IamIdentity::Agency]
+    pub fn with_credentials(mut self, credentials: impl Into<SharedCredentialsProvider>) -> Self {
+        self.credentials = Some(credentials.into());
+        self
+    }
+
+    /// Sets how long before the expiration a cached token is replaced; drops the cached token
+    pub fn with_refresh_ahead(mut self, refresh_ahead: Duration) -> Self {
+        self.cache = Arc::new(TokenCache::new(refresh_ahead));
+        self
+    }
+
+    /// Cached token, a new one is issued once the cached one is about to expire. 
+    /// Concurrent callers share a single token request
+    pub fn token(&self) -> Result<String> {
+        if let Some(token) = self.cache.get(time::OffsetDateTime::now_utc()) {
+            return Ok(token);
+        }
+        let _refreshing = self.refreshing.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(token) = self.cache.get(time::OffsetDateTime::now_utc()) {
+            return Ok(token);
+        }
+        let issued = self.issue_token()?;
+        let token = issued.token.clone();
+        self.cache.set(issued);
+        Ok(token)
+    }
+
+    /// Drops the cached token, e.g. after it was rejected
+    pub fn invalidate(&self) {
+        self.cache.invalidate()
+    }
+
+    /// Drops the cached token if it is `token`, e.g. after the API rejected it, 
+    /// keeping a token issued meanwhile for another call
+    pub fn reject(&self, token: &str) {
+        self.cache.reject(token)
+    }
+
+    /// Issues a new token, bypassing the cache
+    pub fn issue_token(&self) -> Result<IssuedToken> {
+        let url = format!("{}/v3/auth/tokens", self.endpoint);
+        debug!("Request: POST {url}");
+        let mut request = self.http_client.request(Method::POST, url)
+            .json(&auth_tokens_body(&self.identity, &self.scope))
+            .build()?;
+        if let Some(credentials) = &self.credentials {
+            let credentials = credentials.credentials()?;
+            let dt = time::OffsetDateTime::now_utc();
+            mauth::time_stamp_and_sign(&mut request, dt, &credentials.ak, &credentials.sk, credentials.security_token.as_deref())?;
+        }
+        let resp = self.http_client.execute(request)?;
+        let status = resp.status();
+        debug!("Response: status={} len={:?}", status, resp.content_length());
+        if !status.is_success() {
+            return Err(CloudRuError::API(status, resp.text()?));
+        }
+        let subject_token = resp.headers().get(X_SUBJECT_TOKEN).map(|hv| hv.to_str()).transpose()?.map(|s| s.to_owned());
+        let response: TokenResponse = resp.json()?;
+        issued_token(subject_token.as_deref(), &response)
+    }
+}
+
+pub trait IamClientBuild {
+    /// IAM client issuing tokens for `identity` in `scope`; agency tokens are requested with the client credentials
+    fn build_iam(&self, identity: IamIdentity, scope: TokenScope) -> Result<IamClient>;
+}
+
+impl IamClientBuild for Client {
+    fn build_iam(&self, identity: IamIdentity, scope: TokenScope) -> Result<IamClient> {
+        let assumes_agency = matches!(identity, IamIdentity::Agency { .. });
+        let iam = IamClient::new(
+            self.resolve_endpoint(svc_id::iam)?,
+            identity,
+            scope,
+            self.http_client.clone());
+        Ok(if assumes_agency { iam.with_credentials(self.credentials.clone()) } else { iam })
+    }
+}
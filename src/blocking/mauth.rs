@@ -5,6 +5,7 @@ use crate::Result;
 use crate::shared::signing;
 
 pub const X_SDK_DATE: &'static str = "X-Sdk-Date";
+pub const X_SECURITY_TOKEN: &str = "X-Security-Token";
 
 const LONG_DATETIME: &[time::format_description::FormatItem<'static>] =
     time::macros::format_description!("[year][month][day]T[hour][minute][second]Z");
@@ -57,10 +58,13 @@ pub fn signature(string_to_sign: &str, secret_key: &str) -> Result<String> {
     Ok(hexencode(&hmac.finalize().into_bytes()))
 }
 
-pub fn time_stamp_and_sign(r: &mut reqwest::blocking::Request, dt: time::OffsetDateTime, ak: &str, sk: &str) -> Result<()> {
+pub fn time_stamp_and_sign(r: &mut reqwest::blocking::Request, dt: time::OffsetDateTime, ak: &str, sk: &str, security_token: Option<&str>) -> Result<()> {
     let hs = r.headers_mut();
     let dts = x_sdk_date(dt)?;
     hs.insert(X_SDK_DATE, dts.parse()?);
+    if let Some(security_token) = security_token {
+        hs.insert(X_SECURITY_TOKEN, security_token.parse()?);
+    }
     let cr = canonical_request(r)?;
     let sh = signing::signed_header_string(r.headers());    
     let s2s = string_to_sign(&cr, dt).unwrap();
@@ -103,3 +107,15 @@ fn test_signature() {
     let result = "7be6668032f70418fcc22abc52071e57aff61b84a1d2381bb430d6870f4f6ebe";
     assert_eq!(result, signature(s2s, sk).unwrap());
 }
+
+#[test]
+fn test_security_token() {
+    use reqwest::{Method, Url};
+    let mut r = Request::new(Method::GET, Url::parse("https://service.region.example.com/v1/vpcs").unwrap());
+    r.headers_mut().insert("Host", "service.region.example.com".parse().unwrap());
+    let dt = time::macros::datetime!(2019 - 11 - 15 03:36:55 utc);
+    time_stamp_and_sign(&mut r, dt, "AK", "SK", Some("TOKEN")).unwrap();
+    assert_eq!(r.headers().get(X_SECURITY_TOKEN).unwrap(), "TOKEN");
+    let a11n = r.headers().get("Authorization").unwrap().to_str().unwrap();
+    assert!(a11n.contains("SignedHeaders=host;x-sdk-date;x-security-token,"), "{a11n}");
+}
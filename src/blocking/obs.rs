@@ -1,9 +1,8 @@
-use std::{io::{self, Read, Seek, SeekFrom, Write}, sync::Arc};
-use reqwest::{blocking::{Body, Request, RequestBuilder}, header::{HeaderMap, HeaderValue}, Method, Url};
-use tracing::{debug, instrument, Level, enabled};
+use std::{fs::File, io::{self, Read, Seek, SeekFrom, Write}, iter, ops::RangeBounds, panic, path::Path, sync::{atomic::{AtomicBool, AtomicUsize, Ordering}, Arc, Mutex}, thread, time::Duration};
+use reqwest::{blocking::{Body, Request, RequestBuilder, Response}, header::{HeaderMap, HeaderValue}, Method, Url};
+use tracing::{debug, instrument, warn, Level, enabled};
 
-use crate::shared::{mauth_obs::*, obs::{extract_bucket_meta, extract_object_meta}};
-use error::ParameterKind;
+use crate::shared::{mauth_obs::*, obs::{check_transfer_prefixes, content_md5, BucketAddress, delete_objects_body, EtagVerifier, post_policy_fields, effective_part_size, extract_bucket_meta, extract_object_meta, create_bucket_headers, file_md5, file_size_and_mtime, local_sync_entries, plan_sync, sync_download_path, range_header, remote_sync_entry, sync_prefix, object_io_sse_headers, condition_headers, copy_object_headers, put_object_headers, sse_copy_source_headers, sse_read_headers, sse_write_headers, plan_parts, read_part, ListPager, PartRange, ReadAhead}};
 use CloudRuError;
 
 pub use crate::model::obs::*;
@@ -16,16 +15,27 @@ use crate::*;
 
 pub struct ObsClient {
     endpoint: String,
-    credentials: Credentials,
+    credentials: SharedCredentialsProvider,
     http_client: Arc<HttpClient>,
+    addressing_style: AddressingStyle,
 }
 
 impl ObsClient {
-    pub fn new(endpoint: String, credentials: Credentials, http_client: Arc<HttpClient>) -> Self { Self { endpoint, http_client, credentials } }
+    pub fn new(endpoint: String, credentials: impl Into<SharedCredentialsProvider>, http_client: Arc<HttpClient>) -> Self { 
+        Self { endpoint, http_client, credentials: credentials.into(), addressing_style: AddressingStyle::default() } 
+    }
+
+    /// Sets the addressing style of the buckets produced by [ObsClient::bucket]
+    pub fn with_addressing_style(mut self, addressing_style: AddressingStyle) -> Self {
+        self.addressing_style = addressing_style;
+        self
+    }
+
     pub fn bucket(&self, bucket_name: String) -> Result<Bucket> { 
-        Bucket::new(
+        Bucket::new_with_addressing(
             bucket_name, 
             self.endpoint.clone(), 
+            self.addressing_style,
             self.credentials.clone(), 
             self.http_client.clone()
         ) }
@@ -34,10 +44,11 @@ impl ObsClient {
 #[derive(Debug, Clone)]
 pub struct Bucket {
     bucket_name: String,
-    bucket_url: Url,
+    address: BucketAddress,
     host: HeaderValue,
-    credentials: Credentials,
+    credentials: SharedCredentialsProvider,
     http_client: Arc<HttpClient>,
+    integrity_checks: bool,
 }
 
 struct R<'r> { r: &'r mut Request }
@@ -50,14 +61,15 @@ impl<'r> RequestW for R<'r> {
 
 
 trait Signer {
-    fn timestamp_and_sign(self, bucket_name: &str, credentials: &Credentials) -> Result<Request>;
+    fn timestamp_and_sign(self, bucket_name: &str, credentials: &dyn CredentialsProvider) -> Result<Request>;
 }
 
 impl Signer for RequestBuilder {
-    fn timestamp_and_sign(self, bucket_name: &str, credentials: &Credentials) -> Result<Request> {
+    fn timestamp_and_sign(self, bucket_name: &str, credentials: &dyn CredentialsProvider) -> Result<Request> {
+        let credentials = credentials.credentials()?;
         let mut request = self.build()?;
         let dt = time::OffsetDateTime::now_utc();
-        time_stamp_and_sign(bucket_name, &mut R { r: &mut request }, dt, &credentials.ak, &credentials.sk)?;
+        time_stamp_and_sign(bucket_name, &mut R { r: &mut request }, dt, &credentials.ak, &credentials.sk, credentials.security_token.as_deref())?;
         Ok(request)
     }
 }
@@ -67,28 +79,121 @@ macro_rules! bail_on_failure {
         if !$result.status().is_success() {
             let status = $result.status();
             let err = $result.text().cx("error text in bail_on_failure")?;
-            return Err(CloudRuError::API(status, err).into());
+            return Err(CloudRuError::from_api_response(status, err).into());
         }        
     };
 }
 
+fn parse_xml<T: serde::de::DeserializeOwned>(result: Response) -> Result<T> {
+    if enabled!(Level::DEBUG) {
+        let text = result.text()?;
+        debug!(response_text=?&text);
+        Ok(serde_xml_rs::from_str(&text)?)
+    } else {
+        Ok(serde_xml_rs::from_reader(result)?)
+    }
+}
+
+
+impl ObsClient {
+    /// list the buckets and parallel file systems of the account
+    pub fn list_buckets(&self) -> Result<ListBucketsResult> {
+        let url: Url = self.endpoint.parse()?;
+        let request = self.http_client.request(Method::GET, url);
+        let request = request.timestamp_and_sign("", &self.credentials)?;
+
+        debug!(request_full=?request);
+
+        let result = self.http_client.execute(request)?;
+        bail_on_failure!(result);
+
+        parse_xml(result)
+    }
+
+    /// create bucket `bucket_name` with `options` and return its handle
+    pub fn create_bucket(&self, bucket_name: String, options: &CreateBucketOptions) -> Result<Bucket> {
+        let bucket = self.bucket(bucket_name)?;
+        let request = bucket.http_client.request(Method::PUT, bucket.url("/"));
+        let request: RequestBuilder = bucket.start_request(request);
+        let request = request.headers(create_bucket_headers(options));
+        let request = match &options.location {
+            Some(location) => {
+                let body = serde_xml_rs::to_string(&CreateBucketConfiguration { location: location.clone() })?;
+                request.header("content-type", "application/xml").body(body)
+            }
+            None => request,
+        };
+        let request = bucket.sign_request(request)?;
+
+        debug!(request_full=?request);
+
+        let result = bucket.http_client.execute(request)?;
+        bail_on_failure!(result);
+
+        Ok(bucket)
+    }
+
+    /// delete bucket `bucket_name`, which must be empty
+    pub fn delete_bucket(&self, bucket_name: String) -> Result<()> {
+        let bucket = self.bucket(bucket_name)?;
+        let request = bucket.http_client.request(Method::DELETE, bucket.url("/"));
+        let request: RequestBuilder = bucket.start_request(request);
+        let request = bucket.sign_request(request)?;
+
+        debug!(request_full=?request);
+
+        let result = bucket.http_client.execute(request)?;
+        bail_on_failure!(result);
+
+        Ok(())
+    }
+}
 
 impl Bucket {
-    pub fn new(bucket_name: String, obs_endpoint: String, credentials: Credentials, http_client: Arc<HttpClient>) -> Result<Self> {
-        let mut bucket_url: Url = obs_endpoint.parse()?;
-        let bucket_host = bucket_url.host()
-            .ok_or(CloudRuError::Parameter(ParameterKind::S3BucketUrl))?;
-        let host = format!("{}.{}", bucket_name, bucket_host);
-        bucket_url.set_host(Some(&host))?;
-        let host = host.parse()?;
-        Ok(Self { bucket_name, bucket_url, host, credentials, http_client })
+    pub fn new(bucket_name: String, obs_endpoint: String, credentials: impl Into<SharedCredentialsProvider>, http_client: Arc<HttpClient>) -> Result<Self> {
+        Self::new_with_addressing(bucket_name, obs_endpoint, AddressingStyle::default(), credentials, http_client)
+    }
+
+    pub fn new_with_addressing(
+        bucket_name: String, 
+        obs_endpoint: String, 
+        addressing_style: AddressingStyle, 
+        credentials: impl Into<SharedCredentialsProvider>, 
+        http_client: Arc<HttpClient>
+    ) -> Result<Self> {
+        let address = BucketAddress::new(&bucket_name, &obs_endpoint, addressing_style)?;
+        let host = address.host.parse()?;
+        Ok(Self { bucket_name, address, host, credentials: credentials.into(), http_client, integrity_checks: false })
+    }
+
+    #[inline]
+    fn signing_name(&self) -> &str {
+        &self.address.signing_name
+    }
+
+    /// Enables integrity checks: puts and part uploads of in-memory bodies, including the parts of file uploads, 
+    /// and [ObjectIO] writes send `Content-MD5`, and whole-object downloads are verified against the ETag, 
+    /// failing with [CloudRuError::ChecksumMismatch]. 
+    /// Streamed bodies, e.g. a `File` passed to `put_object`, are sent without `Content-MD5`, since it would take a second pass over the data. 
+    /// Objects uploaded in parts or encrypted with SSE-KMS/SSE-C are not verified, since their ETag is not an MD5 of the content
+    pub fn with_integrity_checks(mut self, enabled: bool) -> Self {
+        self.integrity_checks = enabled;
+        self
+    }
+
+    /// adds `Content-MD5` of `data` to the request if integrity checks are enabled
+    fn with_content_md5(&self, request: RequestBuilder, data: &[u8]) -> RequestBuilder {
+        if self.integrity_checks { request.header("content-md5", content_md5(data)) } else { request }
+    }
+
+    /// verifier of the object returned with `headers` if integrity checks are enabled
+    fn etag_verifier(&self, headers: &HeaderMap) -> Option<EtagVerifier> {
+        if self.integrity_checks { EtagVerifier::new(headers) } else { None }
     }
 
     #[inline]
     fn url(&self, path: impl AsRef<str>) -> Url {
-        let mut url = self.bucket_url.clone();
-        url.set_path(path.as_ref());
-        url
+        self.address.url(path.as_ref())
     }
 
     #[inline]
@@ -102,15 +207,15 @@ impl Bucket {
         let url = self.url("/")
             .with_var_opt("prefix", request.prefix)
             .with_var_opt("marker", request.marker)
-            .with_var_opt("max_keys", request.max_keys.map(|s| format!("{s}")))
+            .with_var_opt("max-keys", request.max_keys.map(|s| format!("{s}")))
             .with_var_opt("delimiter", request.delimiter)
-            .with_var_opt("key_marker", request.key_marker)
-            .with_var_opt("version_id_marker", request.version_id_marker)
+            .with_var_opt("key-marker", request.key_marker)
+            .with_var_opt("version-id-marker", request.version_id_marker)
             ;
 
         let request = self.http_client.request(Method::GET, url)
             .header("host", self.host.clone())
-            .timestamp_and_sign(&self.bucket_name, &self.credentials)?;
+            .timestamp_and_sign(self.signing_name(), &self.credentials)?;
 
         debug!(request_full=?request);
 
@@ -135,15 +240,15 @@ impl Bucket {
             .with_var_key("versions")
             .with_var_opt("prefix", request.prefix)
             .with_var_opt("marker", request.marker)
-            .with_var_opt("max_keys", request.max_keys.map(|s| format!("{s}")))
+            .with_var_opt("max-keys", request.max_keys.map(|s| format!("{s}")))
             .with_var_opt("delimiter", request.delimiter)
-            .with_var_opt("key_marker", request.key_marker)
-            .with_var_opt("version_id_marker", request.version_id_marker)
+            .with_var_opt("key-marker", request.key_marker)
+            .with_var_opt("version-id-marker", request.version_id_marker)
             ;
 
         let request = self.http_client.request(Method::GET, url)
             .header("host", self.host.clone())
-            .timestamp_and_sign(&self.bucket_name, &self.credentials)?;
+            .timestamp_and_sign(self.signing_name(), &self.credentials)?;
 
         debug!(request_full=?request);
 
@@ -165,27 +270,277 @@ impl Bucket {
         self.list_objects(ListObjectsRequest { prefix, ..Default::default() })
     }
 
+    /// Lists objects page by page, starting at `request.marker`. Iteration stops after the first error.
+    pub fn iter_objects(&self, request: ListObjectsRequest<'_>) -> impl Iterator<Item = Result<ListObjectsContents>> + '_ {
+        let mut pager = ListPager::new(&request);
+        let mut page = Vec::new().into_iter();
+        iter::from_fn(move || loop {
+            if let Some(item) = page.next() { return Some(Ok(item)) }
+            if pager.is_done() { return None }
+            match self.list_objects(pager.request()) {
+                Ok(result) => page = pager.advance_objects(result).into_iter(),
+                Err(e) => { pager.stop(); return Some(Err(e)) }
+            }
+        })
+    }
+
+    /// Lists objects and common prefixes page by page, starting at `request.marker`. 
+    /// With `request.delimiter` set, yields the "directories" without listing the objects below them.
+    /// Iteration stops after the first error.
+    pub fn iter_entries(&self, request: ListObjectsRequest<'_>) -> impl Iterator<Item = Result<ListEntry>> + '_ {
+        let mut pager = ListPager::new(&request);
+        let mut page = Vec::new().into_iter();
+        iter::from_fn(move || loop {
+            if let Some(item) = page.next() { return Some(Ok(item)) }
+            if pager.is_done() { return None }
+            match self.list_objects(pager.request()) {
+                Ok(result) => page = pager.advance_entries(result).into_iter(),
+                Err(e) => { pager.stop(); return Some(Err(e)) }
+            }
+        })
+    }
+
+    /// Lists object versions and deletion markers page by page, starting at `request.key_marker`/`request.version_id_marker`. 
+    /// Iteration stops after the first error.
+    pub fn iter_object_versions(&self, request: ListObjectsRequest<'_>) -> impl Iterator<Item = Result<ObjectVersionEntry>> + '_ {
+        let mut pager = ListPager::new(&request);
+        let mut page = Vec::new().into_iter();
+        iter::from_fn(move || loop {
+            if let Some(item) = page.next() { return Some(Ok(item)) }
+            if pager.is_done() { return None }
+            match self.list_object_versions(pager.request()) {
+                Ok(result) => page = pager.advance_versions(result).into_iter(),
+                Err(e) => { pager.stop(); return Some(Err(e)) }
+            }
+        })
+    }
+
+    /// get the lifecycle configuration of the bucket, `None` if not configured
+    pub fn get_lifecycle_configuration(&self) -> Result<Option<LifecycleConfiguration>> {
+        match self.get_bucket_config("lifecycle") {
+            Err(e) if e.is_api_error_code("NoSuchLifecycleConfiguration") => Ok(None),
+            r => r.map(Some)
+        }
+    }
+
+    /// replace the lifecycle configuration of the bucket
+    pub fn put_lifecycle_configuration(&self, config: &LifecycleConfiguration) -> Result<()> {
+        self.put_bucket_config("lifecycle", config)
+    }
+
+    /// delete the lifecycle configuration of the bucket
+    pub fn delete_lifecycle_configuration(&self) -> Result<()> {
+        self.delete_bucket_config("lifecycle")
+    }
+
+    /// get the versioning status of the bucket, `None` if versioning has never been enabled
+    pub fn get_versioning(&self) -> Result<Option<VersioningStatus>> {
+        let config: VersioningConfiguration = self.get_bucket_config("versioning")?;
+        Ok(config.status)
+    }
+
+    /// enable or suspend versioning of the bucket
+    pub fn set_versioning(&self, status: VersioningStatus) -> Result<()> {
+        self.put_bucket_config("versioning", &VersioningConfiguration { status: Some(status) })
+    }
+
+    /// get the ACL of the bucket
+    pub fn get_bucket_acl(&self) -> Result<AccessControlPolicy> {
+        self.get_bucket_config("acl")
+    }
+
+    /// replace the ACL of the bucket
+    pub fn put_bucket_acl(&self, acl: &AccessControlPolicy) -> Result<()> {
+        self.put_bucket_config("acl", acl)
+    }
+
+    /// replace the ACL of the bucket with a canned one
+    pub fn set_bucket_canned_acl(&self, acl: CannedAcl) -> Result<()> {
+        self.put_canned_acl("/", acl)
+    }
+
+    /// get the ACL of the object at `remote_path`
+    pub fn get_object_acl(&self, remote_path: impl AsRef<str>) -> Result<AccessControlPolicy> {
+        let result = self.get_subresource(remote_path.as_ref(), "acl")?;
+        parse_xml(result)
+    }
+
+    /// replace the ACL of the object at `remote_path`
+    pub fn put_object_acl(&self, remote_path: impl AsRef<str>, acl: &AccessControlPolicy) -> Result<()> {
+        self.put_subresource(remote_path.as_ref(), "acl", "application/xml", serde_xml_rs::to_string(acl)?)
+    }
+
+    /// replace the ACL of the object at `remote_path` with a canned one
+    pub fn set_object_canned_acl(&self, remote_path: impl AsRef<str>, acl: CannedAcl) -> Result<()> {
+        self.put_canned_acl(remote_path.as_ref(), acl)
+    }
+
+    /// get the bucket policy, `None` if not set
+    pub fn get_bucket_policy(&self) -> Result<Option<BucketPolicy>> {
+        match self.get_subresource("/", "policy") {
+            Err(e) if e.is_api_error_code("NoSuchBucketPolicy") => Ok(None),
+            Err(e) => Err(e),
+            Ok(result) => Ok(Some(result.json()?)),
+        }
+    }
+
+    /// replace the bucket policy
+    pub fn put_bucket_policy(&self, policy: &BucketPolicy) -> Result<()> {
+        self.put_subresource("/", "policy", "application/json", serde_json::to_string(policy)?)
+    }
+
+    /// delete the bucket policy
+    pub fn delete_bucket_policy(&self) -> Result<()> {
+        self.delete_bucket_config("policy")
+    }
+
+    /// get the default encryption of the bucket, `None` if it is not configured
+    pub fn get_bucket_encryption(&self) -> Result<Option<BucketEncryption>> {
+        match self.get_bucket_config("encryption") {
+            Err(e) if e.is_api_error_code("NoSuchEncryptionConfiguration") 
+                || e.is_api_error_code("ServerSideEncryptionConfigurationNotFoundError") => Ok(None),
+            r => r.map(Some)
+        }
+    }
+
+    /// set the default encryption of the bucket
+    pub fn put_bucket_encryption(&self, config: &BucketEncryption) -> Result<()> {
+        self.put_bucket_config("encryption", config)
+    }
+
+    /// delete the default encryption of the bucket
+    pub fn delete_bucket_encryption(&self) -> Result<()> {
+        self.delete_bucket_config("encryption")
+    }
+
+    /// GET /?`subresource` as XML
+    fn get_bucket_config<T: serde::de::DeserializeOwned>(&self, subresource: &str) -> Result<T> {
+        let result = self.get_subresource("/", subresource)?;
+        parse_xml(result)
+    }
+
+    /// PUT /?`subresource` with `config` as XML
+    fn put_bucket_config<T: serde::Serialize>(&self, subresource: &str, config: &T) -> Result<()> {
+        self.put_subresource("/", subresource, "application/xml", serde_xml_rs::to_string(config)?)
+    }
+
+    /// DELETE /?`subresource`
+    fn delete_bucket_config(&self, subresource: &str) -> Result<()> {
+        let url = self.url("/").with_var_key(subresource);
+        let request = self.http_client.request(Method::DELETE, url);
+        let request: RequestBuilder = self.start_request(request);
+        let request = self.sign_request(request)?;
+
+        debug!(request_full=?request);
+
+        let result = self.http_client.execute(request)?;
+        bail_on_failure!(result);
+
+        Ok(())
+    }
+
+    /// GET `remote_path`?`subresource`
+    fn get_subresource(&self, remote_path: &str, subresource: &str) -> Result<Response> {
+        let url = self.url(remote_path).with_var_key(subresource);
+        let request = self.http_client.request(Method::GET, url);
+        let request: RequestBuilder = self.start_request(request);
+        let request = self.sign_request(request)?;
+
+        debug!(request_full=?request);
+
+        let result = self.http_client.execute(request)?;
+        bail_on_failure!(result);
+
+        Ok(result)
+    }
+
+    /// PUT `remote_path`?`subresource` with `body`
+    fn put_subresource(&self, remote_path: &str, subresource: &str, content_type: &str, body: String) -> Result<()> {
+        let url = self.url(remote_path).with_var_key(subresource);
+        let request = self.http_client.request(Method::PUT, url);
+        let request: RequestBuilder = self.start_request(request);
+        let request = request
+            .header("content-type", content_type)
+            .header("content-md5", content_md5(body.as_bytes()))
+            .body(body);
+        let request = self.sign_request(request)?;
+
+        debug!(request_full=?request);
+
+        let result = self.http_client.execute(request)?;
+        bail_on_failure!(result);
+
+        Ok(())
+    }
+
+    /// PUT `remote_path`?acl with the `x-obs-acl` header
+    fn put_canned_acl(&self, remote_path: &str, acl: CannedAcl) -> Result<()> {
+        let url = self.url(remote_path).with_var_key("acl");
+        let request = self.http_client.request(Method::PUT, url);
+        let request: RequestBuilder = self.start_request(request);
+        let request = request.header("x-obs-acl", acl.as_str());
+        let request = self.sign_request(request)?;
+
+        debug!(request_full=?request);
+
+        let result = self.http_client.execute(request)?;
+        bail_on_failure!(result);
+
+        Ok(())
+    }
+
     fn start_request(&self, request: RequestBuilder) -> RequestBuilder {
         request.header("host", self.host.clone())
     }
 
     fn sign_request(&self, request: RequestBuilder) -> Result<Request> {
-        request.timestamp_and_sign(&self.bucket_name, &self.credentials)
+        request.timestamp_and_sign(self.signing_name(), &self.credentials)
     }
 
     /// get object at `remote_path` and write its data to `w`
     pub fn get_object<W: Write>(&self, remote_path: impl AsRef<str>, w: &mut W) -> Result<()> {
+        self.get_object_with_headers(remote_path.as_ref(), w, HeaderMap::new())
+    }
+
+    /// get object at `remote_path`, encrypted with `sse`, and write its data to `w`
+    pub fn get_object_encrypted<W: Write>(&self, remote_path: impl AsRef<str>, w: &mut W, sse: &ServerSideEncryption) -> Result<()> {
+        self.get_object_with_headers(remote_path.as_ref(), w, sse_read_headers(sse)?)
+    }
+
+    /// get object at `remote_path` if `conditions` are met and write its data to `w`, 
+    /// failing with [CloudRuError::NotModified] or [CloudRuError::PreconditionFailed] otherwise
+    pub fn get_object_conditional<W: Write>(&self, remote_path: impl AsRef<str>, w: &mut W, conditions: &ObjectConditions) -> Result<()> {
+        self.get_object_with_headers(remote_path.as_ref(), w, condition_headers(conditions, "")?)
+    }
+
+    fn get_object_with_headers<W: Write>(&self, remote_path: &str, w: &mut W, headers: HeaderMap) -> Result<()> {
         let request = self.http_client.request(Method::GET, self.url(remote_path));
         let request: RequestBuilder = self.start_request(request);
+        let request = request.headers(headers);
         let request = self.sign_request(request)?;
 
         debug!(request_full=?request);
 
-        let mut result = self.http_client.execute(request)?;
+        let result = self.http_client.execute(request)?;
         bail_on_failure!(result);
         
-        result.copy_to(w)?;
-        Ok(())
+        self.copy_verified(result, w)
+    }
+
+    /// write the content of `result` to `w`, verifying it against the ETag if integrity checks are enabled
+    fn copy_verified<W: Write>(&self, mut result: Response, w: &mut W) -> Result<()> {
+        let Some(mut verifier) = self.etag_verifier(result.headers()) else {
+            result.copy_to(w)?;
+            return Ok(())
+        };
+        let mut buf = vec![0; 64 * 1024];
+        loop {
+            let n = result.read(&mut buf)?;
+            if n == 0 { break }
+            verifier.update(&buf[..n]);
+            w.write_all(&buf[..n])?;
+        }
+        verifier.verify()
     }
 
     /// get object version at `remote_path` and write its data to `w`
@@ -197,18 +552,37 @@ impl Bucket {
 
         debug!(request_full=?request);
 
-        let mut result = self.http_client.execute(request)?;
+        let result = self.http_client.execute(request)?;
         bail_on_failure!(result);
         
-        result.copy_to(w)?;
-        Ok(())
+        self.copy_verified(result, w)
     }
 
     /// put object at `remote_path` filling it with data read from `input`
     pub fn put_object<I>(&self, remote_path: impl AsRef<str>, input: I) -> Result<()> where Body: From<I> {
+        self.put_object_with_headers(remote_path.as_ref(), input, HeaderMap::new())
+    }
+
+    /// put object at `remote_path` encrypted with `sse`, filling it with data read from `input`
+    pub fn put_object_encrypted<I>(&self, remote_path: impl AsRef<str>, input: I, sse: &ServerSideEncryption) -> Result<()> where Body: From<I> {
+        self.put_object_with_headers(remote_path.as_ref(), input, sse_write_headers(sse)?)
+    }
+
+    /// put object at `remote_path` with content headers, metadata, storage class and encryption set by `options`, 
+    /// filling it with data read from `input`
+    pub fn put_object_with_options<I>(&self, remote_path: impl AsRef<str>, input: I, options: &PutObjectOptions) -> Result<()> where Body: From<I> {
+        self.put_object_with_headers(remote_path.as_ref(), input, put_object_headers(options)?)
+    }
+
+    fn put_object_with_headers<I>(&self, remote_path: &str, input: I, headers: HeaderMap) -> Result<()> where Body: From<I> {
         let request = self.http_client.request(Method::PUT, self.url(remote_path));
         let request: RequestBuilder = self.start_request(request);
-        let request = request.body(input);
+        let body = Body::from(input);
+        let request = match body.as_bytes() {
+            Some(data) => self.with_content_md5(request, data),
+            None => request,
+        };
+        let request = request.headers(headers).body(body);
         let request = self.sign_request(request)?;
 
         debug!(request_full=?request);
@@ -233,13 +607,143 @@ impl Bucket {
         Ok(())
     }
 
+    /// delete multiple objects (or object versions), splitting them into batches of [DELETE_OBJECTS_MAX_KEYS].
+    /// In `quiet` mode, only the failures are reported.
+    /// @see https://support.hc.sbercloud.ru/api/obs/obs_04_0086.html
+    pub fn delete_objects<I>(&self, objects: I, quiet: bool) -> Result<DeleteObjectsResult> 
+    where I: IntoIterator, I::Item: Into<ObjectIdentifier> {
+        let objects: Vec<ObjectIdentifier> = objects.into_iter().map(Into::into).collect();
+        let mut result = DeleteObjectsResult::default();
+        for batch in objects.chunks(DELETE_OBJECTS_MAX_KEYS) {
+            result.results.extend(self.delete_objects_batch(batch, quiet)?.results);
+        }
+        Ok(result)
+    }
+
+    /// delete all objects under `prefix`, listing and deleting them batch by batch. Returns the failures.
+    /// 
+    /// In a versioned bucket, deletion markers are created for the current versions.
+    pub fn delete_prefix(&self, prefix: &str) -> Result<DeleteObjectsResult> {
+        let mut result = DeleteObjectsResult::default();
+        let mut batch = Vec::with_capacity(DELETE_OBJECTS_MAX_KEYS);
+        for contents in self.iter_objects(ListObjectsRequest { prefix: Some(prefix), ..Default::default() }) {
+            batch.push(ObjectIdentifier::from(contents?.key));
+            if batch.len() == DELETE_OBJECTS_MAX_KEYS {
+                result.results.extend(self.delete_objects_batch(&batch, true)?.results);
+                batch.clear();
+            }
+        }
+        if !batch.is_empty() {
+            result.results.extend(self.delete_objects_batch(&batch, true)?.results);
+        }
+        Ok(result)
+    }
+
+    fn delete_objects_batch(&self, objects: &[ObjectIdentifier], quiet: bool) -> Result<DeleteObjectsResult> {
+        let (body, md5) = delete_objects_body(objects, quiet)?;
+
+        let url = self.url("/").with_var_key("delete");
+        let request = self.http_client.request(Method::POST, url);
+        let request: RequestBuilder = self.start_request(request);
+        let request = request
+            .header("content-type", "application/xml")
+            .header("content-md5", md5)
+            .body(body);
+        let request = self.sign_request(request)?;
+
+        debug!(request_full=?request);
+
+        let result = self.http_client.execute(request)?;
+        bail_on_failure!(result);
+
+        parse_xml(result)
+    }
+
+    /// delete the version `version_id` of the object at `remote_path` permanently
+    pub fn delete_object_version(&self, remote_path: impl AsRef<str>, version_id: impl AsRef<str>) -> Result<()> {
+        let url = self.url(remote_path).with_var("versionId", version_id.as_ref());
+        let request = self.http_client.request(Method::DELETE, url);
+        let request: RequestBuilder = self.start_request(request);
+        let request = self.sign_request(request)?;
+
+        debug!(request_full=?request);
+
+        let result = self.http_client.execute(request)?;
+        bail_on_failure!(result);
+
+        Ok(())
+    }
+
+    /// make the version `version_id` of the object at `remote_path` current by copying it over the current version.
+    /// Returns the ID of the new version
+    pub fn restore_version(&self, remote_path: impl AsRef<str>, version_id: impl AsRef<str>) -> Result<Option<String>> {
+        let remote_path = remote_path.as_ref();
+        let request = self.http_client.request(Method::PUT, self.url(remote_path));
+        let request: RequestBuilder = self.start_request(request);
+        let request = request.header(
+            "x-obs-copy-source", 
+            format!("/{}/{}?versionId={}", self.bucket_name, remote_path.trim_start_matches('/'), version_id.as_ref())
+        );
+        let request = self.sign_request(request)?;
+
+        debug!(request_full=?request);
+
+        let result = self.http_client.execute(request)?;
+        bail_on_failure!(result);
+
+        Ok(extract_object_meta(result.headers()).version_id)
+    }
+
     /// copy object from `source_bucket`:`source_path` to `remote_path`
     pub fn copy_object(&self, remote_path: impl AsRef<str>, source_bucket: impl AsRef<str>, source_path: impl AsRef<str>,) -> Result<()> {
+        self.copy_object_with_headers(remote_path.as_ref(), source_bucket.as_ref(), source_path.as_ref(), HeaderMap::new())
+    }
+
+    /// copy object from `source_bucket`:`source_path`, encrypted with `source_sse`, to `remote_path`, encrypting it with `sse`
+    pub fn copy_object_encrypted(
+        &self, 
+        remote_path: impl AsRef<str>, 
+        source_bucket: impl AsRef<str>, 
+        source_path: impl AsRef<str>,
+        sse: Option<&ServerSideEncryption>,
+        source_sse: Option<&ServerSideEncryption>,
+    ) -> Result<()> {
+        let mut headers = sse.map(sse_write_headers).transpose()?.unwrap_or_default();
+        headers.extend(source_sse.map(sse_copy_source_headers).transpose()?.unwrap_or_default());
+        self.copy_object_with_headers(remote_path.as_ref(), source_bucket.as_ref(), source_path.as_ref(), headers)
+    }
+
+    /// copy object from `source_bucket`:`source_path` to `remote_path`, setting its content headers, metadata, 
+    /// storage class and encryption by `options`. The source's content headers and metadata are kept unless `options` set any of them
+    pub fn copy_object_with_options(
+        &self, 
+        remote_path: impl AsRef<str>, 
+        source_bucket: impl AsRef<str>, 
+        source_path: impl AsRef<str>,
+        options: &PutObjectOptions,
+    ) -> Result<()> {
+        self.copy_object_with_headers(remote_path.as_ref(), source_bucket.as_ref(), source_path.as_ref(), copy_object_headers(options)?)
+    }
+
+    /// copy object from `source_bucket`:`source_path` to `remote_path` if the source object meets `conditions`, 
+    /// failing with [CloudRuError::PreconditionFailed] otherwise
+    pub fn copy_object_conditional(
+        &self, 
+        remote_path: impl AsRef<str>, 
+        source_bucket: impl AsRef<str>, 
+        source_path: impl AsRef<str>,
+        conditions: &ObjectConditions,
+    ) -> Result<()> {
+        let headers = condition_headers(conditions, "x-obs-copy-source-")?;
+        self.copy_object_with_headers(remote_path.as_ref(), source_bucket.as_ref(), source_path.as_ref(), headers)
+    }
+
+    fn copy_object_with_headers(&self, remote_path: &str, source_bucket: &str, source_path: &str, headers: HeaderMap) -> Result<()> {
         let request = self.http_client.request(Method::PUT, self.url(remote_path));
         let request: RequestBuilder = self.start_request(request);
-        let request = request.header(
+        let request = request.headers(headers).header(
             "x-obs-copy-source", 
-            format!("/{}/{}", source_bucket.as_ref(), source_path.as_ref())
+            format!("/{}/{}", source_bucket, source_path)
         );
         let request = self.sign_request(request)?;
 
@@ -251,11 +755,117 @@ impl Bucket {
         Ok(())
     }
 
+    /// copy the objects under `source_prefix` in the `source` bucket to this bucket, replacing `source_prefix` of their keys 
+    /// with `target_prefix`. Up to `options.concurrency` objects are copied simultaneously, the ones larger than 
+    /// [COPY_OBJECT_MAX_SIZE] are copied in parts, keeping their content headers and metadata. The prefix is listed 
+    /// page by page as the copy goes; the keys failed to copy are reported without stopping the others, 
+    /// while a failure to list stops the copy and is returned as the error. Within the same bucket, the prefixes 
+    /// must not overlap, e.g. `a/` cannot be copied to `a/b/`
+    pub fn copy_prefix(&self, source: &Bucket, source_prefix: &str, target_prefix: &str, options: &CopyPrefixOptions) -> Result<CopyPrefixReport> {
+        self.transfer_prefix(source, source_prefix, target_prefix, options, false)
+    }
+
+    /// move the objects under `source_prefix` in the `source` bucket to this bucket as [Self::copy_prefix] does, 
+    /// deleting each source object once it is copied
+    pub fn move_prefix(&self, source: &Bucket, source_prefix: &str, target_prefix: &str, options: &CopyPrefixOptions) -> Result<CopyPrefixReport> {
+        self.transfer_prefix(source, source_prefix, target_prefix, options, true)
+    }
+
+    fn transfer_prefix(&self, source: &Bucket, source_prefix: &str, target_prefix: &str, options: &CopyPrefixOptions, delete_source: bool) -> Result<CopyPrefixReport> {
+        check_transfer_prefixes(&source.bucket_name, source_prefix, &self.bucket_name, target_prefix)?;
+        let request = ListObjectsRequest { prefix: Some(source_prefix), ..Default::default() };
+        // the pager stops after a failure, so the workers run out of objects
+        let objects = Mutex::new(source.iter_objects(request));
+        let list_error = Mutex::new(None);
+        let report = Mutex::new(CopyPrefixReport::default());
+
+        let worker = || loop {
+            let next = objects.lock().unwrap_or_else(|e| e.into_inner()).next();
+            let contents = match next {
+                Some(Ok(contents)) => contents,
+                Some(Err(e)) => { *list_error.lock().unwrap_or_else(|e| e.into_inner()) = Some(e); break }
+                None => break,
+            };
+            let rv = self.copy_prefixed_object(source, &contents, source_prefix, target_prefix, options)
+                .and_then(|()| if delete_source { source.delete_object(&contents.key) } else { Ok(()) });
+            let mut report = report.lock().unwrap_or_else(|e| e.into_inner());
+            match rv {
+                Ok(()) => report.copied.push(contents.key),
+                Err(e) => report.failed.push((contents.key, e)),
+            }
+        };
+
+        thread::scope(|s| {
+            for _ in 0..options.concurrency.max(1) {
+                s.spawn(worker);
+            }
+        });
+        if let Some(e) = list_error.into_inner().unwrap_or_else(|e| e.into_inner()) {
+            return Err(e.cx(format!("listing {source_prefix}")))
+        }
+        Ok(report.into_inner().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    fn copy_prefixed_object(&self, source: &Bucket, contents: &ListObjectsContents, source_prefix: &str, target_prefix: &str, options: &CopyPrefixOptions) -> Result<()> {
+        let target_key = format!("{target_prefix}{}", contents.key.strip_prefix(source_prefix).unwrap_or(&contents.key));
+        if contents.size > COPY_OBJECT_MAX_SIZE {
+            // a multipart copy does not keep the content headers and metadata by itself; 
+            // the parts are copied one by one, since the objects are already copied concurrently
+            let object_options = PutObjectOptions::from_meta(&source.get_object_meta(&contents.key)?);
+            let multipart = MultipartUploadOptions { part_size: options.part_size, concurrency: 1 };
+            self.copy_object_multipart_with_options(target_key, &source.bucket_name, &contents.key, contents.size, &object_options, &multipart).map(|_| ())
+        } else {
+            self.copy_object(target_key, &source.bucket_name, &contents.key)
+        }
+    }
+
+    /// pre-signed URL to download the object at `remote_path`, valid for `expires_in`
+    pub fn presign_get(&self, remote_path: impl AsRef<str>, expires_in: Duration) -> Result<Url> {
+        self.presign(Method::GET, remote_path, expires_in)
+    }
+
+    /// pre-signed URL to upload the object at `remote_path`, valid for `expires_in`. 
+    /// The upload must be sent without `Content-Type` and `Content-MD5` headers, since they are not signed
+    pub fn presign_put(&self, remote_path: impl AsRef<str>, expires_in: Duration) -> Result<Url> {
+        self.presign(Method::PUT, remote_path, expires_in)
+    }
+
+    fn presign(&self, method: Method, remote_path: impl AsRef<str>, expires_in: Duration) -> Result<Url> {
+        let mut request = self.http_client.request(method, self.url(remote_path)).build()?;
+        let expires = time::OffsetDateTime::now_utc() + expires_in;
+        let credentials = self.credentials.credentials()?;
+        presign(self.signing_name(), &R { r: &mut request }, expires.unix_timestamp(), &credentials.ak, &credentials.sk, credentials.security_token.as_deref())
+    }
+
+    /// signed form fields for a browser-based POST upload to the bucket, restricted by `policy`
+    pub fn post_policy_form(&self, policy: &PostPolicy) -> Result<PostPolicyForm> {
+        let expiration = time::OffsetDateTime::now_utc() + policy.expires_in;
+        let credentials = self.credentials.credentials()?;
+        let fields = post_policy_fields(&self.bucket_name, policy, expiration, &credentials.ak, &credentials.sk, credentials.security_token.as_deref())?;
+        Ok(PostPolicyForm { url: self.address.base_url.clone(), fields })
+    }
+
     /// get object's metadata
     /// @see https://support.hc.sbercloud.ru/api/obs/obs_04_0084.html
     pub fn get_object_meta(&self, remote_path: impl AsRef<str>) -> Result<ObjectMeta> {
+        self.get_object_meta_with_headers(remote_path.as_ref(), HeaderMap::new())
+    }
+
+    /// get metadata of the object encrypted with `sse`
+    pub fn get_object_meta_encrypted(&self, remote_path: impl AsRef<str>, sse: &ServerSideEncryption) -> Result<ObjectMeta> {
+        self.get_object_meta_with_headers(remote_path.as_ref(), sse_read_headers(sse)?)
+    }
+
+    /// get object's metadata if `conditions` are met, 
+    /// failing with [CloudRuError::NotModified] or [CloudRuError::PreconditionFailed] otherwise
+    pub fn get_object_meta_conditional(&self, remote_path: impl AsRef<str>, conditions: &ObjectConditions) -> Result<ObjectMeta> {
+        self.get_object_meta_with_headers(remote_path.as_ref(), condition_headers(conditions, "")?)
+    }
+
+    fn get_object_meta_with_headers(&self, remote_path: &str, headers: HeaderMap) -> Result<ObjectMeta> {
         let request = self.http_client.request(Method::HEAD, self.url(remote_path));
         let request: RequestBuilder = self.start_request(request);
+        let request = request.headers(headers);
         let request = self.sign_request(request)?;
 
         debug!(request_full=?request);
@@ -266,7 +876,16 @@ impl Bucket {
         Ok(extract_object_meta(result.headers()))                 
     }
 
-    /// get bucket metadata
+    /// whether the bucket exists (and is accessible)
+    pub fn exists(&self) -> Result<bool> {
+        match self.get_bucket_meta() {
+            Ok(_) => Ok(true),
+            Err(CloudRuError::NoSuchBucket(_)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// get bucket metadata, failing with [CloudRuError::NoSuchBucket] if the bucket does not exist
     pub fn get_bucket_meta(&self) -> Result<BucketMeta> {
         let request = self.http_client.head(self.url("/"));
         let request: RequestBuilder = self.start_request(request);
@@ -275,6 +894,9 @@ impl Bucket {
         debug!(request_full=?request);
 
         let result = self.http_client.execute(request)?;
+        if result.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(CloudRuError::NoSuchBucket(self.bucket_name.clone()))
+        }
         bail_on_failure!(result);
 
         Ok(extract_bucket_meta(result.headers()))
@@ -309,16 +931,26 @@ impl Bucket {
         let bucket_meta = bucket.get_bucket_meta()?;
         let fs_type = FsType::from_bucket_meta(&bucket_meta);
          
-        Ok(ObjectWriter { remote_path, bucket, fs_type, pos })
+        Ok(ObjectWriter { remote_path, bucket, fs_type, pos, write_buf: vec![], write_buffer_size: 0 })
     }
 
 
     /// create file-like IO object that track r/w position
     pub fn object_io(&self, remote_path: impl AsRef<str>) -> Result<ObjectIO> {
-        let remote_path = remote_path.as_ref().to_string();
+        self.open_object_io(remote_path.as_ref(), None)
+    }
+
+    /// create file-like IO object over the object encrypted with `sse`. Data written is encrypted with `sse` as well
+    pub fn object_io_encrypted(&self, remote_path: impl AsRef<str>, sse: ServerSideEncryption) -> Result<ObjectIO> {
+        self.open_object_io(remote_path.as_ref(), Some(sse))
+    }
+
+    fn open_object_io(&self, remote_path: &str, encryption: Option<ServerSideEncryption>) -> Result<ObjectIO> {
+        let remote_path = remote_path.to_string();
+        let read_headers = object_io_sse_headers(encryption.as_ref(), false)?;
 
         // yield 0 if it is 404 error
-        let len = match self.get_object_meta(&remote_path) {
+        let len = match self.get_object_meta_with_headers(&remote_path, read_headers) {
             Ok(metadata) => metadata.content_length.ok_or_else(
                 || CloudRuError::UnknownObjectLength(remote_path.clone())
             )?,
@@ -335,7 +967,423 @@ impl Bucket {
         let bucket_meta = bucket.get_bucket_meta()?;
         let fs_type = FsType::from_bucket_meta(&bucket_meta);
          
-        Ok(ObjectIO { remote_path, bucket, fs_type, pos, len })
+        let read_ahead = ReadAhead::new(OBJECT_IO_DEFAULT_READ_AHEAD);
+         
+        Ok(ObjectIO { remote_path, bucket, fs_type, pos, len, read_ahead, write_buf: vec![], write_buffer_size: 0, encryption })
+    }
+
+    /// sync the local directory `local_dir` with the objects under `prefix` in `direction`. 
+    /// The files missing at the destination or differing in size, modification time (the source is newer) or, 
+    /// with `options.checksum`, MD5 are transferred; the extraneous ones are deleted if `options.delete` is set.
+    /// Returns the steps done, or the ones planned if `options.dry_run` is set
+    pub fn sync(&self, local_dir: impl AsRef<Path>, prefix: impl AsRef<str>, direction: SyncDirection, options: &SyncOptions) -> Result<SyncReport> {
+        let local_dir = local_dir.as_ref();
+        let prefix = sync_prefix(prefix.as_ref());
+        let local = local_sync_entries(local_dir)?;
+        let request = ListObjectsRequest { prefix: Some(&prefix), ..Default::default() };
+        let mut remote = vec![];
+        for contents in self.iter_objects(request) {
+            remote.extend(remote_sync_entry(&prefix, &contents?));
+        }
+        let report = plan_sync(direction, local, remote, options, |path| file_md5(&local_dir.join(path)))?;
+        if !options.dry_run {
+            for action in &report.actions {
+                self.sync_action(local_dir, &prefix, action).cxd(|| format!("sync: {action:?}"))?;
+            }
+        }
+        Ok(report)
+    }
+
+    fn sync_action(&self, local_dir: &Path, prefix: &str, action: &SyncAction) -> Result<()> {
+        debug!(?action, "sync");
+        match action {
+            SyncAction::Upload { path, size } => {
+                let (key, local_path) = (format!("{prefix}{path}"), local_dir.join(path));
+                if *size > MULTIPART_DEFAULT_PART_SIZE {
+                    self.upload_file(key, local_path, &MultipartUploadOptions::default()).map(|_| ())
+                } else {
+                    self.put_object(key, File::open(&local_path)?)
+                }
+            }
+            SyncAction::Download { path, .. } => {
+                let (key, local_path) = (format!("{prefix}{path}"), local_dir.join(path));
+                if let Some(parent) = local_path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                // an existing file is replaced only once the download completes
+                let download_path = sync_download_path(&local_path);
+                let rv = File::create(&download_path).map_err(CloudRuError::from)
+                    .and_then(|mut file| self.get_object(key, &mut file));
+                match rv {
+                    Ok(()) => Ok(std::fs::rename(&download_path, &local_path)?),
+                    Err(e) => {
+                        let _ = std::fs::remove_file(&download_path);
+                        Err(e)
+                    }
+                }
+            }
+            SyncAction::DeleteObject { path } => self.delete_object(format!("{prefix}{path}")),
+            SyncAction::DeleteFile { path } => Ok(std::fs::remove_file(local_dir.join(path))?),
+        }
+    }
+
+    /// initiate multipart upload of the object at `remote_path`
+    /// @see https://support.hc.sbercloud.ru/api/obs/obs_04_0098.html
+    pub fn initiate_multipart_upload(&self, remote_path: impl AsRef<str>) -> Result<InitiateMultipartUploadResult> {
+        self.initiate_multipart_upload_with_options(remote_path, &PutObjectOptions::default())
+    }
+
+    /// initiate multipart upload of the object at `remote_path` with content headers, metadata, storage class and encryption set by `options`
+    pub fn initiate_multipart_upload_with_options(&self, remote_path: impl AsRef<str>, options: &PutObjectOptions) -> Result<InitiateMultipartUploadResult> {
+        let url = self.url(remote_path).with_var_key("uploads");
+        let request = self.http_client.request(Method::POST, url);
+        let request: RequestBuilder = self.start_request(request);
+        let request = request.headers(put_object_headers(options)?);
+        let request = self.sign_request(request)?;
+
+        debug!(request_full=?request);
+
+        let result = self.http_client.execute(request)?;
+        bail_on_failure!(result);
+
+        parse_xml(result)
+    }
+
+    /// upload part `part_number` (starting from 1) of the multipart upload `upload_id`, filling it with data read from `input`
+    /// 
+    /// Returns the ETag of the part
+    /// @see https://support.hc.sbercloud.ru/api/obs/obs_04_0099.html
+    pub fn upload_part<I>(&self, remote_path: impl AsRef<str>, upload_id: impl AsRef<str>, part_number: u32, input: I) -> Result<String> 
+    where Body: From<I> {
+        let url = self.url(remote_path)
+            .with_var("partNumber", format!("{part_number}"))
+            .with_var("uploadId", upload_id);
+        let request = self.http_client.request(Method::PUT, url);
+        let request: RequestBuilder = self.start_request(request);
+        let body = Body::from(input);
+        let request = match body.as_bytes() {
+            Some(data) => self.with_content_md5(request, data),
+            None => request,
+        };
+        let request = request.body(body);
+        let request = self.sign_request(request)?;
+
+        debug!(request_full=?request);
+
+        let result = self.http_client.execute(request)?;
+        bail_on_failure!(result);
+
+        let etag = result.headers().get("etag").ok_or(CloudRuError::MissingHeader("ETag"))?;
+        Ok(etag.to_str()?.to_owned())
+    }
+
+    /// fill part `part_number` of the multipart upload `upload_id` with `range` of bytes of the object `source_bucket`:`source_path`
+    /// 
+    /// Returns the ETag of the part
+    pub fn upload_part_copy(
+        &self, 
+        remote_path: impl AsRef<str>, 
+        upload_id: impl AsRef<str>, 
+        part_number: u32, 
+        source_bucket: impl AsRef<str>, 
+        source_path: impl AsRef<str>,
+        range: impl RangeBounds<u64>,
+    ) -> Result<String> {
+        let url = self.url(remote_path)
+            .with_var("partNumber", format!("{part_number}"))
+            .with_var("uploadId", upload_id);
+        let request = self.http_client.request(Method::PUT, url);
+        let request: RequestBuilder = self.start_request(request);
+        let request = request.header("x-obs-copy-source", format!("/{}/{}", source_bucket.as_ref(), source_path.as_ref()));
+        let request = match range_header(range) {
+            Some(range) => request.header("x-obs-copy-source-range", range),
+            None => request,
+        };
+        let request = self.sign_request(request)?;
+
+        debug!(request_full=?request);
+
+        let result = self.http_client.execute(request)?;
+        bail_on_failure!(result);
+
+        let result: CopyPartResult = parse_xml(result)?;
+        Ok(result.etag)
+    }
+
+    /// copy object `source_bucket`:`source_path` of `source_size` bytes to `remote_path` in parts, 
+    /// as required for the objects larger than [COPY_OBJECT_MAX_SIZE]. 
+    /// Unlike [Self::copy_object], the content headers and metadata of the source are not carried over, 
+    /// see [Self::copy_object_multipart_with_options]
+    pub fn copy_object_multipart(
+        &self, 
+        remote_path: impl AsRef<str>, 
+        source_bucket: impl AsRef<str>, 
+        source_path: impl AsRef<str>,
+        source_size: u64,
+        options: &MultipartUploadOptions,
+    ) -> Result<CompleteMultipartUploadResult> {
+        self.copy_object_multipart_with_options(remote_path, source_bucket, source_path, source_size, &PutObjectOptions::default(), options)
+    }
+
+    /// copy object `source_bucket`:`source_path` of `source_size` bytes to `remote_path` in parts, 
+    /// with content headers, metadata, storage class and encryption set by `object_options`, 
+    /// e.g. [PutObjectOptions::from_meta] of the source
+    pub fn copy_object_multipart_with_options(
+        &self, 
+        remote_path: impl AsRef<str>, 
+        source_bucket: impl AsRef<str>, 
+        source_path: impl AsRef<str>,
+        source_size: u64,
+        object_options: &PutObjectOptions,
+        options: &MultipartUploadOptions,
+    ) -> Result<CompleteMultipartUploadResult> {
+        let (remote_path, source_bucket, source_path) = (remote_path.as_ref(), source_bucket.as_ref(), source_path.as_ref());
+        let parts = plan_parts(source_size, options.part_size);
+
+        let upload_id = self.initiate_multipart_upload_with_options(remote_path, object_options)?.upload_id;
+
+        match self.copy_parts(remote_path, &upload_id, source_bucket, source_path, &parts, options.concurrency) {
+            Ok(completed) => self.complete_multipart_upload(remote_path, &upload_id, completed),
+            Err(e) => {
+                if let Err(abort_error) = self.abort_multipart_upload(remote_path, &upload_id) {
+                    warn!(upload_id, %abort_error, "failed to abort multipart copy");
+                }
+                Err(e)
+            }
+        }
+    }
+
+    fn copy_parts(&self, remote_path: &str, upload_id: &str, source_bucket: &str, source_path: &str, parts: &[PartRange], concurrency: usize) -> Result<Vec<CompletedPart>> {
+        let next = AtomicUsize::new(0);
+        let failed = AtomicBool::new(false);
+
+        let worker = || -> Result<Vec<CompletedPart>> {
+            let mut completed = vec![];
+            while !failed.load(Ordering::Relaxed) {
+                let Some(part) = parts.get(next.fetch_add(1, Ordering::Relaxed)) else { break };
+                let range = part.offset..part.offset + part.size;
+                match self.upload_part_copy(remote_path, upload_id, part.part_number, source_bucket, source_path, range) {
+                    Ok(etag) => completed.push(CompletedPart { part_number: part.part_number, etag }),
+                    Err(e) => {
+                        failed.store(true, Ordering::Relaxed);
+                        return Err(e.cx(format!("copying part {}", part.part_number)));
+                    }
+                }
+            }
+            Ok(completed)
+        };
+
+        let results: Vec<Result<Vec<CompletedPart>>> = thread::scope(|s| {
+            let workers: Vec<_> = (0..concurrency.clamp(1, parts.len().max(1)))
+                .map(|_| s.spawn(worker))
+                .collect();
+            workers.into_iter().map(|w| w.join().unwrap_or_else(|e| panic::resume_unwind(e))).collect()
+        });
+
+        let mut completed = vec![];
+        for r in results {
+            completed.extend(r?);
+        }
+        completed.sort_by_key(|p| p.part_number);
+        Ok(completed)
+    }
+
+    /// combine uploaded `parts` into the object at `remote_path`, completing the multipart upload `upload_id`
+    /// @see https://support.hc.sbercloud.ru/api/obs/obs_04_0102.html
+    pub fn complete_multipart_upload(&self, remote_path: impl AsRef<str>, upload_id: impl AsRef<str>, mut parts: Vec<CompletedPart>) -> Result<CompleteMultipartUploadResult> {
+        parts.sort_by_key(|p| p.part_number);
+        let body = serde_xml_rs::to_string(&CompleteMultipartUpload { parts })?;
+
+        let url = self.url(remote_path).with_var("uploadId", upload_id);
+        let request = self.http_client.request(Method::POST, url);
+        let request: RequestBuilder = self.start_request(request);
+        let request = request.header("content-type", "application/xml").body(body);
+        let request = self.sign_request(request)?;
+
+        debug!(request_full=?request);
+
+        let result = self.http_client.execute(request)?;
+        bail_on_failure!(result);
+
+        parse_xml(result)
+    }
+
+    /// abort the multipart upload `upload_id`, deleting the uploaded parts
+    /// @see https://support.hc.sbercloud.ru/api/obs/obs_04_0103.html
+    pub fn abort_multipart_upload(&self, remote_path: impl AsRef<str>, upload_id: impl AsRef<str>) -> Result<()> {
+        let url = self.url(remote_path).with_var("uploadId", upload_id);
+        let request = self.http_client.request(Method::DELETE, url);
+        let request: RequestBuilder = self.start_request(request);
+        let request = self.sign_request(request)?;
+
+        debug!(request_full=?request);
+
+        let result = self.http_client.execute(request)?;
+        bail_on_failure!(result);
+
+        Ok(())
+    }
+
+    /// list the parts uploaded within the multipart upload `upload_id`, starting after `part_number_marker`
+    /// @see https://support.hc.sbercloud.ru/api/obs/obs_04_0101.html
+    pub fn list_parts(&self, remote_path: impl AsRef<str>, upload_id: impl AsRef<str>, part_number_marker: Option<u32>, max_parts: Option<u32>) -> Result<ListPartsResult> {
+        let url = self.url(remote_path)
+            .with_var("uploadId", upload_id)
+            .with_var_opt("part-number-marker", part_number_marker.map(|s| format!("{s}")))
+            .with_var_opt("max-parts", max_parts.map(|s| format!("{s}")));
+        let request = self.http_client.request(Method::GET, url);
+        let request: RequestBuilder = self.start_request(request);
+        let request = self.sign_request(request)?;
+
+        debug!(request_full=?request);
+
+        let result = self.http_client.execute(request)?;
+        bail_on_failure!(result);
+
+        parse_xml(result)
+    }
+
+    /// upload local file `local_path` to `remote_path` using multipart upload.
+    /// 
+    /// The file is split into parts of `options.part_size`, which are uploaded by at most `options.concurrency` threads.
+    /// On failure, the multipart upload is aborted.
+    pub fn upload_file(&self, remote_path: impl AsRef<str>, local_path: impl AsRef<Path>, options: &MultipartUploadOptions) -> Result<CompleteMultipartUploadResult> {
+        let remote_path = remote_path.as_ref();
+        let local_path = local_path.as_ref();
+        let (len, _) = file_size_and_mtime(local_path)?;
+        let parts = plan_parts(len, options.part_size);
+
+        let upload_id = self.initiate_multipart_upload(remote_path)?.upload_id;
+
+        match self.upload_parts(remote_path, &upload_id, local_path, &parts, options.concurrency, &|_| Ok(())) {
+            Ok(completed) => self.complete_multipart_upload(remote_path, &upload_id, completed),
+            Err(e) => {
+                if let Err(abort_error) = self.abort_multipart_upload(remote_path, &upload_id) {
+                    warn!(upload_id, %abort_error, "failed to abort multipart upload");
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// list all parts uploaded within the multipart upload `upload_id`, following the pagination
+    pub fn list_all_parts(&self, remote_path: impl AsRef<str>, upload_id: impl AsRef<str>) -> Result<Vec<PartInfo>> {
+        let (remote_path, upload_id) = (remote_path.as_ref(), upload_id.as_ref());
+        let mut parts = vec![];
+        let mut marker = None;
+        loop {
+            let result = self.list_parts(remote_path, upload_id, marker, None)?;
+            parts.extend(result.parts.unwrap_or_default());
+            match (result.is_truncated, result.next_part_number_marker) {
+                (Some(true), Some(next)) => marker = Some(next),
+                _ => break,
+            }
+        }
+        Ok(parts)
+    }
+
+    /// upload local file `local_path` to `remote_path` using multipart upload, which can be resumed after a failure.
+    /// 
+    /// The upload state is saved to `checkpoint_path` after each uploaded part. If the checkpoint file exists and describes 
+    /// an upload of the same unchanged file to the same object, the upload is resumed: the uploaded parts are listed and 
+    /// only the missing ones are sent. Otherwise a new upload is initiated. The checkpoint file is removed on success.
+    /// 
+    /// Unlike [Self::upload_file], the multipart upload is not aborted on failure.
+    pub fn upload_file_resumable(&self, remote_path: impl AsRef<str>, local_path: impl AsRef<Path>, checkpoint_path: impl AsRef<Path>, options: &MultipartUploadOptions) -> Result<CompleteMultipartUploadResult> {
+        let remote_path = remote_path.as_ref();
+        let local_path = local_path.as_ref();
+        let checkpoint_path = checkpoint_path.as_ref();
+        let (len, mtime) = file_size_and_mtime(local_path)?;
+
+        let mut resumed = None;
+        if let Some(mut checkpoint) = UploadCheckpoint::load(checkpoint_path)? {
+            if checkpoint.matches(&self.bucket_name, remote_path, len, mtime) {
+                let plan = plan_parts(len, checkpoint.part_size);
+                match self.list_all_parts(remote_path, &checkpoint.upload_id) {
+                    Ok(uploaded) => {
+                        let missing = checkpoint.reconcile(&uploaded, &plan);
+                        resumed = Some((checkpoint, missing));
+                    }
+                    Err(e) if e.is_api_not_found() => 
+                        debug!(upload_id=checkpoint.upload_id, "multipart upload not found, starting over"),
+                    Err(e) => return Err(e),
+                }
+            } else if checkpoint.bucket == self.bucket_name {
+                if let Err(abort_error) = self.abort_multipart_upload(&checkpoint.key, &checkpoint.upload_id) {
+                    warn!(upload_id=checkpoint.upload_id, %abort_error, "failed to abort stale multipart upload");
+                }
+            }
+        }
+
+        let (checkpoint, missing) = match resumed {
+            Some(resumed) => resumed,
+            None => {
+                let upload_id = self.initiate_multipart_upload(remote_path)?.upload_id;
+                let part_size = effective_part_size(len, options.part_size);
+                let plan = plan_parts(len, part_size);
+                let checkpoint = UploadCheckpoint { 
+                    bucket: self.bucket_name.clone(), key: remote_path.to_owned(), upload_id, 
+                    file_size: len, file_mtime: mtime, part_size, parts: vec![] 
+                };
+                (checkpoint, plan)
+            }
+        };
+        checkpoint.save(checkpoint_path)?;
+
+        let upload_id = checkpoint.upload_id.clone();
+        let checkpoint = Mutex::new(checkpoint);
+        self.upload_parts(remote_path, &upload_id, local_path, &missing, options.concurrency, &|part| {
+            let mut checkpoint = checkpoint.lock().unwrap_or_else(|e| e.into_inner());
+            checkpoint.add_part(part.clone());
+            checkpoint.save(checkpoint_path)
+        })?;
+
+        let checkpoint = checkpoint.into_inner().unwrap_or_else(|e| e.into_inner());
+        let result = self.complete_multipart_upload(remote_path, &upload_id, checkpoint.parts)?;
+        std::fs::remove_file(checkpoint_path).cxd(|| format!("removing checkpoint {}", checkpoint_path.display()))?;
+        Ok(result)
+    }
+
+    /// upload `parts` of `local_path` on at most `concurrency` threads, calling `on_part` after each uploaded part
+    fn upload_parts(&self, remote_path: &str, upload_id: &str, local_path: &Path, parts: &[PartRange], concurrency: usize, 
+        on_part: &(dyn Fn(&CompletedPart) -> Result<()> + Sync)
+    ) -> Result<Vec<CompletedPart>> {
+        let next = AtomicUsize::new(0);
+        let failed = AtomicBool::new(false);
+
+        let worker = || -> Result<Vec<CompletedPart>> {
+            let mut file = File::open(local_path).cxd(|| format!("opening {}", local_path.display()))?;
+            let mut completed = vec![];
+            while !failed.load(Ordering::Relaxed) {
+                let Some(part) = parts.get(next.fetch_add(1, Ordering::Relaxed)) else { break };
+                let rv = read_part(&mut file, part)
+                    .and_then(|data| self.upload_part(remote_path, upload_id, part.part_number, data))
+                    .map(|etag| CompletedPart { part_number: part.part_number, etag })
+                    .and_then(|completed| on_part(&completed).map(|()| completed));
+                match rv {
+                    Ok(part) => completed.push(part),
+                    Err(e) => {
+                        failed.store(true, Ordering::Relaxed);
+                        return Err(e.cx(format!("uploading part {}", part.part_number)));
+                    }
+                }
+            }
+            Ok(completed)
+        };
+
+        let results: Vec<Result<Vec<CompletedPart>>> = thread::scope(|s| {
+            let workers: Vec<_> = (0..concurrency.clamp(1, parts.len().max(1)))
+                .map(|_| s.spawn(worker))
+                .collect();
+            workers.into_iter().map(|w| w.join().unwrap_or_else(|e| panic::resume_unwind(e))).collect()
+        });
+
+        let mut completed = vec![];
+        for r in results {
+            completed.extend(r?);
+        }
+        completed.sort_by_key(|p| p.part_number);
+        Ok(completed)
     }
 }
 
@@ -385,7 +1433,7 @@ impl Read for ObjectReader {
             self.pos += count;
             Ok(count as usize)
         } else {
-            Err(CloudRuError::API(result.status(), result.text().cx("text")?).into())
+            Err(CloudRuError::from_api_response(result.status(), result.text().cx("text")?).into())
         }
     }
 }
@@ -410,23 +1458,50 @@ pub struct ObjectWriter {
     remote_path: String,
     bucket: Bucket,
     fs_type: FsType,
+    /// position the buffered data is written at
     pos: u64,
+    write_buf: Vec<u8>,
+    write_buffer_size: usize,
 }
 
 impl ObjectWriter {
     /// Synchronizes cached position with the length of the actual object, so that we can resume appending to it.
-    /// The object must exist and must be created in append mode.
+    /// The object must exist and must be created in append mode. Buffered data is flushed first.
     pub fn sync_position(&mut self) -> Result<u64> {
+        self.flush_buffer()?;
         let meta = self.bucket.get_object_meta(&self.remote_path)?;
         self.pos = meta.content_length.ok_or_else(
             || CloudRuError::UnknownObjectLength(self.remote_path.clone())
         )?;
         Ok(self.pos)
     }
-}
 
-impl Write for ObjectWriter {
-    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    /// Write position, including the buffered data
+    pub fn pos(&self) -> u64 { self.pos + self.write_buf.len() as u64 }
+
+    /// Size of the write buffer, in bytes
+    pub fn write_buffer(&self) -> usize { self.write_buffer_size }
+
+    /// Sets size of the write buffer. Written data is accumulated in the buffer and sent as a single request
+    /// when the buffer fills, on [Write::flush], [Self::finish] and drop. Zero (the default) disables buffering, 
+    /// so that each write is sent immediately.
+    pub fn set_write_buffer(&mut self, size: usize) -> Result<()> {
+        if size < self.write_buf.len() { self.flush_buffer()?; }
+        self.write_buffer_size = size;
+        Ok(())
+    }
+
+    /// Flushes the buffered data and returns the final write position.
+    /// 
+    /// Unlike drop, reports the flush error. The buffered data is discarded in this case.
+    pub fn finish(mut self) -> Result<u64> {
+        let rv = self.flush_buffer();
+        self.write_buf.clear();
+        rv.map(|()| self.pos)
+    }
+
+    /// sends `data` at the current position
+    fn send(&mut self, data: &[u8]) -> Result<()> {
         /*
 POST /ObjectName?append&position=Position HTTP/1.1 
 Host: bucketname.obs.region.example.com
@@ -441,22 +1516,50 @@ Date: date
         let url = write_op.modify_url(self.bucket.url(&self.remote_path));
 
         let request = self.bucket.http_client.request(write_op.method, url)
-            .header("Content-Length", format!("{}", buf.len()));
+            .header("Content-Length", format!("{}", data.len()));
         let request: RequestBuilder = self.bucket.start_request(request);
-        let request = request.body(Vec::from(buf));
+        let request = self.bucket.with_content_md5(request, data).body(Vec::from(data));
         let request = self.bucket.sign_request(request)?;
 
         debug!(request_full=?request);
 
         let result = self.bucket.http_client.execute(request).cx("Client::execute")?;
         bail_on_failure!(result);
-        let count = buf.len();
-        self.pos += count as u64;
-        Ok(count)
+        self.pos += data.len() as u64;
+        Ok(())
+    }
+
+    /// sends the buffered data, if any. The data stays buffered on failure.
+    fn flush_buffer(&mut self) -> Result<()> {
+        if self.write_buf.is_empty() { return Ok(()) }
+        let data = std::mem::take(&mut self.write_buf);
+        self.send(&data).inspect_err(|_| self.write_buf = data)
+    }
+}
+
+impl Write for ObjectWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.write_buf.len() + buf.len() > self.write_buffer_size {
+            self.flush_buffer()?;
+        }
+        if buf.len() >= self.write_buffer_size {
+            self.send(buf)?;
+        } else {
+            self.write_buf.extend_from_slice(buf);
+        }
+        Ok(buf.len())
     }
 
     fn flush(&mut self) -> io::Result<()> {
-        Ok(())
+        Ok(self.flush_buffer()?)
+    }
+}
+
+impl Drop for ObjectWriter {
+    fn drop(&mut self) {
+        if let Err(error) = self.flush_buffer() {
+            warn!(remote_path=self.remote_path, %error, "failed to flush write buffer on drop");
+        }
     }
 }
 
@@ -467,41 +1570,74 @@ pub struct ObjectIO {
     remote_path: String,
     bucket: Bucket,
     fs_type: FsType,
+    /// r/w position; the buffered data is written at it
     pos: u64,
+    /// length of the object, not including the buffered data
     len: u64,
+    read_ahead: ReadAhead,
+    write_buf: Vec<u8>,
+    write_buffer_size: usize,
+    encryption: Option<ServerSideEncryption>,
 } 
 
 impl ObjectIO {
     /// Synchronizes cached position with the length of the actual object, so that we can resume appending to it.
-    /// The object must exist and must be created in append mode.
+    /// The object must exist and must be created in append mode. Buffered data is flushed first.
     pub fn sync_position(&mut self) -> Result<u64> {
-        let meta = self.bucket.get_object_meta(&self.remote_path)?;
+        self.flush_buffer()?;
+        let headers = object_io_sse_headers(self.encryption.as_ref(), false)?;
+        let meta = self.bucket.get_object_meta_with_headers(&self.remote_path, headers)?;
         self.len = meta.content_length.ok_or_else(
             || CloudRuError::UnknownObjectLength(self.remote_path.clone())
         )?;
         if self.pos > self.len { self.pos = self.len }
+        self.read_ahead.invalidate();
         Ok(self.len)
     }
 
 
-    /// Read/write position
-    pub fn pos(&self) -> u64 { self.pos }
-    /// Current length of the entire object
-    pub fn len(&self) -> u64 { self.len }
-}
+    /// Read/write position, including the buffered data
+    pub fn pos(&self) -> u64 { self.pos + self.write_buf.len() as u64 }
+    /// Current length of the entire object, including the buffered data
+    pub fn len(&self) -> u64 { self.len.max(self.pos()) }
 
-impl Read for ObjectIO {
-    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        if buf.is_empty() {
-            return Ok(0)
-        }
+    /// Size of the write buffer, in bytes
+    pub fn write_buffer(&self) -> usize { self.write_buffer_size }
 
-        let (first, last) = (self.pos, self.pos + buf.len() as u64 - 1);
+    /// Sets size of the write buffer. Written data is accumulated in the buffer and sent as a single append/modify 
+    /// request when the buffer fills, on [Write::flush], [Self::finish], reads, seeks and drop. 
+    /// Zero (the default) disables buffering, so that each write is sent immediately.
+    pub fn set_write_buffer(&mut self, size: usize) -> Result<()> {
+        if size < self.write_buf.len() { self.flush_buffer()?; }
+        self.write_buffer_size = size;
+        Ok(())
+    }
+
+    /// Flushes the buffered data and returns the final position.
+    /// 
+    /// Unlike drop, reports the flush error. The buffered data is discarded in this case.
+    pub fn finish(mut self) -> Result<u64> {
+        let rv = self.flush_buffer();
+        self.write_buf.clear();
+        rv.map(|()| self.pos)
+    }
+
+    /// Max size of the read-ahead window, in bytes
+    pub fn read_ahead(&self) -> usize { self.read_ahead.max_window() }
+
+    /// Sets max size of the read-ahead window. Reads fetch up to this many bytes at once while the access is sequential,
+    /// the window shrinks on random access. Zero disables read-ahead. 
+    /// The default is [OBJECT_IO_DEFAULT_READ_AHEAD].
+    pub fn set_read_ahead(&mut self, max_window: usize) { self.read_ahead.set_max_window(max_window) }
+
+    /// fetch `len` bytes starting at `pos`
+    fn read_range(&self, pos: u64, len: usize) -> Result<Vec<u8>> {
+        let (first, last) = (pos, pos + len as u64 - 1);
         let range = format!("bytes={first}-{last}");
 
         let request = self.bucket.http_client.request(Method::GET, self.bucket.url(&self.remote_path));
         let request: RequestBuilder = self.bucket.start_request(request);
-        let request = request.header("range", range);
+        let request = request.header("range", range).headers(object_io_sse_headers(self.encryption.as_ref(), false)?);
         let request = self.bucket.sign_request(request)?;
     
         debug!(request_full=?request);
@@ -509,20 +1645,47 @@ impl Read for ObjectIO {
         let mut result = self.bucket.http_client.execute(request).cx("Client::execute")?;
         if result.status().is_success() {
             if result.status().as_u16() != 206 { //Partial content
-                return Err(io::Error::new(io::ErrorKind::Unsupported, "returning ranges not suppoerted"))
+                return Err(CloudRuError::ReturningRangesNotSupported)
             }
-            let mut w = buf;
-            let count = result.copy_to(&mut w).cx("copy_to")?;
-            self.pos += count;
-            Ok(count as usize)
+            let mut data = Vec::with_capacity(len);
+            result.copy_to(&mut data).cx("copy_to")?;
+            Ok(data)
         } else {
-            Err(CloudRuError::API(result.status(), result.text().cx("text")?).into())
+            Err(CloudRuError::from_api_response(result.status(), result.text().cx("text")?))
         }
     }
 }
 
-impl Write for ObjectIO {
-    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+impl Read for ObjectIO {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0)
+        }
+        self.flush_buffer()?;
+
+        let count = self.read_ahead.read_buffered(self.pos, buf);
+        if count > 0 {
+            self.pos += count as u64;
+            return Ok(count)
+        }
+
+        if self.pos >= self.len {
+            return Ok(0)
+        }
+
+        let fetch_len = self.read_ahead.plan_fetch(self.pos, buf.len(), self.len - self.pos);
+        let data = self.read_range(self.pos, fetch_len)?;
+        let count = data.len().min(buf.len());
+        buf[..count].copy_from_slice(&data[..count]);
+        self.read_ahead.fill(self.pos, data);
+        self.pos += count as u64;
+        Ok(count)
+    }
+}
+
+impl ObjectIO {
+    /// sends `data` at the current position
+    fn send(&mut self, data: &[u8]) -> Result<()> {
                 /*
 POST /ObjectName?append&position=Position HTTP/1.1 
 Host: bucketname.obs.region.example.com
@@ -544,12 +1707,14 @@ Date: date
          */
         let write_op= self.fs_type.eval_write_op(self.pos, self.len)?;
         let url = write_op.modify_url(self.bucket.url(&self.remote_path));
-        let data_len = buf.len();
+        let data_len = data.len();
+        self.read_ahead.invalidate();
 
         let request = self.bucket.http_client.request(write_op.method, url)
-            .header("Content-Length", format!("{data_len}"));
+            .header("Content-Length", format!("{data_len}"))
+            .headers(object_io_sse_headers(self.encryption.as_ref(), self.len == 0)?);
         let request: RequestBuilder = self.bucket.start_request(request);
-        let request = request.body(Vec::from(buf));
+        let request = self.bucket.with_content_md5(request, data).body(Vec::from(data));
         let request = self.bucket.sign_request(request)?;
 
         debug!(request_full=?request);
@@ -558,17 +1723,46 @@ Date: date
         bail_on_failure!(result);
         self.pos += data_len as u64;
         if self.pos > self.len { self.len = self.pos }
-        Ok(data_len)
+        Ok(())
+    }
+
+    /// sends the buffered data, if any. The data stays buffered on failure.
+    fn flush_buffer(&mut self) -> Result<()> {
+        if self.write_buf.is_empty() { return Ok(()) }
+        let data = std::mem::take(&mut self.write_buf);
+        self.send(&data).inspect_err(|_| self.write_buf = data)
+    }
+}
 
+impl Write for ObjectIO {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.write_buf.len() + buf.len() > self.write_buffer_size {
+            self.flush_buffer()?;
+        }
+        if buf.len() >= self.write_buffer_size {
+            self.send(buf)?;
+        } else {
+            self.write_buf.extend_from_slice(buf);
+        }
+        Ok(buf.len())
     }
 
     fn flush(&mut self) -> io::Result<()> {
-        Ok(())
+        Ok(self.flush_buffer()?)
+    }
+}
+
+impl Drop for ObjectIO {
+    fn drop(&mut self) {
+        if let Err(error) = self.flush_buffer() {
+            warn!(remote_path=self.remote_path, %error, "failed to flush write buffer on drop");
+        }
     }
 }
 
 impl io::Seek for ObjectIO {
     fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        self.flush_buffer()?;
         self.pos = match pos {
             io::SeekFrom::Start(pos) =>
                 pos,
@@ -5,13 +5,17 @@ use std::result::Result;
 
 #[derive(Debug)]
 pub enum ParameterKind {
-    S3BucketUrl
+    S3BucketUrl,
+    AddressingStyle,
+    OverlappingPrefixes,
 }
 
 impl fmt::Display for ParameterKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::S3BucketUrl => write!(f, "S3 bucket url")
+            Self::S3BucketUrl => write!(f, "S3 bucket url"),
+            Self::AddressingStyle => write!(f, "OBS addressing style"),
+            Self::OverlappingPrefixes => write!(f, "overlapping source and target prefixes in the same bucket"),
         }
     }
 }
@@ -24,6 +28,9 @@ pub enum CloudRuError {
     #[error("reqwest: header value")]
     ReqwestHeaderValue(#[from] reqwest::header::InvalidHeaderValue),
 
+    #[error("reqwest: header name")]
+    ReqwestHeaderName(#[from] reqwest::header::InvalidHeaderName),
+
     #[error("reqwest: header to string conversion")]
     ReqwestUrl(#[from] url::ParseError),
 
@@ -33,6 +40,15 @@ pub enum CloudRuError {
     #[error("HC API: code={0}, msg='{1}'")]
     API(reqwest::StatusCode, String),
 
+    #[error("HC API: no such bucket `{0}`")]
+    NoSuchBucket(String),
+
+    #[error("HC API: not modified")]
+    NotModified,
+
+    #[error("HC API: precondition failed, msg='{0}'")]
+    PreconditionFailed(String),
+
     #[error("time conversion: {0}")]
     Time(#[from] time::error::Error),
 
@@ -69,12 +85,27 @@ pub enum CloudRuError {
     #[error("Missing project_id")]
     MissingProjectId,
 
+    #[error("Missing credentials: {0}")]
+    MissingCredentials(String),
+
     #[error("Returning ranges not supported")]
     ReturningRangesNotSupported,
 
     #[error("fs write op is inconsistent with the pos/length")]
     InconsistentFsOp { verb: &'static str, pos: u64, len: u64 },
 
+    #[error("io: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("checksum mismatch: ETag {expected}, computed MD5 {actual}")]
+    ChecksumMismatch { expected: String, actual: String },
+
+    #[error("Missing response header `{0}`")]
+    MissingHeader(&'static str),
+
+    #[error("Invalid response: {0}")]
+    InvalidResponse(String),
+
     #[error("[{0}] {1}")]
     Context(String, Box<CloudRuError>),
 
@@ -100,9 +131,36 @@ impl CloudRuError {
         }
     }
 
+    /// error for the API response with the failure `status`. 
+    /// 304 and 412 responses to conditional requests yield [Self::NotModified] and [Self::PreconditionFailed]
+    pub fn from_api_response(status: reqwest::StatusCode, text: String) -> Self {
+        match status.as_u16() {
+            304 => Self::NotModified,
+            412 => Self::PreconditionFailed(text),
+            _ => Self::API(status, text),
+        }
+    }
+
+    pub fn is_not_modified(&self) -> bool {
+        matches!(self.decx(), Self::NotModified)
+    }
+
+    pub fn is_precondition_failed(&self) -> bool {
+        matches!(self.decx(), Self::PreconditionFailed(_))
+    }
+
     pub fn is_api_not_found(&self) -> bool{
         match self.decx() {
             Self::API(n, _) if *n == 404 => true,
+            Self::NoSuchBucket(_) => true,
+            _ => false
+        }
+    }
+
+    /// API error with the `code` error code in the response body, e.g. `NoSuchLifecycleConfiguration`
+    pub fn is_api_error_code(&self, code: &str) -> bool {
+        match self.decx() {
+            Self::API(_, text) => text.contains(&format!("<Code>{code}</Code>")),
             _ => false
         }
     }
@@ -110,6 +168,9 @@ impl CloudRuError {
     pub fn api_status(&self) -> Option<u16>{
         match self.decx() {
             Self::API(n, _) => Some((*n).into()),
+            Self::NoSuchBucket(_) => Some(404),
+            Self::NotModified => Some(304),
+            Self::PreconditionFailed(_) => Some(412),
             _ => None
         }
     }
@@ -7,7 +7,10 @@ pub mod nonblocking;
 pub mod blocking;
 
 pub use error::{CloudRuError, Cx};
-pub use shared::{config::{self, Config}, security::Credentials};
+pub use shared::{config::{self, Config}, security::{
+    Credentials, CredentialsProvider, SharedCredentialsProvider, 
+    EnvCredentialsProvider, IniCredentialsProvider, RefreshingCredentialsProvider
+}};
 pub use serde_json::Value as JsonValue;
 pub use serde_json::to_writer_pretty as json_to_writer_pretty;
 
@@ -2,4 +2,5 @@
 
 pub mod obs;
 pub mod fg;
-pub mod dli;
\ No newline at end of file
+pub mod dli;
+pub mod iam;
\ No newline at end of file
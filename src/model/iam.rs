@@ -0,0 +1,63 @@
+use serde_derive::Deserialize;
+
+/// Identity to obtain an IAM token for
+#[derive(Clone)]
+pub enum IamIdentity {
+    /// IAM user `user_name` of account `domain_name`, authenticated by password
+    Password { user_name: String, password: String, domain_name: String },
+    /// Agency `agency_name` of account `domain_name`, assumed by the AK/SK the token request is signed with
+    Agency { domain_name: String, agency_name: String, duration_seconds: Option<u32> },
+}
+
+impl std::fmt::Debug for IamIdentity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Password { user_name, domain_name, .. } => f.debug_struct("Password")
+                .field("user_name", user_name)
+                .field("domain_name", domain_name)
+                .finish_non_exhaustive(),
+            Self::Agency { domain_name, agency_name, duration_seconds } => f.debug_struct("Agency")
+                .field("domain_name", domain_name)
+                .field("agency_name", agency_name)
+                .field("duration_seconds", duration_seconds)
+                .finish(),
+        }
+    }
+}
+
+/// Scope of an IAM token
+#[derive(Debug, Clone)]
+pub enum TokenScope {
+    Project { id: String },
+    ProjectName { name: String },
+    Domain { name: String },
+}
+
+/// Response body of `POST /v3/auth/tokens`; the token itself is returned in the `X-Subject-Token` header
+#[derive(Deserialize, Debug)]
+pub struct TokenResponse {
+    pub token: TokenInfo,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct TokenInfo {
+    pub expires_at: String,
+    pub issued_at: Option<String>,
+    #[serde(default)]
+    pub methods: Vec<String>,
+}
+
+/// IAM token along with its expiration time
+#[derive(Clone)]
+pub struct IssuedToken {
+    pub token: String,
+    pub expires_at: time::OffsetDateTime,
+}
+
+impl std::fmt::Debug for IssuedToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IssuedToken")
+            .field("expires_at", &self.expires_at)
+            .finish_non_exhaustive()
+    }
+}
@@ -1,4 +1,7 @@
-use serde_derive::Deserialize;
+use std::{collections::BTreeMap, time::Duration};
+
+use serde_derive::{Deserialize, Serialize};
+use url::Url;
 
 #[derive(Debug, Default)]
 pub struct ListObjectsRequest<'t> {
@@ -60,11 +63,36 @@ pub struct ListObjectsResult {
     #[serde(rename="NextMarker")]
     pub next_marker: Option<String>,
 
-    //#[serde(rename="CommonPrefixes")]
-    //pub common_prefixes: Option<String>,
-
     #[serde(rename="Contents")]
     pub contents: Option<Vec<ListObjectsContents>>,
+
+    /// Prefixes up to and including the delimiter, grouping the keys not listed in `contents`. 
+    /// Returned when the delimiter is specified only
+    #[serde(rename="CommonPrefixes")]
+    pub common_prefixes: Option<Vec<CommonPrefix>>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CommonPrefix {
+    #[serde(rename="Prefix")]
+    pub prefix: String,
+}
+
+/// Entry of a delimiter listing: either an object or a common prefix ("directory")
+#[derive(Debug)]
+pub enum ListEntry {
+    Object(ListObjectsContents),
+    Prefix(String),
+}
+
+impl ListEntry {
+    /// Object key or prefix
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Object(c) => &c.key,
+            Self::Prefix(p) => p,
+        }
+    }
 }
 
 #[derive(Deserialize, Debug)]
@@ -84,7 +112,7 @@ pub struct ListObjectsContents {
     /// the object is downloaded, it indicates that the object content has been changed. 
     /// The ETag reflects changes to the object content, rather than the object metadata. 
     /// An uploaded object or copied object has a unique ETag after being encrypted using MD5.
-    #[serde(rename="ETag")]
+    #[serde(rename="ETag", default)]
     pub etag: String,
     
     /// Object type. This parameter is returned when the object type is not Normal.
@@ -92,11 +120,11 @@ pub struct ListObjectsContents {
     pub type_: Option<String>,
     
     /// Object size in bytes
-    #[serde(rename="Size")]
+    #[serde(rename="Size", default)]
     pub size: u64,
 
     /// Storage class of an object. Value options: `STANDARD`, `WARM`, `COLD`
-    #[serde(rename="StorageClass")]
+    #[serde(rename="StorageClass", default)]
     pub storage_class: String,
 
     /// User information, including the domain ID and name of the object owner
@@ -116,14 +144,14 @@ pub struct ListObjectsContents {
 
 
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct Owner {
     /// Domain ID of the object owner
     #[serde(rename="ID")]
     pub id: String,
 
     /// Name of the object owner
-    #[serde(rename="DisplayName")]
+    #[serde(rename="DisplayName", skip_serializing_if="Option::is_none")]
     pub display_name: Option<String>,
 }
 
@@ -177,6 +205,114 @@ pub struct ListObjectVersionsResult {
 
 }
 
+/// Entry of a version listing
+#[derive(Debug)]
+pub enum ObjectVersionEntry {
+    /// Object version
+    Version(ListObjectsContents),
+    /// Deletion marker
+    DeleteMarker(ListObjectsContents),
+}
+
+impl ObjectVersionEntry {
+    pub fn contents(&self) -> &ListObjectsContents {
+        match self {
+            Self::Version(c) | Self::DeleteMarker(c) => c
+        }
+    }
+
+    pub fn is_delete_marker(&self) -> bool { matches!(self, Self::DeleteMarker(_)) }
+}
+
+/// Max number of objects deleted by a single multi-object delete request
+pub const DELETE_OBJECTS_MAX_KEYS: usize = 1000;
+
+/// Object (version) to delete with `Bucket::delete_objects`
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ObjectIdentifier {
+    #[serde(rename="Key")]
+    pub key: String,
+
+    /// Version to delete. The current version is deleted (a deletion marker is created in versioned buckets) if unset
+    #[serde(rename="VersionId", skip_serializing_if="Option::is_none")]
+    pub version_id: Option<String>,
+}
+
+impl From<String> for ObjectIdentifier {
+    fn from(key: String) -> Self { Self { key, version_id: None } }
+}
+
+impl From<&str> for ObjectIdentifier {
+    fn from(key: &str) -> Self { Self { key: key.to_owned(), version_id: None } }
+}
+
+/// Multi-object delete request body
+#[derive(Serialize, Debug)]
+#[serde(rename="Delete")]
+pub struct DeleteObjectsRequest {
+    /// Report failures only
+    #[serde(rename="Quiet")]
+    pub quiet: bool,
+
+    #[serde(rename="Object")]
+    pub objects: Vec<ObjectIdentifier>,
+}
+
+/// Result of a multi-object delete
+#[derive(Deserialize, Debug, Default)]
+pub struct DeleteObjectsResult {
+    /// Per-key results, in the order returned by the server. Successful deletions are omitted in quiet mode
+    #[serde(rename="#content", default)]
+    pub results: Vec<DeleteObjectResult>,
+}
+
+impl DeleteObjectsResult {
+    pub fn deleted(&self) -> impl Iterator<Item = &DeletedObject> {
+        self.results.iter().filter_map(|r| match r { DeleteObjectResult::Deleted(d) => Some(d), _ => None })
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &DeleteObjectError> {
+        self.results.iter().filter_map(|r| match r { DeleteObjectResult::Error(e) => Some(e), _ => None })
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub enum DeleteObjectResult {
+    Deleted(DeletedObject),
+    Error(DeleteObjectError),
+}
+
+#[derive(Deserialize, Debug)]
+pub struct DeletedObject {
+    #[serde(rename="Key")]
+    pub key: String,
+
+    #[serde(rename="VersionId")]
+    pub version_id: Option<String>,
+
+    /// Whether a deletion marker was created or deleted
+    #[serde(rename="DeleteMarker")]
+    pub delete_marker: Option<bool>,
+
+    /// Version ID of the deletion marker created or deleted
+    #[serde(rename="DeleteMarkerVersionId")]
+    pub delete_marker_version_id: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct DeleteObjectError {
+    #[serde(rename="Key")]
+    pub key: String,
+
+    #[serde(rename="VersionId")]
+    pub version_id: Option<String>,
+
+    #[serde(rename="Code")]
+    pub code: String,
+
+    #[serde(rename="Message")]
+    pub message: Option<String>,
+}
 
 /// Object metadata returned by [obs::Bucket::get_object_meta]
 #[derive(Debug)]
@@ -187,6 +323,19 @@ pub struct ObjectMeta {
     pub content_type: Option<String>,
     /// Date the object was last modified, like "WED, 01 Jul 2015 01:19:21 GMT"
     pub last_modified: Option<String>,
+    /// ETag of the object, quoted
+    pub etag: Option<String>,
+    /// Cache-Control
+    pub cache_control: Option<String>,
+    /// Content-Disposition
+    pub content_disposition: Option<String>,
+    /// Content-Encoding
+    pub content_encoding: Option<String>,
+
+    /// x-obs-meta-*
+    /// 
+    /// User metadata, keyed by the header name without the `x-obs-meta-` prefix (lowercased)
+    pub metadata: BTreeMap<String, String>,
 
     /// x-obs-expiration
     /// 
@@ -237,6 +386,34 @@ pub struct ObjectMeta {
     /// 
     /// Type: string
     pub storage_class: Option<String>,
+
+    /// x-obs-server-side-encryption
+    /// 
+    /// Server-side encryption algorithm: `kms` or `AES256` (SSE-OBS). Returned for SSE-KMS and SSE-OBS encrypted objects.
+    /// 
+    /// Type: string
+    pub server_side_encryption: Option<String>,
+
+    /// x-obs-server-side-encryption-kms-key-id
+    /// 
+    /// ID of the KMS master key used for SSE-KMS encryption.
+    /// 
+    /// Type: string
+    pub sse_kms_key_id: Option<String>,
+
+    /// x-obs-server-side-encryption-customer-algorithm
+    /// 
+    /// Encryption algorithm used for SSE-C encryption, `AES256`.
+    /// 
+    /// Type: string
+    pub sse_customer_algorithm: Option<String>,
+
+    /// x-obs-server-side-encryption-customer-key-MD5
+    /// 
+    /// Base64-encoded MD5 digest of the key used for SSE-C encryption.
+    /// 
+    /// Type: string
+    pub sse_customer_key_md5: Option<String>,
 }
 
 
@@ -256,3 +433,933 @@ pub struct BucketMeta {
     /// x-obs-az-redundancy
     pub az_redundancy: Option<String>,
 }
+
+/// Response body of [obs::ObsClient::list_buckets]
+#[derive(Deserialize, Debug)]
+#[serde(rename="ListAllMyBucketsResult")]
+pub struct ListBucketsResult {
+    #[serde(rename="Owner")]
+    pub owner: Option<Owner>,
+
+    #[serde(rename="Buckets", default)]
+    pub buckets: BucketList,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct BucketList {
+    #[serde(rename="Bucket", default)]
+    pub buckets: Vec<BucketInfo>,
+}
+
+/// Bucket as listed by [obs::ObsClient::list_buckets]
+#[derive(Deserialize, Debug, Clone)]
+pub struct BucketInfo {
+    #[serde(rename="Name")]
+    pub name: String,
+
+    /// Time (UTC) when the bucket was created
+    #[serde(rename="CreationDate")]
+    pub creation_date: String,
+
+    /// Region of the bucket
+    #[serde(rename="Location")]
+    pub location: Option<String>,
+
+    /// `OBJECT` for a bucket, `POSIX` for a parallel file system
+    #[serde(rename="BucketType")]
+    pub bucket_type: Option<String>,
+}
+
+/// Options of [obs::ObsClient::create_bucket]
+#[derive(Debug, Clone, Default)]
+pub struct CreateBucketOptions {
+    /// Region to create the bucket in, the endpoint's default one if unset
+    pub location: Option<String>,
+    /// Default storage class of the objects
+    pub storage_class: Option<StorageClass>,
+    /// Create a parallel file system (PFS) rather than an object bucket
+    pub file_interface: bool,
+    pub acl: Option<CannedAcl>,
+}
+
+impl CreateBucketOptions {
+    pub fn new() -> Self { Self::default() }
+
+    pub fn location(mut self, location: &str) -> Self { self.location = Some(location.to_owned()); self }
+
+    pub fn storage_class(mut self, storage_class: StorageClass) -> Self { self.storage_class = Some(storage_class); self }
+
+    pub fn file_interface(mut self, enabled: bool) -> Self { self.file_interface = enabled; self }
+
+    pub fn acl(mut self, acl: CannedAcl) -> Self { self.acl = Some(acl); self }
+}
+
+/// Request body of [obs::ObsClient::create_bucket]
+#[derive(Serialize, Debug)]
+#[serde(rename="CreateBucketConfiguration")]
+pub struct CreateBucketConfiguration {
+    #[serde(rename="Location")]
+    pub location: String,
+}
+
+/// How [obs::Bucket] requests address the bucket; set with the `addressing_style` key of the `[obs]` config section
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AddressingStyle {
+    /// `https://{bucket}.{endpoint-host}/{key}`
+    #[default]
+    VirtualHosted,
+    /// `https://{endpoint-host}/{bucket}/{key}`, for S3-compatible stand-ins, private endpoints 
+    /// and bucket names containing dots
+    Path,
+    /// `https://{endpoint-host}/{key}`, the endpoint being a custom domain bound to the bucket
+    CustomDomain,
+}
+
+impl std::str::FromStr for AddressingStyle {
+    type Err = crate::CloudRuError;
+    fn from_str(s: &str) -> crate::Result<Self> {
+        match s {
+            "virtual-hosted" | "virtual" => Ok(Self::VirtualHosted),
+            "path" => Ok(Self::Path),
+            "custom-domain" => Ok(Self::CustomDomain),
+            _ => Err(crate::CloudRuError::Parameter(crate::error::ParameterKind::AddressingStyle)),
+        }
+    }
+}
+
+/// Result of [obs::Bucket::initiate_multipart_upload]
+#[derive(Deserialize, Debug)]
+pub struct InitiateMultipartUploadResult {
+    /// Name of the bucket to which the multipart upload was initiated
+    #[serde(rename="Bucket")]
+    pub bucket: String,
+
+    /// Object name
+    #[serde(rename="Key")]
+    pub key: String,
+
+    /// ID of the multipart upload. It is used in the follow-up part uploads.
+    #[serde(rename="UploadId")]
+    pub upload_id: String,
+}
+
+/// An uploaded part, as listed in the request of [obs::Bucket::complete_multipart_upload]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CompletedPart {
+    /// Part number, starting from 1
+    #[serde(rename="PartNumber")]
+    pub part_number: u32,
+
+    /// ETag returned when the part was uploaded
+    #[serde(rename="ETag")]
+    pub etag: String,
+}
+
+/// Request body of [obs::Bucket::complete_multipart_upload]
+#[derive(Serialize, Debug)]
+#[serde(rename="CompleteMultipartUpload")]
+pub struct CompleteMultipartUpload {
+    #[serde(rename="Part")]
+    pub parts: Vec<CompletedPart>,
+}
+
+/// Result of [obs::Bucket::complete_multipart_upload]
+#[derive(Deserialize, Debug)]
+pub struct CompleteMultipartUploadResult {
+    /// Path of the combined object
+    #[serde(rename="Location")]
+    pub location: Option<String>,
+
+    /// Bucket in which parts are combined
+    #[serde(rename="Bucket")]
+    pub bucket: String,
+
+    /// Object name
+    #[serde(rename="Key")]
+    pub key: String,
+
+    /// ETag calculated from the ETags of the parts. Differs from MD5 of the whole object.
+    #[serde(rename="ETag")]
+    pub etag: String,
+}
+
+/// Result of [obs::Bucket::list_parts]
+#[derive(Deserialize, Debug)]
+pub struct ListPartsResult {
+    /// Bucket name
+    #[serde(rename="Bucket")]
+    pub bucket: String,
+
+    /// Object name
+    #[serde(rename="Key")]
+    pub key: String,
+
+    /// Upload ID
+    #[serde(rename="UploadId")]
+    pub upload_id: String,
+
+    /// Part number after which listing starts
+    #[serde(rename="PartNumberMarker")]
+    pub part_number_marker: Option<u32>,
+
+    /// Part number to use as the `part_number_marker` of the next request, if the listing is truncated
+    #[serde(rename="NextPartNumberMarker")]
+    pub next_part_number_marker: Option<u32>,
+
+    /// Maximum number of parts returned
+    #[serde(rename="MaxParts")]
+    pub max_parts: Option<u32>,
+
+    /// Indicates whether the returned list of parts is truncated
+    #[serde(rename="IsTruncated")]
+    pub is_truncated: Option<bool>,
+
+    /// Uploaded parts
+    #[serde(rename="Part")]
+    pub parts: Option<Vec<PartInfo>>,
+}
+
+/// A part of the multipart upload, as returned by [obs::Bucket::list_parts]
+#[derive(Deserialize, Debug, Clone)]
+pub struct PartInfo {
+    /// Part number
+    #[serde(rename="PartNumber")]
+    pub part_number: u32,
+
+    /// Time (UTC) when the part was uploaded
+    #[serde(rename="LastModified")]
+    pub last_modified: Option<String>,
+
+    /// ETag of the part
+    #[serde(rename="ETag")]
+    pub etag: String,
+
+    /// Size of the part in bytes
+    #[serde(rename="Size")]
+    pub size: u64,
+}
+
+/// Parameters of the high-level multipart upload (see [obs::Bucket::upload_file])
+#[derive(Debug, Clone)]
+pub struct MultipartUploadOptions {
+    /// Size of each part except the last one. Raised automatically if the object would not fit into
+    /// [MULTIPART_MAX_PARTS] parts.
+    pub part_size: u64,
+    /// Max number of parts uploaded simultaneously
+    pub concurrency: usize,
+}
+
+impl Default for MultipartUploadOptions {
+    fn default() -> Self {
+        Self { part_size: MULTIPART_DEFAULT_PART_SIZE, concurrency: 4 }
+    }
+}
+
+/// State of a resumable multipart upload, persisted in the checkpoint file after each uploaded part
+/// (see [obs::Bucket::upload_file_resumable])
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UploadCheckpoint {
+    /// Bucket name
+    pub bucket: String,
+    /// Object name
+    pub key: String,
+    /// ID of the multipart upload
+    pub upload_id: String,
+    /// Size of the local file being uploaded
+    pub file_size: u64,
+    /// Modification time of the local file (seconds since UNIX epoch), if available
+    pub file_mtime: Option<u64>,
+    /// Part size the file is split with
+    pub part_size: u64,
+    /// Parts uploaded so far
+    pub parts: Vec<CompletedPart>,
+}
+
+/// Content headers, user metadata, storage class and encryption of an object being put or copied
+/// (see [obs::Bucket::put_object_with_options], [obs::Bucket::copy_object_with_options])
+#[derive(Debug, Clone, Default)]
+pub struct PutObjectOptions {
+    pub content_type: Option<String>,
+    pub cache_control: Option<String>,
+    pub content_disposition: Option<String>,
+    pub content_encoding: Option<String>,
+    /// User metadata, sent as `x-obs-meta-*` headers. Names and values must be ASCII
+    pub metadata: BTreeMap<String, String>,
+    pub storage_class: Option<StorageClass>,
+    pub encryption: Option<ServerSideEncryption>,
+}
+
+impl PutObjectOptions {
+    pub fn new() -> Self { Self::default() }
+
+    pub fn content_type(mut self, content_type: &str) -> Self { self.content_type = Some(content_type.to_owned()); self }
+
+    pub fn cache_control(mut self, cache_control: &str) -> Self { self.cache_control = Some(cache_control.to_owned()); self }
+
+    pub fn content_disposition(mut self, content_disposition: &str) -> Self { self.content_disposition = Some(content_disposition.to_owned()); self }
+
+    pub fn content_encoding(mut self, content_encoding: &str) -> Self { self.content_encoding = Some(content_encoding.to_owned()); self }
+
+    /// Add user metadata `name`=`value`
+    pub fn meta(mut self, name: &str, value: &str) -> Self { self.metadata.insert(name.to_owned(), value.to_owned()); self }
+
+    pub fn storage_class(mut self, storage_class: StorageClass) -> Self { self.storage_class = Some(storage_class); self }
+
+    pub fn encryption(mut self, encryption: ServerSideEncryption) -> Self { self.encryption = Some(encryption); self }
+
+    /// whether a copy with these options must replace the content headers and the metadata of the source object
+    pub fn replaces_metadata(&self) -> bool {
+        self.content_type.is_some() || self.cache_control.is_some() || self.content_disposition.is_some() 
+            || self.content_encoding.is_some() || !self.metadata.is_empty()
+    }
+
+    /// Content headers and user metadata of the object described by `meta`, as set on a copy of it
+    pub fn from_meta(meta: &ObjectMeta) -> Self {
+        Self {
+            content_type: meta.content_type.clone(),
+            cache_control: meta.cache_control.clone(),
+            content_disposition: meta.content_disposition.clone(),
+            content_encoding: meta.content_encoding.clone(),
+            metadata: meta.metadata.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Preconditions of a conditional get, head or copy (see [obs::Bucket::get_object_conditional]). 
+/// When they are not met, the request fails with [crate::CloudRuError::NotModified] (`if_none_match`, `if_modified_since` on get and head)
+/// or [crate::CloudRuError::PreconditionFailed]
+#[derive(Debug, Clone, Default)]
+pub struct ObjectConditions {
+    /// proceed only if the object's ETag matches
+    pub if_match: Option<String>,
+    /// proceed only if the object's ETag differs
+    pub if_none_match: Option<String>,
+    /// proceed only if the object was modified after the time
+    pub if_modified_since: Option<time::OffsetDateTime>,
+    /// proceed only if the object was not modified after the time
+    pub if_unmodified_since: Option<time::OffsetDateTime>,
+}
+
+impl ObjectConditions {
+    pub fn new() -> Self { Self::default() }
+
+    pub fn if_match(mut self, etag: &str) -> Self { self.if_match = Some(etag.to_owned()); self }
+
+    pub fn if_none_match(mut self, etag: &str) -> Self { self.if_none_match = Some(etag.to_owned()); self }
+
+    pub fn if_modified_since(mut self, time: time::OffsetDateTime) -> Self { self.if_modified_since = Some(time); self }
+
+    pub fn if_unmodified_since(mut self, time: time::OffsetDateTime) -> Self { self.if_unmodified_since = Some(time); self }
+}
+
+/// Direction of [obs::Bucket::sync]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncDirection {
+    /// Local directory to the bucket prefix
+    Upload,
+    /// Bucket prefix to the local directory
+    Download,
+}
+
+/// Options of [obs::Bucket::sync]
+#[derive(Debug, Clone, Default)]
+pub struct SyncOptions {
+    /// Delete files (objects) at the destination that do not exist at the source
+    pub delete: bool,
+    /// Only report what would be done, without transferring or deleting anything
+    pub dry_run: bool,
+    /// Compare files of the same size by MD5 of the local file and ETag of the object rather than by modification time.
+    /// Objects whose ETag is not an MD5 (e.g. uploaded in parts) are compared by modification time anyway
+    pub checksum: bool,
+}
+
+/// Step of a sync. `path` is relative to both the local directory and the bucket prefix, `/`-separated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyncAction {
+    Upload { path: String, size: u64 },
+    Download { path: String, size: u64 },
+    DeleteObject { path: String },
+    DeleteFile { path: String },
+}
+
+/// Steps performed by [obs::Bucket::sync], or planned ones if it is a dry run
+#[derive(Debug, Clone, Default)]
+pub struct SyncReport {
+    pub actions: Vec<SyncAction>,
+    /// Number of files found up to date
+    pub unchanged: usize,
+    pub dry_run: bool,
+}
+
+impl SyncReport {
+    /// Total size of the files uploaded or downloaded
+    pub fn transferred_bytes(&self) -> u64 {
+        self.actions.iter().map(|a| match a {
+            SyncAction::Upload { size, .. } | SyncAction::Download { size, .. } => *size,
+            _ => 0,
+        }).sum()
+    }
+}
+
+/// Response body of [obs::Bucket::upload_part_copy]
+#[derive(Deserialize, Debug)]
+#[serde(rename="CopyPartResult")]
+pub struct CopyPartResult {
+    #[serde(rename="LastModified")]
+    pub last_modified: String,
+
+    /// ETag of the part
+    #[serde(rename="ETag")]
+    pub etag: String,
+}
+
+/// Options of [obs::Bucket::copy_prefix] and [obs::Bucket::move_prefix]
+#[derive(Debug, Clone)]
+pub struct CopyPrefixOptions {
+    /// Max number of objects copied simultaneously
+    pub concurrency: usize,
+    /// Part size the objects larger than [COPY_OBJECT_MAX_SIZE] are copied with
+    pub part_size: u64,
+}
+
+impl Default for CopyPrefixOptions {
+    fn default() -> Self {
+        Self { concurrency: 4, part_size: MULTIPART_DEFAULT_PART_SIZE }
+    }
+}
+
+/// Result of [obs::Bucket::copy_prefix] and [obs::Bucket::move_prefix]
+#[derive(Debug, Default)]
+pub struct CopyPrefixReport {
+    /// Source keys copied (and deleted, if moved)
+    pub copied: Vec<String>,
+    /// Source keys failed to copy or delete, with the errors
+    pub failed: Vec<(String, crate::CloudRuError)>,
+}
+
+impl CopyPrefixReport {
+    pub fn is_complete(&self) -> bool { self.failed.is_empty() }
+}
+
+/// Max size of an object copied by a single request, larger ones are copied in parts
+pub const COPY_OBJECT_MAX_SIZE: u64 = 5 * 1024 * 1024 * 1024;
+
+/// Max number of parts in a multipart upload
+pub const MULTIPART_MAX_PARTS: u64 = 10000;
+/// Min size of a part (except the last one)
+pub const MULTIPART_MIN_PART_SIZE: u64 = 100 * 1024;
+/// Part size used by default
+pub const MULTIPART_DEFAULT_PART_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Default max read-ahead window of [obs::ObjectIO]
+pub const OBJECT_IO_DEFAULT_READ_AHEAD: usize = 1024 * 1024;
+
+/// Storage class of an object or a bucket
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(into = "&'static str", try_from = "String")]
+pub enum StorageClass {
+    Standard,
+    Warm,
+    Cold,
+}
+
+impl StorageClass {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Standard => "STANDARD",
+            Self::Warm => "WARM",
+            Self::Cold => "COLD",
+        }
+    }
+}
+
+impl From<StorageClass> for &'static str {
+    fn from(value: StorageClass) -> Self { value.as_str() }
+}
+
+impl TryFrom<String> for StorageClass {
+    type Error = String;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "STANDARD" => Ok(Self::Standard),
+            "WARM" => Ok(Self::Warm),
+            "COLD" => Ok(Self::Cold),
+            _ => Err(format!("unknown storage class `{value}`")),
+        }
+    }
+}
+
+/// Status of a configuration rule
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(into = "&'static str", try_from = "String")]
+pub enum RuleStatus {
+    Enabled,
+    Disabled,
+}
+
+impl From<RuleStatus> for &'static str {
+    fn from(value: RuleStatus) -> Self {
+        match value {
+            RuleStatus::Enabled => "Enabled",
+            RuleStatus::Disabled => "Disabled",
+        }
+    }
+}
+
+impl TryFrom<String> for RuleStatus {
+    type Error = String;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "Enabled" => Ok(Self::Enabled),
+            "Disabled" => Ok(Self::Disabled),
+            _ => Err(format!("unknown rule status `{value}`")),
+        }
+    }
+}
+
+/// Bucket lifecycle configuration
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(rename="LifecycleConfiguration")]
+pub struct LifecycleConfiguration {
+    #[serde(rename="Rule", default)]
+    pub rules: Vec<LifecycleRule>,
+}
+
+/// Lifecycle rule applied to the objects under `prefix`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct LifecycleRule {
+    /// Rule ID, unique within the configuration
+    #[serde(rename="ID", skip_serializing_if="Option::is_none")]
+    pub id: Option<String>,
+
+    /// Object name prefix the rule applies to. Empty prefix means the whole bucket
+    #[serde(rename="Prefix", default)]
+    pub prefix: String,
+
+    #[serde(rename="Status")]
+    pub status: RuleStatus,
+
+    /// Transitions of the current versions to other storage classes
+    #[serde(rename="Transition", default, skip_serializing_if="Vec::is_empty")]
+    pub transitions: Vec<Transition>,
+
+    /// Expiration of the current versions
+    #[serde(rename="Expiration", skip_serializing_if="Option::is_none")]
+    pub expiration: Option<Expiration>,
+
+    /// Transitions of the noncurrent versions to other storage classes
+    #[serde(rename="NoncurrentVersionTransition", default, skip_serializing_if="Vec::is_empty")]
+    pub noncurrent_version_transitions: Vec<NoncurrentVersionTransition>,
+
+    /// Expiration of the noncurrent versions
+    #[serde(rename="NoncurrentVersionExpiration", skip_serializing_if="Option::is_none")]
+    pub noncurrent_version_expiration: Option<NoncurrentVersionExpiration>,
+}
+
+/// Transition of objects to `storage_class`, either `days` after creation or at `date`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Transition {
+    #[serde(rename="Days", skip_serializing_if="Option::is_none")]
+    pub days: Option<u32>,
+
+    /// Date in ISO 8601 format at UTC midnight, e.g. `2024-01-01T00:00:00.000Z`
+    #[serde(rename="Date", skip_serializing_if="Option::is_none")]
+    pub date: Option<String>,
+
+    #[serde(rename="StorageClass")]
+    pub storage_class: StorageClass,
+}
+
+/// Expiration of objects, either `days` after creation or at `date`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Expiration {
+    #[serde(rename="Days", skip_serializing_if="Option::is_none")]
+    pub days: Option<u32>,
+
+    /// Date in ISO 8601 format at UTC midnight, e.g. `2024-01-01T00:00:00.000Z`
+    #[serde(rename="Date", skip_serializing_if="Option::is_none")]
+    pub date: Option<String>,
+}
+
+/// Transition of objects to `storage_class` `noncurrent_days` after they become noncurrent
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct NoncurrentVersionTransition {
+    #[serde(rename="NoncurrentDays")]
+    pub noncurrent_days: u32,
+
+    #[serde(rename="StorageClass")]
+    pub storage_class: StorageClass,
+}
+
+/// Expiration of objects `noncurrent_days` after they become noncurrent
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct NoncurrentVersionExpiration {
+    #[serde(rename="NoncurrentDays")]
+    pub noncurrent_days: u32,
+}
+
+/// Versioning status of a bucket
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(into = "&'static str", try_from = "String")]
+pub enum VersioningStatus {
+    Enabled,
+    Suspended,
+}
+
+impl From<VersioningStatus> for &'static str {
+    fn from(value: VersioningStatus) -> Self {
+        match value {
+            VersioningStatus::Enabled => "Enabled",
+            VersioningStatus::Suspended => "Suspended",
+        }
+    }
+}
+
+impl TryFrom<String> for VersioningStatus {
+    type Error = String;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "Enabled" => Ok(Self::Enabled),
+            "Suspended" => Ok(Self::Suspended),
+            _ => Err(format!("unknown versioning status `{value}`")),
+        }
+    }
+}
+
+/// Bucket versioning configuration
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(rename="VersioningConfiguration")]
+pub struct VersioningConfiguration {
+    /// Not set if versioning has never been enabled for the bucket
+    #[serde(rename="Status", skip_serializing_if="Option::is_none")]
+    pub status: Option<VersioningStatus>,
+}
+
+/// Condition of a browser-based POST upload policy. Field names are given without the leading `$`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyCondition {
+    /// Form field must be equal to the value
+    Eq(String, String),
+    /// Form field must start with the prefix
+    StartsWith(String, String),
+    /// Size of the uploaded file must be within the range (inclusive)
+    ContentLengthRange(u64, u64),
+}
+
+/// Policy of a browser-based POST upload (see `Bucket::post_policy_form`)
+/// 
+/// Each field set with the builder methods is also added to the form, along with the condition matching it.
+#[derive(Debug, Clone, Default)]
+pub struct PostPolicy {
+    /// Validity period of the policy
+    pub expires_in: Duration,
+    pub conditions: Vec<PolicyCondition>,
+    /// Form fields added along with the signature
+    pub fields: Vec<(String, String)>,
+}
+
+impl PostPolicy {
+    pub fn new(expires_in: Duration) -> Self { Self { expires_in, ..Default::default() } }
+
+    /// Upload to `key` exactly
+    pub fn key(self, key: &str) -> Self { self.field("key", key) }
+
+    /// Upload under `prefix`. The form's `key` is set to `prefix${filename}`, but the browser may change it within the prefix
+    pub fn key_prefix(mut self, prefix: &str) -> Self {
+        self.fields.push(("key".to_owned(), format!("{prefix}${{filename}}")));
+        self.condition(PolicyCondition::StartsWith("key".to_owned(), prefix.to_owned()))
+    }
+
+    /// Limit the size of the uploaded file to `min..=max` bytes
+    pub fn content_length_range(self, min: u64, max: u64) -> Self {
+        self.condition(PolicyCondition::ContentLengthRange(min, max))
+    }
+
+    /// Set `Content-Type` of the uploaded object
+    pub fn content_type(self, content_type: &str) -> Self { self.field("Content-Type", content_type) }
+
+    /// Allow any `Content-Type` starting with `prefix`, e.g. `image/`. The browser must add the field to the form
+    pub fn content_type_prefix(self, prefix: &str) -> Self {
+        self.condition(PolicyCondition::StartsWith("Content-Type".to_owned(), prefix.to_owned()))
+    }
+
+    /// Set canned ACL of the uploaded object, e.g. `public-read`
+    pub fn acl(self, acl: &str) -> Self { self.field("acl", acl) }
+
+    /// Respond with `status` (200, 201 or 204) on success
+    pub fn success_action_status(self, status: u16) -> Self { self.field("success_action_status", &status.to_string()) }
+
+    /// Add form field `name`=`value`, e.g. `x-obs-meta-*`, requiring it to be unchanged
+    pub fn field(mut self, name: &str, value: &str) -> Self {
+        self.fields.push((name.to_owned(), value.to_owned()));
+        self.condition(PolicyCondition::Eq(name.to_owned(), value.to_owned()))
+    }
+
+    pub fn condition(mut self, condition: PolicyCondition) -> Self {
+        self.conditions.push(condition);
+        self
+    }
+}
+
+/// Signed browser-based POST upload form. The file must be sent as the last field of the form, named `file`
+#[derive(Debug, Clone)]
+pub struct PostPolicyForm {
+    /// Form action URL
+    pub url: Url,
+    /// Form fields, including `policy` and `signature`
+    pub fields: Vec<(String, String)>,
+}
+
+/// Canned ACL, set with the `x-obs-acl` header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CannedAcl {
+    Private,
+    PublicRead,
+    PublicReadWrite,
+    /// Bucket only: public read access to the bucket and the objects in it
+    PublicReadDelivered,
+    /// Bucket only: public read/write access to the bucket and the objects in it
+    PublicReadWriteDelivered,
+    /// Object only: full control for the bucket owner
+    BucketOwnerFullControl,
+}
+
+impl CannedAcl {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Private => "private",
+            Self::PublicRead => "public-read",
+            Self::PublicReadWrite => "public-read-write",
+            Self::PublicReadDelivered => "public-read-delivered",
+            Self::PublicReadWriteDelivered => "public-read-write-delivered",
+            Self::BucketOwnerFullControl => "bucket-owner-full-control",
+        }
+    }
+}
+
+/// Bucket or object ACL
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename="AccessControlPolicy")]
+pub struct AccessControlPolicy {
+    #[serde(rename="Owner")]
+    pub owner: Owner,
+
+    /// Object only: whether the object ACL is inherited from the bucket ACL
+    #[serde(rename="Delivered", skip_serializing_if="Option::is_none")]
+    pub delivered: Option<bool>,
+
+    #[serde(rename="AccessControlList")]
+    pub access_control_list: AccessControlList,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct AccessControlList {
+    #[serde(rename="Grant", default)]
+    pub grants: Vec<Grant>,
+}
+
+/// Permission granted to a grantee
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Grant {
+    #[serde(rename="Grantee")]
+    pub grantee: Grantee,
+
+    #[serde(rename="Permission")]
+    pub permission: Permission,
+
+    /// Bucket only: whether the grant is delivered to the objects in the bucket
+    #[serde(rename="Delivered", skip_serializing_if="Option::is_none")]
+    pub delivered: Option<bool>,
+}
+
+/// Either a user (by domain ID) or a predefined group
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct Grantee {
+    /// Domain ID of the user
+    #[serde(rename="ID", skip_serializing_if="Option::is_none")]
+    pub id: Option<String>,
+
+    /// Predefined group, `Everyone` is the only one supported
+    #[serde(rename="Canned", skip_serializing_if="Option::is_none")]
+    pub canned: Option<String>,
+}
+
+impl Grantee {
+    pub fn user(domain_id: &str) -> Self { Self { id: Some(domain_id.to_owned()), canned: None } }
+    pub fn everyone() -> Self { Self { id: None, canned: Some("Everyone".to_owned()) } }
+    pub fn is_everyone(&self) -> bool { self.canned.as_deref() == Some("Everyone") }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(into = "&'static str", try_from = "String")]
+pub enum Permission {
+    Read,
+    Write,
+    ReadAcp,
+    WriteAcp,
+    FullControl,
+}
+
+impl From<Permission> for &'static str {
+    fn from(value: Permission) -> Self {
+        match value {
+            Permission::Read => "READ",
+            Permission::Write => "WRITE",
+            Permission::ReadAcp => "READ_ACP",
+            Permission::WriteAcp => "WRITE_ACP",
+            Permission::FullControl => "FULL_CONTROL",
+        }
+    }
+}
+
+impl TryFrom<String> for Permission {
+    type Error = String;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "READ" => Ok(Self::Read),
+            "WRITE" => Ok(Self::Write),
+            "READ_ACP" => Ok(Self::ReadAcp),
+            "WRITE_ACP" => Ok(Self::WriteAcp),
+            "FULL_CONTROL" => Ok(Self::FullControl),
+            _ => Err(format!("unknown permission `{value}`")),
+        }
+    }
+}
+
+/// Bucket policy (JSON)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BucketPolicy {
+    #[serde(rename="Version", skip_serializing_if="Option::is_none")]
+    pub version: Option<String>,
+
+    #[serde(rename="Statement")]
+    pub statements: Vec<PolicyStatement>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PolicyStatement {
+    #[serde(rename="Sid", skip_serializing_if="Option::is_none")]
+    pub sid: Option<String>,
+
+    #[serde(rename="Effect")]
+    pub effect: PolicyEffect,
+
+    /// Users the statement applies to, e.g. `{"ID": ["domain/<domain_id>:user/<user_id>"]}` or `"*"`
+    #[serde(rename="Principal", skip_serializing_if="Option::is_none")]
+    pub principal: Option<serde_json::Value>,
+
+    /// Users the statement does not apply to
+    #[serde(rename="NotPrincipal", skip_serializing_if="Option::is_none")]
+    pub not_principal: Option<serde_json::Value>,
+
+    /// Actions, e.g. `GetObject`, `*`
+    #[serde(rename="Action", default, skip_serializing_if="StringList::is_empty")]
+    pub action: StringList,
+
+    #[serde(rename="NotAction", default, skip_serializing_if="StringList::is_empty")]
+    pub not_action: StringList,
+
+    /// Resources, e.g. `bucket/*`
+    #[serde(rename="Resource", default, skip_serializing_if="StringList::is_empty")]
+    pub resource: StringList,
+
+    #[serde(rename="NotResource", default, skip_serializing_if="StringList::is_empty")]
+    pub not_resource: StringList,
+
+    #[serde(rename="Condition", skip_serializing_if="Option::is_none")]
+    pub condition: Option<serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyEffect {
+    Allow,
+    Deny,
+}
+
+/// Policy element given either as a single string or as a list of strings
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum StringList {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Default for StringList {
+    fn default() -> Self { Self::Many(vec![]) }
+}
+
+impl StringList {
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        match self {
+            Self::One(s) => std::slice::from_ref(s).iter(),
+            Self::Many(v) => v.iter(),
+        }.map(String::as_str)
+    }
+
+    pub fn is_empty(&self) -> bool { self.iter().next().is_none() }
+}
+
+/// Server-side encryption of an object
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServerSideEncryption {
+    /// SSE-KMS with the given master key, or the default one if `None`
+    Kms(Option<String>),
+    /// SSE-OBS, keys managed by OBS
+    Obs,
+    /// SSE-C with the key provided by the customer. The same key must be provided to read the object
+    Customer(CustomerKey),
+}
+
+/// 256-bit SSE-C key
+#[derive(Clone, PartialEq, Eq)]
+pub struct CustomerKey(pub [u8; 32]);
+
+impl std::fmt::Debug for CustomerKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("CustomerKey(..)")
+    }
+}
+
+/// Default encryption of the objects put into a bucket
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename="ServerSideEncryptionConfiguration")]
+pub struct BucketEncryption {
+    #[serde(rename="Rule")]
+    pub rule: BucketEncryptionRule,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BucketEncryptionRule {
+    #[serde(rename="ApplyServerSideEncryptionByDefault")]
+    pub apply_server_side_encryption_by_default: DefaultEncryption,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DefaultEncryption {
+    /// `kms` or `AES256` (SSE-OBS)
+    #[serde(rename="SSEAlgorithm")]
+    pub sse_algorithm: String,
+
+    /// KMS master key for `kms`, the default one if unset
+    #[serde(rename="KMSMasterKeyID", skip_serializing_if="Option::is_none")]
+    pub kms_master_key_id: Option<String>,
+}
+
+impl BucketEncryption {
+    /// SSE-KMS with the given master key, or the default one if `None`
+    pub fn kms(key_id: Option<&str>) -> Self {
+        Self::with_algorithm("kms", key_id.map(str::to_owned))
+    }
+
+    /// SSE-OBS
+    pub fn obs() -> Self {
+        Self::with_algorithm("AES256", None)
+    }
+
+    fn with_algorithm(sse_algorithm: &str, kms_master_key_id: Option<String>) -> Self {
+        Self { rule: BucketEncryptionRule { apply_server_side_encryption_by_default: DefaultEncryption { 
+            sse_algorithm: sse_algorithm.to_owned(), kms_master_key_id
+        } } }
+    }
+}
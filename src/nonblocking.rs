@@ -5,6 +5,7 @@ pub mod client;
 pub mod obs;
 pub mod mauth;
 pub mod dli;
+pub mod iam;
 
 pub use client::{Client, ClientBuilder, ClientBuild, ServiceClientBuild};
 pub use reqwest::Client as HttpClient;
\ No newline at end of file
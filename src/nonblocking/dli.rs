@@ -11,7 +11,7 @@ use crate::*;
 pub struct DliClient {
     endpoint: String,
     project_id: String,
-    credentials: Credentials,
+    credentials: SharedCredentialsProvider,
     http_client: HttpClient,
 }
 
@@ -19,14 +19,14 @@ impl DliClient {
     pub fn new(
         endpoint: String,
         project_id: String,
-        credentials: Credentials,
+        credentials: impl Into<SharedCredentialsProvider>,
         http_client: HttpClient,
     ) -> Self {
         Self {
             endpoint,
             project_id,
             http_client,
-            credentials,
+            credentials: credentials.into(),
         }
     }
     pub async fn get_databases(&self) -> Result<model::GetDatabasesResponse> {
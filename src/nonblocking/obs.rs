@@ -1,31 +1,46 @@
-use std::io;
+use std::{fs::File, io, ops::RangeBounds, path::Path, pin::pin, sync::{Arc, Mutex}, time::Duration};
 
 use bytes::Bytes;
 
+use futures_util::{future::BoxFuture, stream, Stream, StreamExt, TryStreamExt};
 use http::header::CONTENT_LENGTH;
-use tracing::{debug, instrument};
-use reqwest::{header::{HeaderMap, HeaderValue}, Body, Method, Request, RequestBuilder};
+use tracing::{debug, instrument, warn};
+use reqwest::{header::{HeaderMap, HeaderValue}, Body, Method, Request, RequestBuilder, Response};
 use url::Url;
 
 pub use crate::model::obs::*;
-use self::{error::ParameterKind, shared::obs::FsType};
-use crate::shared::{mauth_obs::*, obs::{extract_bucket_meta, extract_object_meta}, urltools::WithVar};
+use self::shared::obs::{FsType, WriteOp};
+use crate::shared::{mauth_obs::*, obs::{check_transfer_prefixes, content_md5, BucketAddress, delete_objects_body, EtagVerifier, post_policy_fields, effective_part_size, extract_bucket_meta, extract_object_meta, create_bucket_headers, file_md5, file_size_and_mtime, local_sync_entries, plan_sync, sync_download_path, remote_sync_entry, sync_prefix, object_io_sse_headers, condition_headers, copy_object_headers, put_object_headers, sse_copy_source_headers, sse_read_headers, sse_write_headers, plan_parts, ListPager, range_header, read_part, PartRange}, urltools::WithVar};
+use super::common::unblock;
+#[cfg(feature = "tokio")]
+use crate::shared::obs::ReadAhead;
 use super::*;
 use crate::*;
 
 
 pub struct ObsClient {
     endpoint: String,
-    credentials: Credentials,
+    credentials: SharedCredentialsProvider,
     http_client: HttpClient,
+    addressing_style: AddressingStyle,
 }
 
 impl ObsClient {
-    pub fn new(endpoint: String, credentials: Credentials, http_client: HttpClient) -> Self { Self { endpoint, http_client, credentials } }
+    pub fn new(endpoint: String, credentials: impl Into<SharedCredentialsProvider>, http_client: HttpClient) -> Self { 
+        Self { endpoint, http_client, credentials: credentials.into(), addressing_style: AddressingStyle::default() } 
+    }
+
+    /// Sets the addressing style of the buckets produced by [ObsClient::bucket]
+    pub fn with_addressing_style(mut self, addressing_style: AddressingStyle) -> Self {
+        self.addressing_style = addressing_style;
+        self
+    }
+
     pub fn bucket(&self, bucket_name: String) -> Result<Bucket> { 
-        Bucket::new(
+        Bucket::new_with_addressing(
             bucket_name, 
             self.endpoint.clone(), 
+            self.addressing_style,
             self.credentials.clone(), 
             self.http_client.clone()
         ) }
@@ -34,10 +49,11 @@ impl ObsClient {
 #[derive(Debug, Clone)]
 pub struct Bucket {
     bucket_name: String,
-    bucket_url: Url,
+    address: BucketAddress,
     host: HeaderValue,
-    credentials: Credentials,
+    credentials: SharedCredentialsProvider,
     http_client: HttpClient,
+    integrity_checks: bool,
 }
 
 struct R<'r> { r: &'r mut Request }
@@ -50,23 +66,24 @@ impl<'r> RequestW for R<'r> {
 
 
 trait Signer {
-    fn timestamp_and_sign(self, bucket_name: &str, credentials: &Credentials) -> Result<Request>;
+    fn timestamp_and_sign(self, bucket_name: &str, credentials: &dyn CredentialsProvider) -> Result<Request>;
 }
 
 impl Signer for RequestBuilder {
-    fn timestamp_and_sign(self, bucket_name: &str, credentials: &Credentials) -> Result<Request> {
+    fn timestamp_and_sign(self, bucket_name: &str, credentials: &dyn CredentialsProvider) -> Result<Request> {
+        let credentials = credentials.credentials()?;
         let mut request = self.build()?;
 
-        // work around HTTP 411 issue when a 0-length object is created
+        // work around HTTP 411 issue when a 0-length object is created, or a PUT has no body at all (e.g. bucket creation)
         if 
             request.method() == Method::PUT &&
-            request.body().and_then(|b| b.as_bytes()).is_some_and(|w| w.is_empty()) 
+            request.body().is_none_or(|b| b.as_bytes().is_some_and(|w| w.is_empty()))
         {
             request.headers_mut().entry(CONTENT_LENGTH).or_insert_with(|| "0".parse().unwrap());
         }
 
         let dt = time::OffsetDateTime::now_utc();
-        time_stamp_and_sign(bucket_name, &mut R { r: &mut request }, dt, &credentials.ak, &credentials.sk)?;
+        time_stamp_and_sign(bucket_name, &mut R { r: &mut request }, dt, &credentials.ak, &credentials.sk, credentials.security_token.as_deref())?;
         Ok(request)
     }
 }
@@ -76,28 +93,144 @@ macro_rules! bail_on_failure {
         if !$result.status().is_success() {
             let status = $result.status();
             let err = $result.text().await.cx("error text in bail_on_failure")?;
-            return Err(CloudRuError::API(status, err));
+            return Err(CloudRuError::from_api_response(status, err));
         }        
     };
 }
 
+async fn parse_xml<T: serde::de::DeserializeOwned>(result: Response) -> Result<T> {
+    let text = result.text().await?;
+    debug!(response_text=?&text);
+    Ok(serde_xml_rs::from_str(&text)?)
+}
+
+/// appends `batch` to `file` off the async executor, handing the file back
+async fn write_batch(mut file: File, batch: Vec<u8>) -> io::Result<File> {
+    unblock(move || io::Write::write_all(&mut file, &batch).map(|_| file)).await
+}
+
+/// passes the chunks of `stream` through `verifier`, yielding [CloudRuError::ChecksumMismatch] at the end of the stream
+fn verified_stream(
+    stream: impl Stream<Item = Result<Bytes>> + Send + 'static, 
+    verifier: Option<EtagVerifier>
+) -> impl Stream<Item = Result<Bytes>> + Send + 'static {
+    stream::unfold((Box::pin(stream), verifier), |(mut stream, mut verifier)| async move {
+        match stream.next().await {
+            Some(Ok(chunk)) => {
+                if let Some(verifier) = verifier.as_mut() {
+                    verifier.update(&chunk);
+                }
+                Some((Ok(chunk), (stream, verifier)))
+            }
+            Some(Err(e)) => Some((Err(e), (stream, None))),
+            None => match verifier.map(EtagVerifier::verify) {
+                Some(Err(e)) => Some((Err(e), (stream, None))),
+                _ => None,
+            },
+        }
+    })
+}
+
+
+impl ObsClient {
+    /// list the buckets and parallel file systems of the account
+    pub async fn list_buckets(&self) -> Result<ListBucketsResult> {
+        let url: Url = self.endpoint.parse()?;
+        let request = self.http_client.request(Method::GET, url);
+        let request = request.timestamp_and_sign("", &self.credentials)?;
+
+        debug!(request_full=?request);
+
+        let result = self.http_client.execute(request).await?;
+        bail_on_failure!(result);
+
+        parse_xml(result).await
+    }
+
+    /// create bucket `bucket_name` with `options` and return its handle
+    pub async fn create_bucket(&self, bucket_name: String, options: &CreateBucketOptions) -> Result<Bucket> {
+        let bucket = self.bucket(bucket_name)?;
+        let request = bucket.http_client.request(Method::PUT, bucket.url("/"));
+        let request: RequestBuilder = bucket.start_request(request);
+        let request = request.headers(create_bucket_headers(options));
+        let request = match &options.location {
+            Some(location) => {
+                let body = serde_xml_rs::to_string(&CreateBucketConfiguration { location: location.clone() })?;
+                request.header("content-type", "application/xml").body(body)
+            }
+            None => request,
+        };
+        let request = bucket.sign_request(request)?;
+
+        debug!(request_full=?request);
+
+        let result = bucket.http_client.execute(request).await?;
+        bail_on_failure!(result);
+
+        Ok(bucket)
+    }
+
+    /// delete bucket `bucket_name`, which must be empty
+    pub async fn delete_bucket(&self, bucket_name: String) -> Result<()> {
+        let bucket = self.bucket(bucket_name)?;
+        let request = bucket.http_client.request(Method::DELETE, bucket.url("/"));
+        let request: RequestBuilder = bucket.start_request(request);
+        let request = bucket.sign_request(request)?;
+
+        debug!(request_full=?request);
+
+        let result = bucket.http_client.execute(request).await?;
+        bail_on_failure!(result);
+
+        Ok(())
+    }
+}
 
 impl Bucket {
-    pub fn new(bucket_name: String, obs_endpoint: String, credentials: Credentials, http_client: HttpClient) -> Result<Self> {
-        let mut bucket_url: Url = obs_endpoint.parse()?;
-        let bucket_host = bucket_url.host()
-            .ok_or(CloudRuError::Parameter(ParameterKind::S3BucketUrl))?;
-        let host = format!("{}.{}", bucket_name, bucket_host);
-        bucket_url.set_host(Some(&host))?;
-        let host = host.parse()?;
-        Ok(Self { bucket_name, bucket_url, host, credentials, http_client })
+    pub fn new(bucket_name: String, obs_endpoint: String, credentials: impl Into<SharedCredentialsProvider>, http_client: HttpClient) -> Result<Self> {
+        Self::new_with_addressing(bucket_name, obs_endpoint, AddressingStyle::default(), credentials, http_client)
+    }
+
+    pub fn new_with_addressing(
+        bucket_name: String, 
+        obs_endpoint: String, 
+        addressing_style: AddressingStyle, 
+        credentials: impl Into<SharedCredentialsProvider>, 
+        http_client: HttpClient
+    ) -> Result<Self> {
+        let address = BucketAddress::new(&bucket_name, &obs_endpoint, addressing_style)?;
+        let host = address.host.parse()?;
+        Ok(Self { bucket_name, address, host, credentials: credentials.into(), http_client, integrity_checks: false })
+    }
+
+    #[inline]
+    fn signing_name(&self) -> &str {
+        &self.address.signing_name
+    }
+
+    /// Enables integrity checks: puts and part uploads of in-memory bodies, including the parts of file uploads, 
+    /// and [ObjectIO] writes send `Content-MD5`, and whole-object downloads are verified against the ETag, 
+    /// failing with [CloudRuError::ChecksumMismatch]. 
+    /// Streamed bodies, e.g. a `File` passed to `put_object`, are sent without `Content-MD5`, since it would take a second pass over the data. 
+    /// Objects uploaded in parts or encrypted with SSE-KMS/SSE-C are not verified, since their ETag is not an MD5 of the content
+    pub fn with_integrity_checks(mut self, enabled: bool) -> Self {
+        self.integrity_checks = enabled;
+        self
+    }
+
+    /// adds `Content-MD5` of `data` to the request if integrity checks are enabled
+    fn with_content_md5(&self, request: RequestBuilder, data: &[u8]) -> RequestBuilder {
+        if self.integrity_checks { request.header("content-md5", content_md5(data)) } else { request }
+    }
+
+    /// verifier of the object returned with `headers` if integrity checks are enabled
+    fn etag_verifier(&self, headers: &HeaderMap) -> Option<EtagVerifier> {
+        if self.integrity_checks { EtagVerifier::new(headers) } else { None }
     }
 
     #[inline]
     fn url(&self, path: impl AsRef<str>) -> Url {
-        let mut url = self.bucket_url.clone();
-        url.set_path(path.as_ref());
-        url
+        self.address.url(path.as_ref())
     }
 
     #[inline]
@@ -111,15 +244,15 @@ impl Bucket {
         let url = self.url("/")
             .with_var_opt("prefix", request.prefix)
             .with_var_opt("marker", request.marker)
-            .with_var_opt("max_keys", request.max_keys.map(|s| format!("{s}")))
+            .with_var_opt("max-keys", request.max_keys.map(|s| format!("{s}")))
             .with_var_opt("delimiter", request.delimiter)
-            .with_var_opt("key_marker", request.key_marker)
-            .with_var_opt("version_id_marker", request.version_id_marker)
+            .with_var_opt("key-marker", request.key_marker)
+            .with_var_opt("version-id-marker", request.version_id_marker)
             ;
 
         let request = self.http_client.request(Method::GET, url)
             .header("host", self.host.clone())
-            .timestamp_and_sign(&self.bucket_name, &self.credentials)?;
+            .timestamp_and_sign(self.signing_name(), &self.credentials)?;
 
         debug!(request_full=?request);
 
@@ -133,22 +266,27 @@ impl Bucket {
         Ok(p)
     }
 
-    #[instrument]
+    #[deprecated(note="use list_object_versions instead")]
     pub async fn list_object_versionss(&self, request: ListObjectsRequest<'_>) -> Result<ListObjectVersionsResult> {
+        self.list_object_versions(request).await
+    }
+
+    #[instrument]
+    pub async fn list_object_versions(&self, request: ListObjectsRequest<'_>) -> Result<ListObjectVersionsResult> {
         
         let url = self.url("/")
             .with_var_key("versions")
             .with_var_opt("prefix", request.prefix)
             .with_var_opt("marker", request.marker)
-            .with_var_opt("max_keys", request.max_keys.map(|s| format!("{s}")))
+            .with_var_opt("max-keys", request.max_keys.map(|s| format!("{s}")))
             .with_var_opt("delimiter", request.delimiter)
-            .with_var_opt("key_marker", request.key_marker)
-            .with_var_opt("version_id_marker", request.version_id_marker)
+            .with_var_opt("key-marker", request.key_marker)
+            .with_var_opt("version-id-marker", request.version_id_marker)
             ;
 
         let request = self.http_client.request(Method::GET, url)
             .header("host", self.host.clone())
-            .timestamp_and_sign(&self.bucket_name, &self.credentials)?;
+            .timestamp_and_sign(self.signing_name(), &self.credentials)?;
 
         debug!(request_full=?request);
 
@@ -166,26 +304,254 @@ impl Bucket {
         self.list_objects(ListObjectsRequest { prefix, ..Default::default() }).await
     }
 
+    /// Lists objects page by page, starting at `request.marker`. The stream ends after the first error.
+    pub fn iter_objects(&self, request: ListObjectsRequest<'_>) -> impl Stream<Item = Result<ListObjectsContents>> + Send + '_ {
+        stream::try_unfold(ListPager::new(&request), move |mut pager| async move {
+            if pager.is_done() { return Ok::<_, CloudRuError>(None) }
+            let result = self.list_objects(pager.request()).await?;
+            let page = pager.advance_objects(result);
+            Ok(Some((stream::iter(page.into_iter().map(Ok)), pager)))
+        }).try_flatten()
+    }
+
+    /// Lists objects and common prefixes page by page, starting at `request.marker`. 
+    /// With `request.delimiter` set, yields the "directories" without listing the objects below them.
+    /// The stream ends after the first error.
+    pub fn iter_entries(&self, request: ListObjectsRequest<'_>) -> impl Stream<Item = Result<ListEntry>> + Send + '_ {
+        stream::try_unfold(ListPager::new(&request), move |mut pager| async move {
+            if pager.is_done() { return Ok::<_, CloudRuError>(None) }
+            let result = self.list_objects(pager.request()).await?;
+            let page = pager.advance_entries(result);
+            Ok(Some((stream::iter(page.into_iter().map(Ok)), pager)))
+        }).try_flatten()
+    }
+
+    /// Lists object versions and deletion markers page by page, starting at `request.key_marker`/`request.version_id_marker`. 
+    /// The stream ends after the first error.
+    pub fn iter_object_versions(&self, request: ListObjectsRequest<'_>) -> impl Stream<Item = Result<ObjectVersionEntry>> + Send + '_ {
+        stream::try_unfold(ListPager::new(&request), move |mut pager| async move {
+            if pager.is_done() { return Ok::<_, CloudRuError>(None) }
+            let result = self.list_object_versions(pager.request()).await?;
+            let page = pager.advance_versions(result);
+            Ok(Some((stream::iter(page.into_iter().map(Ok)), pager)))
+        }).try_flatten()
+    }
+
+    /// get the lifecycle configuration of the bucket, `None` if not configured
+    pub async fn get_lifecycle_configuration(&self) -> Result<Option<LifecycleConfiguration>> {
+        match self.get_bucket_config("lifecycle").await {
+            Err(e) if e.is_api_error_code("NoSuchLifecycleConfiguration") => Ok(None),
+            r => r.map(Some)
+        }
+    }
+
+    /// replace the lifecycle configuration of the bucket
+    pub async fn put_lifecycle_configuration(&self, config: &LifecycleConfiguration) -> Result<()> {
+        self.put_bucket_config("lifecycle", config).await
+    }
+
+    /// delete the lifecycle configuration of the bucket
+    pub async fn delete_lifecycle_configuration(&self) -> Result<()> {
+        self.delete_bucket_config("lifecycle").await
+    }
+
+    /// get the versioning status of the bucket, `None` if versioning has never been enabled
+    pub async fn get_versioning(&self) -> Result<Option<VersioningStatus>> {
+        let config: VersioningConfiguration = self.get_bucket_config("versioning").await?;
+        Ok(config.status)
+    }
+
+    /// enable or suspend versioning of the bucket
+    pub async fn set_versioning(&self, status: VersioningStatus) -> Result<()> {
+        self.put_bucket_config("versioning", &VersioningConfiguration { status: Some(status) }).await
+    }
+
+    /// get the ACL of the bucket
+    pub async fn get_bucket_acl(&self) -> Result<AccessControlPolicy> {
+        self.get_bucket_config("acl").await
+    }
+
+    /// replace the ACL of the bucket
+    pub async fn put_bucket_acl(&self, acl: &AccessControlPolicy) -> Result<()> {
+        self.put_bucket_config("acl", acl).await
+    }
+
+    /// replace the ACL of the bucket with a canned one
+    pub async fn set_bucket_canned_acl(&self, acl: CannedAcl) -> Result<()> {
+        self.put_canned_acl("/", acl).await
+    }
+
+    /// get the ACL of the object at `remote_path`
+    pub async fn get_object_acl(&self, remote_path: impl AsRef<str>) -> Result<AccessControlPolicy> {
+        let result = self.get_subresource(remote_path.as_ref(), "acl").await?;
+        parse_xml(result).await
+    }
+
+    /// replace the ACL of the object at `remote_path`
+    pub async fn put_object_acl(&self, remote_path: impl AsRef<str>, acl: &AccessControlPolicy) -> Result<()> {
+        self.put_subresource(remote_path.as_ref(), "acl", "application/xml", serde_xml_rs::to_string(acl)?).await
+    }
+
+    /// replace the ACL of the object at `remote_path` with a canned one
+    pub async fn set_object_canned_acl(&self, remote_path: impl AsRef<str>, acl: CannedAcl) -> Result<()> {
+        self.put_canned_acl(remote_path.as_ref(), acl).await
+    }
+
+    /// get the bucket policy, `None` if not set
+    pub async fn get_bucket_policy(&self) -> Result<Option<BucketPolicy>> {
+        match self.get_subresource("/", "policy").await {
+            Err(e) if e.is_api_error_code("NoSuchBucketPolicy") => Ok(None),
+            Err(e) => Err(e),
+            Ok(result) => Ok(Some(result.json().await?)),
+        }
+    }
+
+    /// replace the bucket policy
+    pub async fn put_bucket_policy(&self, policy: &BucketPolicy) -> Result<()> {
+        self.put_subresource("/", "policy", "application/json", serde_json::to_string(policy)?).await
+    }
+
+    /// delete the bucket policy
+    pub async fn delete_bucket_policy(&self) -> Result<()> {
+        self.delete_bucket_config("policy").await
+    }
+
+    /// get the default encryption of the bucket, `None` if it is not configured
+    pub async fn get_bucket_encryption(&self) -> Result<Option<BucketEncryption>> {
+        match self.get_bucket_config("encryption").await {
+            Err(e) if e.is_api_error_code("NoSuchEncryptionConfiguration") 
+                || e.is_api_error_code("ServerSideEncryptionConfigurationNotFoundError") => Ok(None),
+            r => r.map(Some)
+        }
+    }
+
+    /// set the default encryption of the bucket
+    pub async fn put_bucket_encryption(&self, config: &BucketEncryption) -> Result<()> {
+        self.put_bucket_config("encryption", config).await
+    }
+
+    /// delete the default encryption of the bucket
+    pub async fn delete_bucket_encryption(&self) -> Result<()> {
+        self.delete_bucket_config("encryption").await
+    }
+
+    /// GET /?`subresource` as XML
+    async fn get_bucket_config<T: serde::de::DeserializeOwned>(&self, subresource: &str) -> Result<T> {
+        let result = self.get_subresource("/", subresource).await?;
+        parse_xml(result).await
+    }
+
+    /// PUT /?`subresource` with `config` as XML
+    async fn put_bucket_config<T: serde::Serialize>(&self, subresource: &str, config: &T) -> Result<()> {
+        self.put_subresource("/", subresource, "application/xml", serde_xml_rs::to_string(config)?).await
+    }
+
+    /// DELETE /?`subresource`
+    async fn delete_bucket_config(&self, subresource: &str) -> Result<()> {
+        let url = self.url("/").with_var_key(subresource);
+        let request = self.http_client.request(Method::DELETE, url);
+        let request: RequestBuilder = self.start_request(request);
+        let request = self.sign_request(request)?;
+
+        debug!(request_full=?request);
+
+        let result = self.http_client.execute(request).await?;
+        bail_on_failure!(result);
+
+        Ok(())
+    }
+
+    /// GET `remote_path`?`subresource`
+    async fn get_subresource(&self, remote_path: &str, subresource: &str) -> Result<Response> {
+        let url = self.url(remote_path).with_var_key(subresource);
+        let request = self.http_client.request(Method::GET, url);
+        let request: RequestBuilder = self.start_request(request);
+        let request = self.sign_request(request)?;
+
+        debug!(request_full=?request);
+
+        let result = self.http_client.execute(request).await?;
+        bail_on_failure!(result);
+
+        Ok(result)
+    }
+
+    /// PUT `remote_path`?`subresource` with `body`
+    async fn put_subresource(&self, remote_path: &str, subresource: &str, content_type: &str, body: String) -> Result<()> {
+        let url = self.url(remote_path).with_var_key(subresource);
+        let request = self.http_client.request(Method::PUT, url);
+        let request: RequestBuilder = self.start_request(request);
+        let request = request
+            .header("content-type", content_type)
+            .header("content-md5", content_md5(body.as_bytes()))
+            .body(body);
+        let request = self.sign_request(request)?;
+
+        debug!(request_full=?request);
+
+        let result = self.http_client.execute(request).await?;
+        bail_on_failure!(result);
+
+        Ok(())
+    }
+
+    /// PUT `remote_path`?acl with the `x-obs-acl` header
+    async fn put_canned_acl(&self, remote_path: &str, acl: CannedAcl) -> Result<()> {
+        let url = self.url(remote_path).with_var_key("acl");
+        let request = self.http_client.request(Method::PUT, url);
+        let request: RequestBuilder = self.start_request(request);
+        let request = request.header("x-obs-acl", acl.as_str());
+        let request = self.sign_request(request)?;
+
+        debug!(request_full=?request);
+
+        let result = self.http_client.execute(request).await?;
+        bail_on_failure!(result);
+
+        Ok(())
+    }
+
     fn start_request(&self, request: RequestBuilder) -> RequestBuilder {
         request.header("host", self.host.clone())
     }
 
     fn sign_request(&self, request: RequestBuilder) -> Result<Request> {
-        request.timestamp_and_sign(&self.bucket_name, &self.credentials)
+        request.timestamp_and_sign(self.signing_name(), &self.credentials)
     }
 
 
     /// get object at `remote_path`
     pub async fn get_object(&self, remote_path: impl AsRef<str>) -> Result<Bytes> {
+        self.get_object_with_headers(remote_path.as_ref(), HeaderMap::new()).await
+    }
+
+    /// get object at `remote_path`, encrypted with `sse`
+    pub async fn get_object_encrypted(&self, remote_path: impl AsRef<str>, sse: &ServerSideEncryption) -> Result<Bytes> {
+        self.get_object_with_headers(remote_path.as_ref(), sse_read_headers(sse)?).await
+    }
+
+    /// get object at `remote_path` if `conditions` are met, 
+    /// failing with [CloudRuError::NotModified] or [CloudRuError::PreconditionFailed] otherwise
+    pub async fn get_object_conditional(&self, remote_path: impl AsRef<str>, conditions: &ObjectConditions) -> Result<Bytes> {
+        self.get_object_with_headers(remote_path.as_ref(), condition_headers(conditions, "")?).await
+    }
+
+    async fn get_object_with_headers(&self, remote_path: &str, headers: HeaderMap) -> Result<Bytes> {
         let request = self.http_client.request(Method::GET, self.url(remote_path));
         let request: RequestBuilder = self.start_request(request);
+        let request = request.headers(headers);
         let request = self.sign_request(request)?;
 
         debug!(request_full=?request);
 
         let result = self.http_client.execute(request).await?;
         bail_on_failure!(result);
+        let verifier = self.etag_verifier(result.headers());
         let rv = result.bytes().await?;
+        if let Some(mut verifier) = verifier {
+            verifier.update(&rv);
+            verifier.verify()?;
+        }
         
         Ok(rv)
     }
@@ -201,16 +567,76 @@ impl Bucket {
 
         let result = self.http_client.execute(request).await?;
         bail_on_failure!(result);
+        let verifier = self.etag_verifier(result.headers());
         let rv = result.bytes().await?;
+        if let Some(mut verifier) = verifier {
+            verifier.update(&rv);
+            verifier.verify()?;
+        }
         
         Ok(rv)
     }
 
+    /// get object at `remote_path` as a stream of data chunks, without buffering the entire object in memory
+    pub async fn get_object_stream(&self, remote_path: impl AsRef<str>) -> Result<impl Stream<Item = Result<Bytes>> + Send + 'static> {
+        self.object_stream(self.url(remote_path), None).await
+    }
+
+    /// get object version at `remote_path` as a stream of data chunks
+    pub async fn get_object_version_stream(&self, remote_path: impl AsRef<str>, version_id: impl AsRef<str>) -> Result<impl Stream<Item = Result<Bytes>> + Send + 'static> {
+        let url = self.url(remote_path).with_var("versionId", version_id.as_ref());
+        self.object_stream(url, None).await
+    }
+
+    /// get `range` of bytes of the object at `remote_path` as a stream of data chunks
+    pub async fn get_object_range_stream(&self, remote_path: impl AsRef<str>, range: impl RangeBounds<u64>) -> Result<impl Stream<Item = Result<Bytes>> + Send + 'static> {
+        self.object_stream(self.url(remote_path), range_header(range)).await
+    }
+
+    async fn object_stream(&self, url: Url, range: Option<String>) -> Result<impl Stream<Item = Result<Bytes>> + Send + 'static> {
+        let request = self.http_client.request(Method::GET, url);
+        let request: RequestBuilder = self.start_request(request);
+        let is_ranged = range.is_some();
+        let request = if let Some(range) = range { request.header("range", range) } else { request };
+        let request = self.sign_request(request)?;
+
+        debug!(request_full=?request);
+
+        let result = self.http_client.execute(request).await?;
+        bail_on_failure!(result);
+        if is_ranged && result.status().as_u16() != 206 { //Partial content
+            return Err(CloudRuError::ReturningRangesNotSupported)
+        }
+        let verifier = if is_ranged { None } else { self.etag_verifier(result.headers()) };
+
+        Ok(verified_stream(result.bytes_stream().map_err(CloudRuError::from), verifier))
+    }
+
     /// put object at `remote_path` filling it with data read from `input`
     pub async fn put_object<I>(&self, remote_path: impl AsRef<str>, input: I) -> Result<()> where Body: From<I> {
+        self.put_object_with_headers(remote_path.as_ref(), input, HeaderMap::new()).await
+    }
+
+    /// put object at `remote_path` encrypted with `sse`, filling it with data read from `input`
+    pub async fn put_object_encrypted<I>(&self, remote_path: impl AsRef<str>, input: I, sse: &ServerSideEncryption) -> Result<()> where Body: From<I> {
+        self.put_object_with_headers(remote_path.as_ref(), input, sse_write_headers(sse)?).await
+    }
+
+    /// put object at `remote_path` with content headers, metadata, storage class and encryption set by `options`, 
+    /// filling it with data read from `input`
+    pub async fn put_object_with_options<I>(&self, remote_path: impl AsRef<str>, input: I, options: &PutObjectOptions) -> Result<()> where Body: From<I> {
+        self.put_object_with_headers(remote_path.as_ref(), input, put_object_headers(options)?).await
+    }
+
+    async fn put_object_with_headers<I>(&self, remote_path: &str, input: I, headers: HeaderMap) -> Result<()> where Body: From<I> {
         let request = self.http_client.request(Method::PUT, self.url(remote_path));
         let request: RequestBuilder = self.start_request(request);
-        let request = request.body(input);
+        let body = Body::from(input);
+        let request = match body.as_bytes() {
+            Some(data) => self.with_content_md5(request, data),
+            None => request,
+        };
+        let request = request.headers(headers).body(body);
         let request = self.sign_request(request)?;
 
         debug!(request_full=?request);
@@ -235,13 +661,144 @@ impl Bucket {
         Ok(())
     }
 
+    /// delete multiple objects (or object versions), splitting them into batches of [DELETE_OBJECTS_MAX_KEYS].
+    /// In `quiet` mode, only the failures are reported.
+    /// @see https://support.hc.sbercloud.ru/api/obs/obs_04_0086.html
+    pub async fn delete_objects<I>(&self, objects: I, quiet: bool) -> Result<DeleteObjectsResult> 
+    where I: IntoIterator, I::Item: Into<ObjectIdentifier> {
+        let objects: Vec<ObjectIdentifier> = objects.into_iter().map(Into::into).collect();
+        let mut result = DeleteObjectsResult::default();
+        for batch in objects.chunks(DELETE_OBJECTS_MAX_KEYS) {
+            result.results.extend(self.delete_objects_batch(batch, quiet).await?.results);
+        }
+        Ok(result)
+    }
+
+    /// delete all objects under `prefix`, listing and deleting them batch by batch. Returns the failures.
+    /// 
+    /// In a versioned bucket, deletion markers are created for the current versions.
+    pub async fn delete_prefix(&self, prefix: &str) -> Result<DeleteObjectsResult> {
+        let mut result = DeleteObjectsResult::default();
+        let mut batch = Vec::with_capacity(DELETE_OBJECTS_MAX_KEYS);
+        let mut objects = pin!(self.iter_objects(ListObjectsRequest { prefix: Some(prefix), ..Default::default() }));
+        while let Some(contents) = objects.try_next().await? {
+            batch.push(ObjectIdentifier::from(contents.key));
+            if batch.len() == DELETE_OBJECTS_MAX_KEYS {
+                result.results.extend(self.delete_objects_batch(&batch, true).await?.results);
+                batch.clear();
+            }
+        }
+        if !batch.is_empty() {
+            result.results.extend(self.delete_objects_batch(&batch, true).await?.results);
+        }
+        Ok(result)
+    }
+
+    async fn delete_objects_batch(&self, objects: &[ObjectIdentifier], quiet: bool) -> Result<DeleteObjectsResult> {
+        let (body, md5) = delete_objects_body(objects, quiet)?;
+
+        let url = self.url("/").with_var_key("delete");
+        let request = self.http_client.request(Method::POST, url);
+        let request: RequestBuilder = self.start_request(request);
+        let request = request
+            .header("content-type", "application/xml")
+            .header("content-md5", md5)
+            .body(body);
+        let request = self.sign_request(request)?;
+
+        debug!(request_full=?request);
+
+        let result = self.http_client.execute(request).await?;
+        bail_on_failure!(result);
+
+        parse_xml(result).await
+    }
+
+    /// delete the version `version_id` of the object at `remote_path` permanently
+    pub async fn delete_object_version(&self, remote_path: impl AsRef<str>, version_id: impl AsRef<str>) -> Result<()> {
+        let url = self.url(remote_path).with_var("versionId", version_id.as_ref());
+        let request = self.http_client.request(Method::DELETE, url);
+        let request: RequestBuilder = self.start_request(request);
+        let request = self.sign_request(request)?;
+
+        debug!(request_full=?request);
+
+        let result = self.http_client.execute(request).await?;
+        bail_on_failure!(result);
+
+        Ok(())
+    }
+
+    /// make the version `version_id` of the object at `remote_path` current by copying it over the current version.
+    /// Returns the ID of the new version
+    pub async fn restore_version(&self, remote_path: impl AsRef<str>, version_id: impl AsRef<str>) -> Result<Option<String>> {
+        let remote_path = remote_path.as_ref();
+        let request = self.http_client.request(Method::PUT, self.url(remote_path));
+        let request: RequestBuilder = self.start_request(request);
+        let request = request.header(
+            "x-obs-copy-source", 
+            format!("/{}/{}?versionId={}", self.bucket_name, remote_path.trim_start_matches('/'), version_id.as_ref())
+        );
+        let request = self.sign_request(request)?;
+
+        debug!(request_full=?request);
+
+        let result = self.http_client.execute(request).await?;
+        bail_on_failure!(result);
+
+        Ok(extract_object_meta(result.headers()).version_id)
+    }
+
     /// copy object from `source_bucket`:`source_path` to `remote_path`
     pub async fn copy_object(&self, remote_path: impl AsRef<str>, source_bucket: impl AsRef<str>, source_path: impl AsRef<str>,) -> Result<()> {
+        self.copy_object_with_headers(remote_path.as_ref(), source_bucket.as_ref(), source_path.as_ref(), HeaderMap::new()).await
+    }
+
+    /// copy object from `source_bucket`:`source_path`, encrypted with `source_sse`, to `remote_path`, encrypting it with `sse`
+    pub async fn copy_object_encrypted(
+        &self, 
+        remote_path: impl AsRef<str>, 
+        source_bucket: impl AsRef<str>, 
+        source_path: impl AsRef<str>,
+        sse: Option<&ServerSideEncryption>,
+        source_sse: Option<&ServerSideEncryption>,
+    ) -> Result<()> {
+        let mut headers = sse.map(sse_write_headers).transpose()?.unwrap_or_default();
+        headers.extend(source_sse.map(sse_copy_source_headers).transpose()?.unwrap_or_default());
+        self.copy_object_with_headers(remote_path.as_ref(), source_bucket.as_ref(), source_path.as_ref(), headers).await
+    }
+
+    /// copy object from `source_bucket`:`source_path` to `remote_path`, setting its content headers, metadata, 
+    /// storage class and encryption by `options`. The source's content headers and metadata are kept unless `options` set any of them
+    pub async fn copy_object_with_options(
+        &self, 
+        remote_path: impl AsRef<str>, 
+        source_bucket: impl AsRef<str>, 
+        source_path: impl AsRef<str>,
+        options: &PutObjectOptions,
+    ) -> Result<()> {
+        self.copy_object_with_headers(remote_path.as_ref(), source_bucket.as_ref(), source_path.as_ref(), copy_object_headers(options)?).await
+    }
+
+    /// copy object from `source_bucket`:`source_path` to `remote_path` if the source object meets `conditions`, 
+    /// failing with [CloudRuError::PreconditionFailed] otherwise
+    pub async fn copy_object_conditional(
+        &self, 
+        remote_path: impl AsRef<str>, 
+        source_bucket: impl AsRef<str>, 
+        source_path: impl AsRef<str>,
+        conditions: &ObjectConditions,
+    ) -> Result<()> {
+        let headers = condition_headers(conditions, "x-obs-copy-source-")?;
+        self.copy_object_with_headers(remote_path.as_ref(), source_bucket.as_ref(), source_path.as_ref(), headers).await
+    }
+
+    async fn copy_object_with_headers(&self, remote_path: &str, source_bucket: &str, source_path: &str, headers: HeaderMap) -> Result<()> {
         let request = self.http_client.request(Method::PUT, self.url(remote_path));
         let request: RequestBuilder = self.start_request(request);
-        let request = request.header(
+        let request = request.headers(headers).header(
             "x-obs-copy-source", 
-            format!("/{}/{}", source_bucket.as_ref(), source_path.as_ref())
+            format!("/{}/{}", source_bucket, source_path)
         );
         let request = self.sign_request(request)?;
 
@@ -253,11 +810,105 @@ impl Bucket {
         Ok(())
     }
 
+    /// copy the objects under `source_prefix` in the `source` bucket to this bucket, replacing `source_prefix` of their keys 
+    /// with `target_prefix`. Up to `options.concurrency` objects are copied simultaneously, the ones larger than 
+    /// [COPY_OBJECT_MAX_SIZE] are copied in parts, keeping their content headers and metadata. The prefix is listed 
+    /// page by page as the copy goes; the keys failed to copy are reported without stopping the others, 
+    /// while a failure to list stops the copy and is returned as the error. Within the same bucket, the prefixes 
+    /// must not overlap, e.g. `a/` cannot be copied to `a/b/`
+    pub async fn copy_prefix(&self, source: &Bucket, source_prefix: &str, target_prefix: &str, options: &CopyPrefixOptions) -> Result<CopyPrefixReport> {
+        self.transfer_prefix(source, source_prefix, target_prefix, options, false).await
+    }
+
+    /// move the objects under `source_prefix` in the `source` bucket to this bucket as [Self::copy_prefix] does, 
+    /// deleting each source object once it is copied
+    pub async fn move_prefix(&self, source: &Bucket, source_prefix: &str, target_prefix: &str, options: &CopyPrefixOptions) -> Result<CopyPrefixReport> {
+        self.transfer_prefix(source, source_prefix, target_prefix, options, true).await
+    }
+
+    async fn transfer_prefix(&self, source: &Bucket, source_prefix: &str, target_prefix: &str, options: &CopyPrefixOptions, delete_source: bool) -> Result<CopyPrefixReport> {
+        check_transfer_prefixes(&source.bucket_name, source_prefix, &self.bucket_name, target_prefix)?;
+        let request = ListObjectsRequest { prefix: Some(source_prefix), ..Default::default() };
+        let mut results = pin!(source.iter_objects(request)
+            .map_ok(|contents| async move {
+                let mut rv = self.copy_prefixed_object(source, &contents, source_prefix, target_prefix, options).await;
+                if rv.is_ok() && delete_source {
+                    rv = source.delete_object(&contents.key).await;
+                }
+                Ok((contents.key, rv))
+            })
+            .try_buffer_unordered(options.concurrency.max(1)));
+
+        let mut report = CopyPrefixReport::default();
+        while let Some((key, rv)) = results.try_next().await.map_err(|e| e.cx(format!("listing {source_prefix}")))? {
+            match rv {
+                Ok(()) => report.copied.push(key),
+                Err(e) => report.failed.push((key, e)),
+            }
+        }
+        Ok(report)
+    }
+
+    async fn copy_prefixed_object(&self, source: &Bucket, contents: &ListObjectsContents, source_prefix: &str, target_prefix: &str, options: &CopyPrefixOptions) -> Result<()> {
+        let target_key = format!("{target_prefix}{}", contents.key.strip_prefix(source_prefix).unwrap_or(&contents.key));
+        if contents.size > COPY_OBJECT_MAX_SIZE {
+            // a multipart copy does not keep the content headers and metadata by itself; 
+            // the parts are copied one by one, since the objects are already copied concurrently
+            let object_options = PutObjectOptions::from_meta(&source.get_object_meta(&contents.key).await?);
+            let multipart = MultipartUploadOptions { part_size: options.part_size, concurrency: 1 };
+            self.copy_object_multipart_with_options(target_key, &source.bucket_name, &contents.key, contents.size, &object_options, &multipart).await.map(|_| ())
+        } else {
+            self.copy_object(target_key, &source.bucket_name, &contents.key).await
+        }
+    }
+
+    /// pre-signed URL to download the object at `remote_path`, valid for `expires_in`
+    pub fn presign_get(&self, remote_path: impl AsRef<str>, expires_in: Duration) -> Result<Url> {
+        self.presign(Method::GET, remote_path, expires_in)
+    }
+
+    /// pre-signed URL to upload the object at `remote_path`, valid for `expires_in`. 
+    /// The upload must be sent without `Content-Type` and `Content-MD5` headers, since they are not signed
+    pub fn presign_put(&self, remote_path: impl AsRef<str>, expires_in: Duration) -> Result<Url> {
+        self.presign(Method::PUT, remote_path, expires_in)
+    }
+
+    fn presign(&self, method: Method, remote_path: impl AsRef<str>, expires_in: Duration) -> Result<Url> {
+        let mut request = self.http_client.request(method, self.url(remote_path)).build()?;
+        let expires = time::OffsetDateTime::now_utc() + expires_in;
+        let credentials = self.credentials.credentials()?;
+        presign(self.signing_name(), &R { r: &mut request }, expires.unix_timestamp(), &credentials.ak, &credentials.sk, credentials.security_token.as_deref())
+    }
+
+    /// signed form fields for a browser-based POST upload to the bucket, restricted by `policy`
+    pub fn post_policy_form(&self, policy: &PostPolicy) -> Result<PostPolicyForm> {
+        let expiration = time::OffsetDateTime::now_utc() + policy.expires_in;
+        let credentials = self.credentials.credentials()?;
+        let fields = post_policy_fields(&self.bucket_name, policy, expiration, &credentials.ak, &credentials.sk, credentials.security_token.as_deref())?;
+        Ok(PostPolicyForm { url: self.address.base_url.clone(), fields })
+    }
+
     /// get object's metadata
     /// @see https://support.hc.sbercloud.ru/api/obs/obs_04_0084.html
     pub async fn get_object_meta(&self, remote_path: impl AsRef<str>) -> Result<ObjectMeta> {
+        self.get_object_meta_with_headers(remote_path.as_ref(), HeaderMap::new()).await
+    }
+
+    /// get metadata of the object encrypted with `sse`
+    pub async fn get_object_meta_encrypted(&self, remote_path: impl AsRef<str>, sse: &ServerSideEncryption) -> Result<ObjectMeta> {
+        self.get_object_meta_with_headers(remote_path.as_ref(), sse_read_headers(sse)?).await
+    }
+
+    /// get object's metadata if `conditions` are met, 
+    /// failing with [CloudRuError::NotModified] or [CloudRuError::PreconditionFailed] otherwise
+    pub async fn get_object_meta_conditional(&self, remote_path: impl AsRef<str>, conditions: &ObjectConditions) -> Result<ObjectMeta> {
+        self.get_object_meta_with_headers(remote_path.as_ref(), condition_headers(conditions, "")?).await
+    }
+
+    async fn get_object_meta_with_headers(&self, remote_path: &str, headers: HeaderMap) -> Result<ObjectMeta> {
         let request = self.http_client.request(Method::HEAD, self.url(remote_path));
         let request: RequestBuilder = self.start_request(request);
+        let request = request.headers(headers);
         let request = self.sign_request(request)?;
 
         debug!(request_full=?request);
@@ -268,7 +919,16 @@ impl Bucket {
         Ok(extract_object_meta(result.headers()))             
     }
 
-    /// get bucket metadata
+    /// whether the bucket exists (and is accessible)
+    pub async fn exists(&self) -> Result<bool> {
+        match self.get_bucket_meta().await {
+            Ok(_) => Ok(true),
+            Err(CloudRuError::NoSuchBucket(_)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// get bucket metadata, failing with [CloudRuError::NoSuchBucket] if the bucket does not exist
     pub async fn get_bucket_meta(&self) -> Result<BucketMeta> {
         let request = self.http_client.head(self.url("/"));
         let request: RequestBuilder = self.start_request(request);
@@ -277,6 +937,9 @@ impl Bucket {
         debug!(request_full=?request);
 
         let result = self.http_client.execute(request).await?;
+        if result.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(CloudRuError::NoSuchBucket(self.bucket_name.clone()))
+        }
         bail_on_failure!(result);
 
         Ok(extract_bucket_meta(result.headers()))
@@ -284,10 +947,20 @@ impl Bucket {
 
     /// create file-like IO object that track r/w position
     pub async fn object_io(&self, remote_path: impl AsRef<str>) -> Result<ObjectIO> {
-        let remote_path = remote_path.as_ref().to_string();
+        self.open_object_io(remote_path.as_ref(), None).await
+    }
+
+    /// create file-like IO object over the object encrypted with `sse`. Data written is encrypted with `sse` as well
+    pub async fn object_io_encrypted(&self, remote_path: impl AsRef<str>, sse: ServerSideEncryption) -> Result<ObjectIO> {
+        self.open_object_io(remote_path.as_ref(), Some(sse)).await
+    }
+
+    async fn open_object_io(&self, remote_path: &str, encryption: Option<ServerSideEncryption>) -> Result<ObjectIO> {
+        let remote_path = remote_path.to_string();
+        let read_headers = object_io_sse_headers(encryption.as_ref(), false)?;
 
         // yield 0 if it is 404 error
-        let len = match self.get_object_meta(&remote_path).await {
+        let len = match self.get_object_meta_with_headers(&remote_path, read_headers).await {
             Ok(metadata) => metadata.content_length.ok_or_else(
                 || CloudRuError::UnknownObjectLength(remote_path.clone())
             )?,
@@ -304,10 +977,428 @@ impl Bucket {
         let bucket_meta = bucket.get_bucket_meta().await?;
         let fs_type = FsType::from_bucket_meta(&bucket_meta);
          
-        Ok(ObjectIO { remote_path, bucket, fs_type, pos, len })
+        Ok(ObjectIO { 
+            remote_path, bucket, fs_type, pos, len, encryption,
+            #[cfg(feature = "tokio")]
+            state: AsyncState { op: None, seek: None, read_ahead: ReadAhead::new(OBJECT_IO_DEFAULT_READ_AHEAD) },
+        })
+    }
+
+    /// sync the local directory `local_dir` with the objects under `prefix` in `direction`. 
+    /// The files missing at the destination or differing in size, modification time (the source is newer) or, 
+    /// with `options.checksum`, MD5 are transferred; the extraneous ones are deleted if `options.delete` is set.
+    /// Returns the steps done, or the ones planned if `options.dry_run` is set
+    pub async fn sync(&self, local_dir: impl AsRef<Path>, prefix: impl AsRef<str>, direction: SyncDirection, options: &SyncOptions) -> Result<SyncReport> {
+        let local_dir = local_dir.as_ref();
+        let prefix = sync_prefix(prefix.as_ref());
+        let local = {
+            let local_dir = local_dir.to_owned();
+            unblock(move || local_sync_entries(&local_dir)).await?
+        };
+        let request = ListObjectsRequest { prefix: Some(&prefix), ..Default::default() };
+        let mut remote = vec![];
+        let mut objects = pin!(self.iter_objects(request));
+        while let Some(contents) = objects.try_next().await? {
+            remote.extend(remote_sync_entry(&prefix, &contents));
+        }
+        // with `options.checksum` the local files are hashed while planning
+        let report = {
+            let (local_dir, options) = (local_dir.to_owned(), options.clone());
+            unblock(move || plan_sync(direction, local, remote, &options, |path| file_md5(&local_dir.join(path)))).await?
+        };
+        if !options.dry_run {
+            for action in &report.actions {
+                self.sync_action(local_dir, &prefix, action).await.cxd(|| format!("sync: {action:?}"))?;
+            }
+        }
+        Ok(report)
+    }
+
+    async fn sync_action(&self, local_dir: &Path, prefix: &str, action: &SyncAction) -> Result<()> {
+        debug!(?action, "sync");
+        match action {
+            SyncAction::Upload { path, size } => {
+                let (key, local_path) = (format!("{prefix}{path}"), local_dir.join(path));
+                if *size > MULTIPART_DEFAULT_PART_SIZE {
+                    self.upload_file(key, local_path, &MultipartUploadOptions::default()).await.map(|_| ())
+                } else {
+                    self.put_object(key, unblock(move || std::fs::read(local_path)).await?).await
+                }
+            }
+            SyncAction::Download { path, .. } => {
+                // the chunks are written in batches to keep the hand-offs to the blocking threads few
+                const WRITE_BATCH: usize = 1024 * 1024;
+                let (key, local_path) = (format!("{prefix}{path}"), local_dir.join(path));
+                // an existing file is replaced only once the download completes
+                let download_path = sync_download_path(&local_path);
+                let rv = async {
+                    let download_path = download_path.clone();
+                    let mut file = unblock(move || {
+                        if let Some(parent) = download_path.parent() {
+                            std::fs::create_dir_all(parent)?;
+                        }
+                        File::create(&download_path)
+                    }).await?;
+                    let mut stream = pin!(self.get_object_stream(key).await?);
+                    let mut batch = Vec::with_capacity(WRITE_BATCH);
+                    while let Some(chunk) = stream.try_next().await? {
+                        batch.extend_from_slice(&chunk);
+                        if batch.len() >= WRITE_BATCH {
+                            file = write_batch(file, std::mem::replace(&mut batch, Vec::with_capacity(WRITE_BATCH))).await?;
+                        }
+                    }
+                    write_batch(file, batch).await?;
+                    Ok::<_, CloudRuError>(())
+                }.await;
+                unblock(move || match rv {
+                    Ok(()) => Ok(std::fs::rename(&download_path, &local_path)?),
+                    Err(e) => {
+                        let _ = std::fs::remove_file(&download_path);
+                        Err(e)
+                    }
+                }).await
+            }
+            SyncAction::DeleteObject { path } => self.delete_object(format!("{prefix}{path}")).await,
+            SyncAction::DeleteFile { path } => {
+                let local_path = local_dir.join(path);
+                Ok(unblock(move || std::fs::remove_file(local_path)).await?)
+            }
+        }
+    }
+
+    /// initiate multipart upload of the object at `remote_path`
+    /// @see https://support.hc.sbercloud.ru/api/obs/obs_04_0098.html
+    pub async fn initiate_multipart_upload(&self, remote_path: impl AsRef<str>) -> Result<InitiateMultipartUploadResult> {
+        self.initiate_multipart_upload_with_options(remote_path, &PutObjectOptions::default()).await
+    }
+
+    /// initiate multipart upload of the object at `remote_path` with content headers, metadata, storage class and encryption set by `options`
+    pub async fn initiate_multipart_upload_with_options(&self, remote_path: impl AsRef<str>, options: &PutObjectOptions) -> Result<InitiateMultipartUploadResult> {
+        let url = self.url(remote_path).with_var_key("uploads");
+        let request = self.http_client.request(Method::POST, url);
+        let request: RequestBuilder = self.start_request(request);
+        let request = request.headers(put_object_headers(options)?);
+        let request = self.sign_request(request)?;
+
+        debug!(request_full=?request);
+
+        let result = self.http_client.execute(request).await?;
+        bail_on_failure!(result);
+
+        parse_xml(result).await
+    }
+
+    /// upload part `part_number` (starting from 1) of the multipart upload `upload_id`, filling it with data read from `input`
+    /// 
+    /// Returns the ETag of the part
+    /// @see https://support.hc.sbercloud.ru/api/obs/obs_04_0099.html
+    pub async fn upload_part<I>(&self, remote_path: impl AsRef<str>, upload_id: impl AsRef<str>, part_number: u32, input: I) -> Result<String> 
+    where Body: From<I> {
+        let url = self.url(remote_path)
+            .with_var("partNumber", format!("{part_number}"))
+            .with_var("uploadId", upload_id);
+        let request = self.http_client.request(Method::PUT, url);
+        let request: RequestBuilder = self.start_request(request);
+        let body = Body::from(input);
+        let request = match body.as_bytes() {
+            Some(data) => self.with_content_md5(request, data),
+            None => request,
+        };
+        let request = request.body(body);
+        let request = self.sign_request(request)?;
+
+        debug!(request_full=?request);
+
+        let result = self.http_client.execute(request).await?;
+        bail_on_failure!(result);
+
+        let etag = result.headers().get("etag").ok_or(CloudRuError::MissingHeader("ETag"))?;
+        Ok(etag.to_str()?.to_owned())
+    }
+
+    /// fill part `part_number` of the multipart upload `upload_id` with `range` of bytes of the object `source_bucket`:`source_path`
+    /// 
+    /// Returns the ETag of the part
+    pub async fn upload_part_copy(
+        &self, 
+        remote_path: impl AsRef<str>, 
+        upload_id: impl AsRef<str>, 
+        part_number: u32, 
+        source_bucket: impl AsRef<str>, 
+        source_path: impl AsRef<str>,
+        range: impl RangeBounds<u64>,
+    ) -> Result<String> {
+        let url = self.url(remote_path)
+            .with_var("partNumber", format!("{part_number}"))
+            .with_var("uploadId", upload_id);
+        let request = self.http_client.request(Method::PUT, url);
+        let request: RequestBuilder = self.start_request(request);
+        let request = request.header("x-obs-copy-source", format!("/{}/{}", source_bucket.as_ref(), source_path.as_ref()));
+        let request = match range_header(range) {
+            Some(range) => request.header("x-obs-copy-source-range", range),
+            None => request,
+        };
+        let request = self.sign_request(request)?;
+
+        debug!(request_full=?request);
+
+        let result = self.http_client.execute(request).await?;
+        bail_on_failure!(result);
+
+        let result: CopyPartResult = parse_xml(result).await?;
+        Ok(result.etag)
+    }
+
+    /// copy object `source_bucket`:`source_path` of `source_size` bytes to `remote_path` in parts, 
+    /// as required for the objects larger than [COPY_OBJECT_MAX_SIZE]. 
+    /// Unlike [Self::copy_object], the content headers and metadata of the source are not carried over, 
+    /// see [Self::copy_object_multipart_with_options]
+    pub async fn copy_object_multipart(
+        &self, 
+        remote_path: impl AsRef<str>, 
+        source_bucket: impl AsRef<str>, 
+        source_path: impl AsRef<str>,
+        source_size: u64,
+        options: &MultipartUploadOptions,
+    ) -> Result<CompleteMultipartUploadResult> {
+        self.copy_object_multipart_with_options(remote_path, source_bucket, source_path, source_size, &PutObjectOptions::default(), options).await
+    }
+
+    /// copy object `source_bucket`:`source_path` of `source_size` bytes to `remote_path` in parts, 
+    /// with content headers, metadata, storage class and encryption set by `object_options`, 
+    /// e.g. [PutObjectOptions::from_meta] of the source
+    pub async fn copy_object_multipart_with_options(
+        &self, 
+        remote_path: impl AsRef<str>, 
+        source_bucket: impl AsRef<str>, 
+        source_path: impl AsRef<str>,
+        source_size: u64,
+        object_options: &PutObjectOptions,
+        options: &MultipartUploadOptions,
+    ) -> Result<CompleteMultipartUploadResult> {
+        let (remote_path, source_bucket, source_path) = (remote_path.as_ref(), source_bucket.as_ref(), source_path.as_ref());
+        let parts = plan_parts(source_size, options.part_size);
+
+        let upload_id = self.initiate_multipart_upload_with_options(remote_path, object_options).await?.upload_id;
+
+        match self.copy_parts(remote_path, &upload_id, source_bucket, source_path, &parts, options.concurrency).await {
+            Ok(completed) => self.complete_multipart_upload(remote_path, &upload_id, completed).await,
+            Err(e) => {
+                if let Err(abort_error) = self.abort_multipart_upload(remote_path, &upload_id).await {
+                    warn!(upload_id, %abort_error, "failed to abort multipart copy");
+                }
+                Err(e)
+            }
+        }
     }
 
+    async fn copy_parts(&self, remote_path: &str, upload_id: &str, source_bucket: &str, source_path: &str, parts: &[PartRange], concurrency: usize) -> Result<Vec<CompletedPart>> {
+        let mut completed: Vec<CompletedPart> = stream::iter(parts.iter().copied())
+            .map(|part| async move {
+                let range = part.offset..part.offset + part.size;
+                self.upload_part_copy(remote_path, upload_id, part.part_number, source_bucket, source_path, range).await
+                    .map(|etag| CompletedPart { part_number: part.part_number, etag })
+                    .map_err(|e| e.cx(format!("copying part {}", part.part_number)))
+            })
+            .buffer_unordered(concurrency.max(1))
+            .try_collect()
+            .await?;
+
+        completed.sort_by_key(|p| p.part_number);
+        Ok(completed)
+    }
+
+    /// combine uploaded `parts` into the object at `remote_path`, completing the multipart upload `upload_id`
+    /// @see https://support.hc.sbercloud.ru/api/obs/obs_04_0102.html
+    pub async fn complete_multipart_upload(&self, remote_path: impl AsRef<str>, upload_id: impl AsRef<str>, mut parts: Vec<CompletedPart>) -> Result<CompleteMultipartUploadResult> {
+        parts.sort_by_key(|p| p.part_number);
+        let body = serde_xml_rs::to_string(&CompleteMultipartUpload { parts })?;
+
+        let url = self.url(remote_path).with_var("uploadId", upload_id);
+        let request = self.http_client.request(Method::POST, url);
+        let request: RequestBuilder = self.start_request(request);
+        let request = request.header("content-type", "application/xml").body(body);
+        let request = self.sign_request(request)?;
+
+        debug!(request_full=?request);
 
+        let result = self.http_client.execute(request).await?;
+        bail_on_failure!(result);
+
+        parse_xml(result).await
+    }
+
+    /// abort the multipart upload `upload_id`, deleting the uploaded parts
+    /// @see https://support.hc.sbercloud.ru/api/obs/obs_04_0103.html
+    pub async fn abort_multipart_upload(&self, remote_path: impl AsRef<str>, upload_id: impl AsRef<str>) -> Result<()> {
+        let url = self.url(remote_path).with_var("uploadId", upload_id);
+        let request = self.http_client.request(Method::DELETE, url);
+        let request: RequestBuilder = self.start_request(request);
+        let request = self.sign_request(request)?;
+
+        debug!(request_full=?request);
+
+        let result = self.http_client.execute(request).await?;
+        bail_on_failure!(result);
+
+        Ok(())
+    }
+
+    /// list the parts uploaded within the multipart upload `upload_id`, starting after `part_number_marker`
+    /// @see https://support.hc.sbercloud.ru/api/obs/obs_04_0101.html
+    pub async fn list_parts(&self, remote_path: impl AsRef<str>, upload_id: impl AsRef<str>, part_number_marker: Option<u32>, max_parts: Option<u32>) -> Result<ListPartsResult> {
+        let url = self.url(remote_path)
+            .with_var("uploadId", upload_id)
+            .with_var_opt("part-number-marker", part_number_marker.map(|s| format!("{s}")))
+            .with_var_opt("max-parts", max_parts.map(|s| format!("{s}")));
+        let request = self.http_client.request(Method::GET, url);
+        let request: RequestBuilder = self.start_request(request);
+        let request = self.sign_request(request)?;
+
+        debug!(request_full=?request);
+
+        let result = self.http_client.execute(request).await?;
+        bail_on_failure!(result);
+
+        parse_xml(result).await
+    }
+
+    /// list all parts uploaded within the multipart upload `upload_id`, following the pagination
+    pub async fn list_all_parts(&self, remote_path: impl AsRef<str>, upload_id: impl AsRef<str>) -> Result<Vec<PartInfo>> {
+        let (remote_path, upload_id) = (remote_path.as_ref(), upload_id.as_ref());
+        let mut parts = vec![];
+        let mut marker = None;
+        loop {
+            let result = self.list_parts(remote_path, upload_id, marker, None).await?;
+            parts.extend(result.parts.unwrap_or_default());
+            match (result.is_truncated, result.next_part_number_marker) {
+                (Some(true), Some(next)) => marker = Some(next),
+                _ => break,
+            }
+        }
+        Ok(parts)
+    }
+
+    /// upload local file `local_path` to `remote_path` using multipart upload.
+    /// 
+    /// The file is split into parts of `options.part_size`, at most `options.concurrency` of which are uploaded simultaneously.
+    /// The parts are read from the file off the async executor. On failure, the multipart upload is aborted.
+    pub async fn upload_file(&self, remote_path: impl AsRef<str>, local_path: impl AsRef<Path>, options: &MultipartUploadOptions) -> Result<CompleteMultipartUploadResult> {
+        let remote_path = remote_path.as_ref();
+        let local_path = local_path.as_ref();
+        let (len, _) = { let local_path = local_path.to_owned(); unblock(move || file_size_and_mtime(&local_path)).await? };
+        let parts = plan_parts(len, options.part_size);
+
+        let upload_id = self.initiate_multipart_upload(remote_path).await?.upload_id;
+
+        match self.upload_parts(remote_path, &upload_id, local_path, &parts, options.concurrency, &|_| Box::pin(async { Ok(()) })).await {
+            Ok(completed) => self.complete_multipart_upload(remote_path, &upload_id, completed).await,
+            Err(e) => {
+                if let Err(abort_error) = self.abort_multipart_upload(remote_path, &upload_id).await {
+                    warn!(upload_id, %abort_error, "failed to abort multipart upload");
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// upload local file `local_path` to `remote_path` using multipart upload, which can be resumed after a failure.
+    /// 
+    /// The upload state is saved to `checkpoint_path` after each uploaded part. If the checkpoint file exists and describes 
+    /// an upload of the same unchanged file to the same object, the upload is resumed: the uploaded parts are listed and 
+    /// only the missing ones are sent. Otherwise a new upload is initiated. The checkpoint file is removed on success.
+    /// 
+    /// Unlike [Self::upload_file], the multipart upload is not aborted on failure.
+    pub async fn upload_file_resumable(&self, remote_path: impl AsRef<str>, local_path: impl AsRef<Path>, checkpoint_path: impl AsRef<Path>, options: &MultipartUploadOptions) -> Result<CompleteMultipartUploadResult> {
+        let remote_path = remote_path.as_ref();
+        let local_path = local_path.as_ref();
+        let checkpoint_path = checkpoint_path.as_ref().to_owned();
+        let (len, mtime) = { let local_path = local_path.to_owned(); unblock(move || file_size_and_mtime(&local_path)).await? };
+
+        let mut resumed = None;
+        let loaded = { let checkpoint_path = checkpoint_path.clone(); unblock(move || UploadCheckpoint::load(&checkpoint_path)).await? };
+        if let Some(mut checkpoint) = loaded {
+            if checkpoint.matches(&self.bucket_name, remote_path, len, mtime) {
+                let plan = plan_parts(len, checkpoint.part_size);
+                match self.list_all_parts(remote_path, &checkpoint.upload_id).await {
+                    Ok(uploaded) => {
+                        let missing = checkpoint.reconcile(&uploaded, &plan);
+                        resumed = Some((checkpoint, missing));
+                    }
+                    Err(e) if e.is_api_not_found() => 
+                        debug!(upload_id=checkpoint.upload_id, "multipart upload not found, starting over"),
+                    Err(e) => return Err(e),
+                }
+            } else if checkpoint.bucket == self.bucket_name {
+                if let Err(abort_error) = self.abort_multipart_upload(&checkpoint.key, &checkpoint.upload_id).await {
+                    warn!(upload_id=checkpoint.upload_id, %abort_error, "failed to abort stale multipart upload");
+                }
+            }
+        }
+
+        let (checkpoint, missing) = match resumed {
+            Some(resumed) => resumed,
+            None => {
+                let upload_id = self.initiate_multipart_upload(remote_path).await?.upload_id;
+                let part_size = effective_part_size(len, options.part_size);
+                let plan = plan_parts(len, part_size);
+                let checkpoint = UploadCheckpoint { 
+                    bucket: self.bucket_name.clone(), key: remote_path.to_owned(), upload_id, 
+                    file_size: len, file_mtime: mtime, part_size, parts: vec![] 
+                };
+                (checkpoint, plan)
+            }
+        };
+
+        let upload_id = checkpoint.upload_id.clone();
+        let checkpoint = Arc::new(Mutex::new(checkpoint));
+        // saves are serialized by the lock, so the last one written has all the parts added so far
+        let save = |part: Option<CompletedPart>| {
+            let (checkpoint, checkpoint_path) = (checkpoint.clone(), checkpoint_path.clone());
+            unblock(move || {
+                let mut checkpoint = checkpoint.lock().unwrap_or_else(|e| e.into_inner());
+                if let Some(part) = part {
+                    checkpoint.add_part(part);
+                }
+                checkpoint.save(&checkpoint_path)
+            })
+        };
+        save(None).await?;
+        self.upload_parts(remote_path, &upload_id, local_path, &missing, options.concurrency, &|part| Box::pin(save(Some(part.clone())))).await?;
+
+        let parts = std::mem::take(&mut checkpoint.lock().unwrap_or_else(|e| e.into_inner()).parts);
+        let result = self.complete_multipart_upload(remote_path, &upload_id, parts).await?;
+        unblock(move || std::fs::remove_file(&checkpoint_path).cxd(|| format!("removing checkpoint {}", checkpoint_path.display()))).await?;
+        Ok(result)
+    }
+
+    /// upload `parts` of `local_path`, at most `concurrency` at a time, awaiting `on_part` after each uploaded part. 
+    /// The parts are read off the async executor
+    async fn upload_parts(&self, remote_path: &str, upload_id: &str, local_path: &Path, parts: &[PartRange], concurrency: usize, 
+        on_part: &(dyn Fn(&CompletedPart) -> BoxFuture<'static, Result<()>> + Sync)
+    ) -> Result<Vec<CompletedPart>> {
+        let mut completed: Vec<CompletedPart> = stream::iter(parts.iter().copied())
+            .map(|part| async move {
+                let rv = async {
+                    let local_path = local_path.to_owned();
+                    let data = unblock(move || {
+                        let mut file = File::open(&local_path).cxd(|| format!("opening {}", local_path.display()))?;
+                        read_part(&mut file, &part)
+                    }).await?;
+                    let etag = self.upload_part(remote_path, upload_id, part.part_number, data).await?;
+                    let completed = CompletedPart { part_number: part.part_number, etag };
+                    on_part(&completed).await?;
+                    Ok(completed)
+                };
+                rv.await.map_err(|e: CloudRuError| e.cx(format!("uploading part {}", part.part_number)))
+            })
+            .buffer_unordered(concurrency.max(1))
+            .try_collect()
+            .await?;
+
+        completed.sort_by_key(|p| p.part_number);
+        Ok(completed)
+    }
 }
 
 
@@ -317,13 +1408,17 @@ pub struct ObjectIO {
     fs_type: FsType,
     pos: u64,
     len: u64,
+    encryption: Option<ServerSideEncryption>,
+    #[cfg(feature = "tokio")]
+    state: AsyncState,
 } 
 
 impl ObjectIO {
     /// Synchronizes cached position with the length of the actual object, so that we can resume appending to it.
     /// The object must exist and must be created in append mode.
     pub async fn sync_position(&mut self) -> Result<u64> {
-        let meta = self.bucket.get_object_meta(&self.remote_path).await?;
+        let headers = object_io_sse_headers(self.encryption.as_ref(), false)?;
+        let meta = self.bucket.get_object_meta_with_headers(&self.remote_path, headers).await?;
         self.len = meta.content_length.ok_or_else(
             || CloudRuError::UnknownObjectLength(self.remote_path.clone())
         )?;
@@ -338,37 +1433,73 @@ impl ObjectIO {
 
     /// read `len` bytes from the bucket 
     pub async fn read(&mut self, len: usize) -> Result<Bytes> {
-        if len == 0 {
-            return Ok(Bytes::new())
+        let headers = object_io_sse_headers(self.encryption.as_ref(), false)?;
+        let rv = read_range(&self.bucket, &self.remote_path, self.pos, len, headers).await?;
+        self.pos += rv.len() as u64;
+        Ok(rv)
+    }
+
+    /// write/append `data` to the bucket 
+    pub async fn write(&mut self, data: Bytes) -> Result<usize> {
+        self.discard_read_ahead();
+        let write_op= self.fs_type.eval_write_op(self.pos, self.len)?;
+        let headers = object_io_sse_headers(self.encryption.as_ref(), self.len == 0)?;
+        let data_len = write_at(&self.bucket, &self.remote_path, write_op, data, headers).await?;
+        self.pos += data_len as u64;
+        if self.pos > self.len { self.len = self.pos }
+        Ok(data_len)
+    }
+
+    /// drops the data read ahead by [tokio::io::AsyncRead] implementation, since it is being overwritten
+    fn discard_read_ahead(&mut self) {
+        #[cfg(feature = "tokio")]
+        {
+            self.state.read_ahead.invalidate();
+            if matches!(self.state.op, Some(PendingOp::Read(..))) { self.state.op = None }
         }
+    }
 
-        let (first, last) = (self.pos, self.pos + len as u64 - 1);
-        let range = format!("bytes={first}-{last}");
+    /// Max size of the read-ahead window of [tokio::io::AsyncRead] reads, in bytes
+    #[cfg(feature = "tokio")]
+    pub fn read_ahead(&self) -> usize { self.state.read_ahead.max_window() }
 
-        let request = self.bucket.http_client.request(Method::GET, self.bucket.url(&self.remote_path));
-        let request: RequestBuilder = self.bucket.start_request(request);
-        let request = request.header("range", range);
-        let request = self.bucket.sign_request(request)?;
-    
-        debug!(request_full=?request);
+    /// Sets max size of the read-ahead window of [tokio::io::AsyncRead] reads, which fetch up to this many bytes at once 
+    /// while the access is sequential; the window shrinks on random access. Zero disables read-ahead. 
+    /// The default is [OBJECT_IO_DEFAULT_READ_AHEAD].
+    #[cfg(feature = "tokio")]
+    pub fn set_read_ahead(&mut self, max_window: usize) { self.state.read_ahead.set_max_window(max_window) }
+}
 
-        let result = self.bucket.http_client.execute(request).await.cx("Client::execute")?;
-        if result.status().is_success() {
-            if result.status().as_u16() != 206 { //Partial content
-                return Err(CloudRuError::ReturningRangesNotSupported)
-            }
-            let rv: Bytes = result.bytes().await?;
-            self.pos += rv.len() as u64;
-            Ok(rv)
-        } else {
-            Err(CloudRuError::API(result.status(), result.text().await.cx("text")?))
-        }
+/// read `len` bytes of `remote_path` starting at `pos`
+async fn read_range(bucket: &Bucket, remote_path: &str, pos: u64, len: usize, headers: HeaderMap) -> Result<Bytes> {
+    if len == 0 {
+        return Ok(Bytes::new())
+    }
+
+    let (first, last) = (pos, pos + len as u64 - 1);
+    let range = format!("bytes={first}-{last}");
+
+    let request = bucket.http_client.request(Method::GET, bucket.url(remote_path));
+    let request: RequestBuilder = bucket.start_request(request);
+    let request = request.header("range", range).headers(headers);
+    let request = bucket.sign_request(request)?;
 
+    debug!(request_full=?request);
+
+    let result = bucket.http_client.execute(request).await.cx("Client::execute")?;
+    if result.status().is_success() {
+        if result.status().as_u16() != 206 { //Partial content
+            return Err(CloudRuError::ReturningRangesNotSupported)
+        }
+        Ok(result.bytes().await?)
+    } else {
+        Err(CloudRuError::from_api_response(result.status(), result.text().await.cx("text")?))
     }
+}
 
-    /// write/append `data` to the bucket 
-    pub async fn write(&mut self, data: Bytes) -> Result<usize> {
-        /*
+/// write `data` to `remote_path` as prescribed by `write_op`
+async fn write_at(bucket: &Bucket, remote_path: &str, write_op: WriteOp, data: Bytes, headers: HeaderMap) -> Result<usize> {
+    /*
 POST /ObjectName?append&position=Position HTTP/1.1 
 Host: bucketname.obs.region.example.com
 Content-Type: application/xml 
@@ -377,8 +1508,8 @@ Authorization: authorization
 Date: date
 <Optional Additional Header> 
 <object Content>
-        */
-        /*
+    */
+    /*
 PUT /ObjectName?modify&position=Position HTTP/1.1
 Host: bucketname.obs.region.example.com
 Content-Type: type
@@ -386,25 +1517,22 @@ Content-Length: length
 Authorization: authorization
 Date: date
 <object Content>
-         */
-        let write_op= self.fs_type.eval_write_op(self.pos, self.len)?;
-        let url = write_op.modify_url(self.bucket.url(&self.remote_path));
-        let data_len = data.len();
-
-        let request = self.bucket.http_client.request(write_op.method, url)
-            .header("Content-Length", format!("{data_len}"));
-        let request: RequestBuilder = self.bucket.start_request(request);
-        let request = request.body(data);
-        let request = self.bucket.sign_request(request)?;
-
-        debug!(request_full=?request);
-
-        let result = self.bucket.http_client.execute(request).await.cx("Client::execute")?;
-        bail_on_failure!(result);
-        self.pos += data_len as u64;
-        if self.pos > self.len { self.len = self.pos }
-        Ok(data_len)
-    }
+    */
+    let url = write_op.modify_url(bucket.url(remote_path));
+    let data_len = data.len();
+
+    let request = bucket.http_client.request(write_op.method, url)
+        .header("Content-Length", format!("{data_len}"))
+        .headers(headers);
+    let request: RequestBuilder = bucket.start_request(request);
+    let request = bucket.with_content_md5(request, &data).body(data);
+    let request = bucket.sign_request(request)?;
+
+    debug!(request_full=?request);
+
+    let result = bucket.http_client.execute(request).await.cx("Client::execute")?;
+    bail_on_failure!(result);
+    Ok(data_len)
 }
 
 impl io::Seek for ObjectIO {
@@ -419,4 +1547,146 @@ impl io::Seek for ObjectIO {
         };
         Ok(self.pos)
     }
-}
\ No newline at end of file
+}
+
+/// The operation started by a `poll_*` method of the tokio I/O traits and not yet completed
+#[cfg(feature = "tokio")]
+enum PendingOp {
+    /// fetch at the position
+    Read(u64, futures_util::future::BoxFuture<'static, Result<Bytes>>),
+    Write(futures_util::future::BoxFuture<'static, Result<usize>>),
+}
+
+#[cfg(feature = "tokio")]
+struct AsyncState {
+    op: Option<PendingOp>,
+    /// seek started while a write is pending, applied once it completes
+    seek: Option<io::SeekFrom>,
+    read_ahead: ReadAhead,
+}
+
+#[cfg(feature = "tokio")]
+impl ObjectIO {
+    /// drives the pending write (if any) to completion, updating the position and length
+    fn poll_pending_write(&mut self, cx: &mut std::task::Context<'_>) -> std::task::Poll<io::Result<()>> {
+        use std::task::{ready, Poll};
+        let Some(PendingOp::Write(f)) = &mut self.state.op else { return Poll::Ready(Ok(())) };
+        let rv = ready!(f.as_mut().poll(cx));
+        self.state.op = None;
+        let data_len = rv?;
+        self.pos += data_len as u64;
+        if self.pos > self.len { self.len = self.pos }
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Reads at most `len() - pos()` bytes. While the reads are sequential, each ranged GET fetches up to 
+/// [ObjectIO::read_ahead] bytes, and the following reads are served from memory
+#[cfg(feature = "tokio")]
+impl tokio::io::AsyncRead for ObjectIO {
+    fn poll_read(self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>, buf: &mut tokio::io::ReadBuf<'_>) -> std::task::Poll<io::Result<()>> {
+        use std::task::{ready, Poll};
+        let this = self.get_mut();
+        ready!(this.poll_pending_write(cx))?;
+        if buf.remaining() == 0 { return Poll::Ready(Ok(())) }
+        loop {
+            let count = this.state.read_ahead.read_buffered(this.pos, buf.initialize_unfilled());
+            if count > 0 {
+                buf.advance(count);
+                this.pos += count as u64;
+                return Poll::Ready(Ok(()));
+            }
+            if let Some(PendingOp::Read(at, f)) = &mut this.state.op {
+                let at = *at;
+                let rv = ready!(f.as_mut().poll(cx));
+                this.state.op = None;
+                let data = rv?;
+                // the fetch may have been started before a seek, then the data is kept for the reads coming back to it
+                if data.is_empty() && at == this.pos { return Poll::Ready(Ok(())) }
+                this.state.read_ahead.fill(at, Vec::from(data));
+            } else {
+                if this.pos >= this.len { return Poll::Ready(Ok(())) }
+                let len = this.state.read_ahead.plan_fetch(this.pos, buf.remaining(), this.len - this.pos);
+                let (bucket, remote_path, pos) = (this.bucket.clone(), this.remote_path.clone(), this.pos);
+                let headers = object_io_sse_headers(this.encryption.as_ref(), false)?;
+                this.state.op = Some(PendingOp::Read(pos, Box::pin(async move {
+                    read_range(&bucket, &remote_path, pos, len, headers).await
+                })));
+            }
+        }
+    }
+}
+
+/// Each write is sent as a separate append (OBS) or modify (PFS) request, as in [ObjectIO::write]. 
+/// `poll_write` accepts the data once the previous request completes and starts a request for it; the request is 
+/// driven to completion by the next write, read, seek, `poll_flush` or `poll_shutdown`, which report its failure. 
+/// [ObjectIO::pos] and [ObjectIO::len] are updated on completion, so flush before relying on them
+#[cfg(feature = "tokio")]
+impl tokio::io::AsyncWrite for ObjectIO {
+    fn poll_write(self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>, buf: &[u8]) -> std::task::Poll<io::Result<usize>> {
+        use std::task::{ready, Poll};
+        let this = self.get_mut();
+        ready!(this.poll_pending_write(cx))?;
+        if buf.is_empty() { return Poll::Ready(Ok(0)) }
+        this.discard_read_ahead();
+        let write_op = this.fs_type.eval_write_op(this.pos, this.len)?;
+        let (bucket, remote_path, data) = (this.bucket.clone(), this.remote_path.clone(), Bytes::copy_from_slice(buf));
+        let headers = object_io_sse_headers(this.encryption.as_ref(), this.len == 0)?;
+        this.state.op = Some(PendingOp::Write(Box::pin(async move {
+            write_at(&bucket, &remote_path, write_op, data, headers).await
+        })));
+        // starts sending right away; the data is accepted, so a completed request fails on the next call
+        if let Poll::Ready(Err(e)) = this.poll_pending_write(cx) { return Poll::Ready(Err(e)) }
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> std::task::Poll<io::Result<()>> {
+        self.get_mut().poll_pending_write(cx)
+    }
+
+    fn poll_shutdown(self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> std::task::Poll<io::Result<()>> {
+        self.get_mut().poll_pending_write(cx)
+    }
+}
+
+/// A seek started while a write is pending is applied once the write completes
+#[cfg(feature = "tokio")]
+impl tokio::io::AsyncSeek for ObjectIO {
+    fn start_seek(self: std::pin::Pin<&mut Self>, position: io::SeekFrom) -> io::Result<()> {
+        let this = self.get_mut();
+        if this.state.seek.is_some() {
+            return Err(io::Error::other("another seek is in progress"));
+        }
+        if matches!(this.state.op, Some(PendingOp::Write(_))) {
+            this.state.seek = Some(position);
+            return Ok(());
+        }
+        io::Seek::seek(this, position).map(|_| ())
+    }
+
+    fn poll_complete(self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> std::task::Poll<io::Result<u64>> {
+        use std::task::{ready, Poll};
+        let this = self.get_mut();
+        let written = ready!(this.poll_pending_write(cx));
+        let seek = this.state.seek.take();
+        written?;
+        if let Some(position) = seek {
+            io::Seek::seek(this, position)?;
+        }
+        Poll::Ready(Ok(this.pos))
+    }
+}
+
+#[test]
+fn test_verified_stream() {
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    let mut headers = HeaderMap::new();
+    headers.insert("etag", HeaderValue::from_static("\"5d41402abc4b2a76b9719d911017c592\"")); // md5("hello")
+    let collect = |chunks: Vec<&'static [u8]>| {
+        let stream = stream::iter(chunks.into_iter().map(|c| Ok(Bytes::from_static(c))));
+        runtime.block_on(verified_stream(stream, EtagVerifier::new(&headers)).try_collect::<Vec<Bytes>>())
+    };
+
+    assert_eq!(collect(vec![b"he", b"llo"]).unwrap().concat(), b"hello");
+    assert!(matches!(collect(vec![b"he", b"LLO"]), Err(CloudRuError::ChecksumMismatch { .. })));
+}
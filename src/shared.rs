@@ -5,6 +5,8 @@ pub mod config;
 pub(crate) mod client;
 pub(crate) mod security;
 pub(crate) mod urltools;
+pub(crate) mod timetools;
 pub(crate) mod mauth_obs;
 pub(crate) mod signing;
-pub(crate) mod obs;
\ No newline at end of file
+pub(crate) mod obs;
+pub(crate) mod iam;
\ No newline at end of file
@@ -1,11 +1,12 @@
 use super::config::*;
+use super::security::*;
 use crate::*;
 
 pub(crate) type ServiceId = &'static str;
 
 pub struct Client<HC> {
     pub(crate) config: Config,
-    pub(crate) credentials: Credentials,
+    pub(crate) credentials: SharedCredentialsProvider,
     pub(crate) http_client: HC,
 }
 
@@ -36,6 +37,7 @@ pub struct ClientBuilder {
     pub project_id: Option<String>,
     pub region: Option<String>,
     pub credentials: Option<Credentials>,
+    pub credentials_provider: Option<SharedCredentialsProvider>,
 }
 
 impl ClientBuilder {
@@ -46,6 +48,8 @@ impl ClientBuilder {
     pub fn project_id(self, arg: &str) -> Self { Self { project_id: Some(arg.to_owned()), ..self } }
     pub fn region(self, arg: &str) -> Self { Self { region: Some(arg.to_owned()), ..self } }
     pub fn credentials(self, arg: Credentials) -> Self { Self { credentials: Some(arg), ..self } }
+    /// Sets the provider queried for the credentials before each request, taking precedence over the other credential settings
+    pub fn credentials_provider(self, arg: impl Into<SharedCredentialsProvider>) -> Self { Self { credentials_provider: Some(arg.into()), ..self } }
     pub fn build_with_http_client<HC>(self, http_client: HC) -> Result<Client<HC>> {
         let (config_path, force) = self.config_file
             .map(|f| (f, true))
@@ -56,13 +60,15 @@ impl ClientBuilder {
         if let Some(project_id) = self.project_id { config.project_id = Some(project_id); }
         if let Some(region) = self.region { config.region = region; }
         
-        let credentials = if let Some(credentials) = self.credentials {
-            credentials
+        let credentials = if let Some(credentials_provider) = self.credentials_provider {
+            credentials_provider
+        } else if let Some(credentials) = self.credentials {
+            credentials.into()
         } else {
-            read_credentials(
+            SharedCredentialsProvider::new(IniCredentialsProvider::new(
                 self.credentials_file.unwrap_or_else(|| DEFAULT_CREDENTIALS_FILE.to_owned()),
                 self.credentials_id.unwrap_or_else(|| DEFAULT_CREDENTIAL.to_owned())
-            )?
+            )?)
         };
 
         Ok(Client {config, credentials, http_client })
@@ -76,6 +82,7 @@ impl ClientBuilder {
     /// Variable base names are:
     /// 
     /// * `AK` and `SK` - access key and secret key (both required)
+    /// * `SECURITY_TOKEN` - security token of temporary credentials
     /// * `SCA_CONFIG_FILE`
     /// * `SCA_CREDENTIALS_FILE`
     /// * `SCA_CREDENTIALS_ID`
@@ -84,21 +91,13 @@ impl ClientBuilder {
     /// 
     /// Example: if `env_prefix` == "P" and `env_flavor_prefix` == "F", the loader attempts to load the config file setting 
     /// first from `P_F_SCA_CONFIG_FILE`, then from `P_SCA_CONFIG_FILE`
+    /// 
+    /// The keys are read once; use [EnvCredentialsProvider] with [ClientBuilder::credentials_provider] to re-read them before each request
     pub fn from_environment(mut self, env_prefix: Option<&str>, env_flavor_prefix: Option<&str>) -> Self {
-        use std::env::var;
-        let pe: Box<dyn Fn(&str) -> std::result::Result<String, _>> = match (env_prefix, env_flavor_prefix) {
-            (None, None) => 
-                Box::new(|s| var(s)),
-            (Some(p), None) => 
-                Box::new(move |s: &str| var(&format!("{p}_{s}"))),
-            (None, Some(f)) => 
-                Box::new(move |s: &str| var(&format!("{f}_{s}")).or_else(|_| var(s))),
-            (Some(p), Some(f)) => 
-                Box::new(move |s: &str| var(&format!("{p}_{f}_{s}")).or_else(|_| var(&format!("{p}_{s}")))),
-        };
+        let pe = |s: &str| env_var(env_prefix, env_flavor_prefix, s);
         
         if let (Ok(ak), Ok(sk)) = (pe("AK"), pe("SK")) {
-            self = self.credentials(Credentials { ak, sk })
+            self = self.credentials(Credentials { ak, sk, security_token: pe("SECURITY_TOKEN").ok() })
         }
         if let Ok(config_file) = pe("SCA_CONFIG_FILE") {
             self = self.config_file(&config_file)
@@ -146,6 +145,7 @@ impl ClientBuilder {
         let vars = [
             ("AK", "Access key"),
             ("SK", "Secret key"),
+            ("SECURITY_TOKEN", "Security token of temporary credentials"),
             ("SCA_CONFIG_FILE", "Configuration file path"),
             ("SCA_CREDENTIALS_FILE", "Credentials file path"),
             ("SCA_CREDENTIALS_ID", "Id of credentials"),
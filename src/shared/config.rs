@@ -2,6 +2,7 @@ use std::collections::HashMap;
 
 use phf::phf_map;
 use crate::*;
+use crate::model::obs::AddressingStyle;
 
 pub const DEFAULT_CREDENTIALS_FILE: &'static str = "~/.cloudru/credentials";
 pub const DEFAULT_CONFIG_FILE: &'static str = "~/.cloudru/config";
@@ -10,10 +11,11 @@ pub const DEFAULT_LOG_LEVEL:  &'static str = "INFO";
 
 pub const ACCESS_KEY_ID_KEY: &'static str = "access_key_id";
 pub const SECRET_ACCESS_KEY_KEY: &'static str = "secret_access_key";
+pub const SECURITY_TOKEN_KEY: &str = "security_token";
 
 
 
-fn tildeexpand(s: String) -> String {
+pub(crate) fn tildeexpand(s: String) -> String {
     let v = std::env::var("HOME").unwrap_or("~".to_owned());
     s.replace("~", &v)
 }
@@ -21,8 +23,16 @@ fn tildeexpand(s: String) -> String {
 pub fn read_credentials(path: String, id: String) -> Result<Credentials> {
     let path = tildeexpand(path);
     let cred_ini = ini::Ini::load_from_file(&path).cxd(|| format!("reading credentials file {path}"))?;
-    let def = &cred_ini[id.as_ref()];
-    Ok(Credentials{ ak: def[ACCESS_KEY_ID_KEY].to_owned(), sk:  def[SECRET_ACCESS_KEY_KEY].to_owned() })
+    let def = cred_ini.section(Some(id.as_str()))
+        .ok_or_else(|| CloudRuError::MissingCredentials(format!("profile `{id}` in {path}")))?;
+    let key = |name: &str| def.get(name)
+        .map(|s| s.to_owned())
+        .ok_or_else(|| CloudRuError::MissingCredentials(format!("`{name}` of profile `{id}` in {path}")));
+    Ok(Credentials{ 
+        ak: key(ACCESS_KEY_ID_KEY)?, 
+        sk: key(SECRET_ACCESS_KEY_KEY)?,
+        security_token: def.get(SECURITY_TOKEN_KEY).map(|s| s.to_owned()),
+    })
 }
 
 #[derive(Default)]
@@ -43,7 +53,8 @@ impl Endpoint {
 pub struct Config {
     pub endpoint: Endpoint,
     pub project_id: Option<String>,
-    pub region: String
+    pub region: String,
+    pub obs_addressing_style: AddressingStyle,
 }
 
 pub fn read_config(path: String, force: bool) -> Result<Config> {
@@ -61,6 +72,10 @@ pub fn read_config(path: String, force: bool) -> Result<Config> {
     let common = &config_ini["common"];
     c.project_id = common.get("project_id").map(|s| s.to_owned());
     c.region = common.get("region").unwrap_or(DEFAULT_REGION).to_string();
+
+    if let Some(obs) = config_ini.section(Some("obs")) {
+        if let Some(style) = obs.get("addressing_style") { c.obs_addressing_style = style.parse()?; }
+    }
     Ok(c)
 }
 
@@ -70,6 +85,7 @@ pub mod svc_id {
     pub static fg: &str = "fg";
     pub static obs: &str = "obs";
     pub static dli: &str = "dli";
+    pub static iam: &str = "iam";
 }
 
 pub const DEFAULT_ENDPOINTS: phf::Map<&'static str, &'static str> = phf_map!{
@@ -77,7 +93,27 @@ pub const DEFAULT_ENDPOINTS: phf::Map<&'static str, &'static str> = phf_map!{
     "fg" => "https://functiongraph.ru-moscow-1.hc.sbercloud.ru",
     "obs" => "https://obs.ru-moscow-1.hc.sbercloud.ru",
     "dli"  => "https://dli.ru-moscow-1.hc.sbercloud.ru",
+    "iam" => "https://iam.ru-moscow-1.hc.sbercloud.ru",
 };
 
 
-pub const DEFAULT_REGION: &'static str = "ru-moscow-1";
\ No newline at end of file
+pub const DEFAULT_REGION: &'static str = "ru-moscow-1";
+
+#[test]
+fn test_read_config_addressing_style() {
+    let dir = std::env::temp_dir().join(format!("cloudru-config-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("config");
+    let read = |obs: &str| {
+        std::fs::write(&path, format!("[endpoint]\nobs=http://localhost:9000\n[common]\nregion=ru-moscow-1\n{obs}")).unwrap();
+        read_config(path.to_str().unwrap().to_owned(), true)
+    };
+
+    assert_eq!(read("").unwrap().obs_addressing_style, AddressingStyle::VirtualHosted);
+    assert_eq!(read("[obs]\naddressing_style=path\n").unwrap().obs_addressing_style, AddressingStyle::Path);
+    assert_eq!(read("[obs]\naddressing_style=virtual-hosted\n").unwrap().obs_addressing_style, AddressingStyle::VirtualHosted);
+    assert_eq!(read("[obs]\naddressing_style=custom-domain\n").unwrap().obs_addressing_style, AddressingStyle::CustomDomain);
+    assert!(matches!(read("[obs]\naddressing_style=bogus\n"), Err(CloudRuError::Parameter(_))));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
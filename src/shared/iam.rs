@@ -0,0 +1,137 @@
+use std::{sync::{Mutex, PoisonError}, time::Duration};
+
+use serde_json::{json, Value};
+use crate::*;
+use crate::model::iam::*;
+use super::timetools::parse_utc_time;
+
+pub const X_AUTH_TOKEN: &str = "X-Auth-Token";
+pub const X_SUBJECT_TOKEN: &str = "X-Subject-Token";
+
+/// `X-Auth-Token` header value, marked sensitive so that it is not printed with the request
+pub fn auth_token_header(token: &str) -> Result<reqwest::header::HeaderValue> {
+    let mut value = reqwest::header::HeaderValue::from_str(token)?;
+    value.set_sensitive(true);
+    Ok(value)
+}
+
+/// Tokens are refreshed this long before they expire by default
+pub const DEFAULT_TOKEN_REFRESH_AHEAD: Duration = Duration::from_secs(300);
+
+/// Request body of `POST /v3/auth/tokens`
+pub fn auth_tokens_body(identity: &IamIdentity, scope: &TokenScope) -> Value {
+    let identity = match identity {
+        IamIdentity::Password { user_name, password, domain_name } => json!({
+            "methods": ["password"],
+            "password": { "user": { "name": user_name, "password": password, "domain": { "name": domain_name } } }
+        }),
+        IamIdentity::Agency { domain_name, agency_name, duration_seconds } => {
+            let mut assume_role = json!({ "domain_name": domain_name, "agency_name": agency_name });
+            if let Some(duration_seconds) = duration_seconds {
+                assume_role["duration_seconds"] = json!(duration_seconds);
+            }
+            json!({ "methods": ["assume_role"], "assume_role": assume_role })
+        }
+    };
+    let scope = match scope {
+        TokenScope::Project { id } => json!({ "project": { "id": id } }),
+        TokenScope::ProjectName { name } => json!({ "project": { "name": name } }),
+        TokenScope::Domain { name } => json!({ "domain": { "name": name } }),
+    };
+    json!({ "auth": { "identity": identity, "scope": scope } })
+}
+
+/// token from the `X-Subject-Token` header and the expiration from the response body
+pub fn issued_token(subject_token: Option<&str>, response: &TokenResponse) -> Result<IssuedToken> {
+    let token = subject_token.ok_or(CloudRuError::MissingHeader(X_SUBJECT_TOKEN))?.to_owned();
+    let expires_at = parse_utc_time(&response.token.expires_at)
+        .ok_or_else(|| CloudRuError::InvalidResponse(format!("token expires_at `{}`", response.token.expires_at)))?;
+    Ok(IssuedToken { token, expires_at })
+}
+
+/// Token kept until `refresh_ahead` before its expiration
+#[derive(Debug)]
+pub struct TokenCache {
+    refresh_ahead: Duration,
+    cached: Mutex<Option<IssuedToken>>,
+}
+
+impl TokenCache {
+    pub fn new(refresh_ahead: Duration) -> Self { Self { refresh_ahead, cached: Mutex::new(None) } }
+
+    pub fn get(&self, now: time::OffsetDateTime) -> Option<String> {
+        self.cached.lock().unwrap_or_else(PoisonError::into_inner).as_ref()
+            .filter(|t| now + self.refresh_ahead < t.expires_at)
+            .map(|t| t.token.clone())
+    }
+
+    pub fn set(&self, token: IssuedToken) {
+        *self.cached.lock().unwrap_or_else(PoisonError::into_inner) = Some(token);
+    }
+
+    pub fn invalidate(&self) {
+        *self.cached.lock().unwrap_or_else(PoisonError::into_inner) = None;
+    }
+
+    /// drops the cached token if it is `token`, keeping one issued since
+    pub fn reject(&self, token: &str) {
+        let mut cached = self.cached.lock().unwrap_or_else(PoisonError::into_inner);
+        if cached.as_ref().is_some_and(|t| t.token == token) {
+            *cached = None;
+        }
+    }
+}
+
+#[test]
+fn test_auth_tokens_body() {
+    let identity = IamIdentity::Password { user_name: "user".to_owned(), password: "secret".to_owned(), domain_name: "account".to_owned() };
+    let body = auth_tokens_body(&identity, &TokenScope::Project { id: "p1".to_owned() });
+    assert_eq!(body, json!({ "auth": {
+        "identity": { "methods": ["password"], "password": { "user": { "name": "user", "password": "secret", "domain": { "name": "account" } } } },
+        "scope": { "project": { "id": "p1" } }
+    }}));
+
+    let identity = IamIdentity::Agency { domain_name: "account".to_owned(), agency_name: "ops".to_owned(), duration_seconds: Some(3600) };
+    let body = auth_tokens_body(&identity, &TokenScope::Domain { name: "account".to_owned() });
+    assert_eq!(body, json!({ "auth": {
+        "identity": { "methods": ["assume_role"], "assume_role": { "domain_name": "account", "agency_name": "ops", "duration_seconds": 3600 } },
+        "scope": { "domain": { "name": "account" } }
+    }}));
+}
+
+#[test]
+fn test_token_cache() {
+    let response: TokenResponse = serde_json::from_str(
+        r#"{"token":{"expires_at":"2024-01-02T12:00:00.000000Z","issued_at":"2024-01-01T12:00:00.000000Z","methods":["password"]}}"#
+    ).unwrap();
+    assert!(issued_token(None, &response).is_err());
+    let token = issued_token(Some("TOKEN"), &response).unwrap();
+    assert_eq!(token.expires_at, time::macros::datetime!(2024-01-02 12:00 UTC));
+
+    let cache = TokenCache::new(DEFAULT_TOKEN_REFRESH_AHEAD);
+    let now = time::macros::datetime!(2024-01-02 11:00 UTC);
+    assert_eq!(cache.get(now), None);
+    cache.set(token);
+    assert_eq!(cache.get(now).as_deref(), Some("TOKEN"));
+    assert_eq!(cache.get(time::macros::datetime!(2024-01-02 11:56 UTC)), None);
+    cache.reject("OTHER");
+    assert_eq!(cache.get(now).as_deref(), Some("TOKEN"));
+    cache.reject("TOKEN");
+    assert_eq!(cache.get(now), None);
+
+    cache.set(issued_token(Some("TOKEN"), &response).unwrap());
+    cache.invalidate();
+    assert_eq!(cache.get(now), None);
+}
+
+#[test]
+fn test_secrets_redacted() {
+    let identity = IamIdentity::Password { user_name: "user".to_owned(), password: "secret".to_owned(), domain_name: "account".to_owned() };
+    let token = IssuedToken { token: "TOKEN".to_owned(), expires_at: time::macros::datetime!(2024-01-02 12:00 UTC) };
+    let header = auth_token_header("TOKEN").unwrap();
+    assert!(header.is_sensitive());
+    for debug in [format!("{identity:?}"), format!("{token:?}"), format!("{header:?}")] {
+        assert!(!debug.contains("secret") && !debug.contains("TOKEN"), "{debug}");
+    }
+    assert!(format!("{identity:?}").contains("user"));
+}
@@ -13,6 +13,7 @@ pub trait RequestW {
 }
 
 pub const STANDARD_DATE: &'static str = "date";
+pub const X_OBS_SECURITY_TOKEN: &str = "x-obs-security-token";
 
 const STD_DATETIME: &[time::format_description::FormatItem<'static>] =
     time::macros::format_description!("[weekday repr:short], [day] [month repr:short] [year] [hour]:[minute]:[second] GMT");
@@ -32,30 +33,46 @@ const SUBRESOURCES: phf::Set<&str> = phf_set!(
     "uploadId", "uploads", "versionId", "versioning", "versions", "website", 
     "x-obs-security-token");
 
+fn get_hv<'t>(m: &'t dyn RequestW, h: &str) -> Result<&'t str> {
+    Ok(match m.headers().get(h) { Some(hv) => hv.to_str()?, None => "" })
+}
+
 pub fn string_to_sign(bucket_name: &str, m: &dyn RequestW) -> Result<String> {
+    string_to_sign_at(bucket_name, m, get_hv(m, "date")?)
+}
+
+/// String to sign with `date` in place of the Date header value, e.g. the `Expires` of a pre-signed URL
+pub fn string_to_sign_at(bucket_name: &str, m: &dyn RequestW, date: &str) -> Result<String> {
+    string_to_sign_url(bucket_name, m, m.url(), date)
+}
+
+fn string_to_sign_url(bucket_name: &str, m: &dyn RequestW, url: &Url, date: &str) -> Result<String> {
 /*  HTTP-Verb + "\n" + 
     Content-MD5 + "\n" + 
     Content-Type + "\n" + 
     Date + "\n" + 
     CanonicalizedHeaders + CanonicalizedResource */
-    fn get_hv<'t>(m: &'t dyn RequestW, h: &str) -> Result<&'t str> {
-        Ok(match m.headers().get(h) { Some(hv) => hv.to_str()?, None => "" })
-    }
-
     let verb = m.method().to_string();
     let content_md5 = get_hv(m, "content-md5")?;
     let content_type = get_hv(m, "content-type")?;
-    let date = get_hv(m, "date")?;
 
-    let hv: std::result::Result<String, _> = m.headers().iter()
-        .filter(|(hn, _)| hn.as_str().starts_with("x-obs-"))
-        .map(|(hn, hv)| hv.to_str().map(|hv| format!("{}:{}\n", hn.as_str(), hv)))
+    // x-obs-* headers sorted by name, with the values of a repeated header joined by commas
+    let mut obs_headers: std::collections::BTreeMap<&str, Vec<&str>> = Default::default();
+    for (hn, hv) in m.headers().iter().filter(|(hn, _)| hn.as_str().starts_with("x-obs-")) {
+        obs_headers.entry(hn.as_str()).or_default().push(hv.to_str()?);
+    }
+    let canonicalized_headers: String = obs_headers.into_iter()
+        .map(|(hn, hv)| format!("{hn}:{}\n", hv.join(",")))
         .collect();
-    let canonicalized_headers = hv?;
 
-    let mut canonicalized_resource = format!("/{}{}", bucket_name, m.url().path());
+    // service-level requests, e.g. listing buckets, are not addressed to a bucket, and path-style urls carry it in the path
+    let mut canonicalized_resource = if bucket_name.is_empty() { 
+        url.path().to_owned() 
+    } else { 
+        format!("/{}{}", bucket_name, url.path()) 
+    };
     let kvm: std::collections::BTreeMap<String, String> =
-        m.url().query_pairs()
+        url.query_pairs()
             .filter_map(|(k, v)| if SUBRESOURCES.contains(k.as_ref()) { Some((k.to_string(), v.to_string())) } else { None })
             .collect();
     if !kvm.is_empty() {
@@ -75,11 +92,21 @@ pub fn signature(string_to_sign: &str, secret_key: &str) -> Result<String> {
     Ok(encoded)
 }
 
-pub fn time_stamp_and_sign(bucket_name: &str, r: &mut dyn RequestW, dt: time::OffsetDateTime, ak: &str, sk: &str) -> Result<()> {
+pub fn time_stamp_and_sign(
+    bucket_name: &str, 
+    r: &mut dyn RequestW, 
+    dt: time::OffsetDateTime, 
+    ak: &str, 
+    sk: &str, 
+    security_token: Option<&str>
+) -> Result<()> {
     let hs = r.headers_mut();
 
     let dts = obs_date(dt)?;
     hs.insert(STANDARD_DATE, dts.parse()?);
+    if let Some(security_token) = security_token {
+        hs.insert(X_OBS_SECURITY_TOKEN, security_token.parse()?);
+    }
     
     let s2sign = string_to_sign(bucket_name, r)?;
     trace!("s2sign:`{s2sign}`");
@@ -92,6 +119,25 @@ pub fn time_stamp_and_sign(bucket_name: &str, r: &mut dyn RequestW, dt: time::Of
     Ok(())
 }
 
+/// Query-string authenticated (pre-signed) URL for the request `r`, valid until `expires` (seconds since UNIX epoch). 
+/// The security token of temporary credentials goes to the query string and is signed as a sub-resource
+pub fn presign(bucket_name: &str, r: &dyn RequestW, expires: i64, ak: &str, sk: &str, security_token: Option<&str>) -> Result<Url> {
+    let mut url = r.url().clone();
+    if let Some(security_token) = security_token {
+        url.query_pairs_mut().append_pair(X_OBS_SECURITY_TOKEN, security_token);
+    }
+
+    let expires = expires.to_string();
+    let s2sign = string_to_sign_url(bucket_name, r, &url, &expires)?;
+    trace!("s2sign:`{s2sign}`");
+    let sig = signature(&s2sign, sk)?;
+
+    url.query_pairs_mut()
+        .append_pair("AccessKeyId", ak)
+        .append_pair("Expires", &expires)
+        .append_pair("Signature", &sig);
+    Ok(url)
+}
 
 #[test]
 fn test_string_to_sign() {
@@ -253,4 +299,89 @@ x-obs-date:Tue, 15 Oct 2015 07:20:09 GMT\n
     );
     let expected = "PUT\nI5pU0r4+sgO9Emgl1KMQUg==\n\n\nx-obs-date:Tue, 15 Oct 2015 07:20:09 GMT\n/obs.ccc.com/object.txt";
     assert_eq!(expected, string_to_sign("obs.ccc.com", &req).unwrap());
-}
\ No newline at end of file
+
+    /*
+    PUT /object.txt HTTP/1.1
+    Date: Tue, 15 Oct 2015 07:20:09 GMT
+    x-obs-server-side-encryption: kms
+    x-obs-acl: private
+    ------------
+    PUT\n
+    \n
+    \n
+    Tue, 15 Oct 2015 07:20:09 GMT\n
+    x-obs-acl:private\n
+    x-obs-server-side-encryption:kms\n
+    /bucket/object.txt
+    */
+    let req = req!(Method::PUT, "https://bucket.endpoint/object.txt",
+        "x-obs-server-side-encryption" => "kms",
+        "x-obs-acl" => "private",
+        "Date" => "Tue, 15 Oct 2015 07:20:09 GMT"
+    );
+    let expected = "PUT\n\n\nTue, 15 Oct 2015 07:20:09 GMT\nx-obs-acl:private\nx-obs-server-side-encryption:kms\n/bucket/object.txt";
+    assert_eq!(expected, string_to_sign("bucket", &req).unwrap());
+
+    /*
+    GET /object.txt?AccessKeyId=AK&Expires=1532779451&Signature=... HTTP/1.1
+    Host: bucket.obs.region.example.com
+    ------------
+    GET\n
+    \n
+    \n
+    1532779451\n
+    /bucket/object.txt
+    */
+    let req = req!(Method::GET, "https://bucket.endpoint/object.txt",
+        "Host" => "bucket.obs.region.example.com"
+    );
+    let expected = "GET\n\n\n1532779451\n/bucket/object.txt";
+    assert_eq!(expected, string_to_sign_at("bucket", &req, "1532779451").unwrap());
+    let url = presign("bucket", &req, 1532779451, "AK", "SK", None).unwrap();
+    let query: Vec<(String, String)> = url.query_pairs().map(|(k, v)| (k.into_owned(), v.into_owned())).collect();
+    let sig = signature(expected, "SK").unwrap();
+    assert_eq!(query, [
+        ("AccessKeyId".to_owned(), "AK".to_owned()), 
+        ("Expires".to_owned(), "1532779451".to_owned()), 
+        ("Signature".to_owned(), sig)
+    ]);
+
+    let req = req!(Method::GET, "https://obs.region.example.com/",
+        "Date" => "Tue, 15 Oct 2015 07:20:09 GMT"
+    );
+    let expected = "GET\n\n\nTue, 15 Oct 2015 07:20:09 GMT\n/";
+    assert_eq!(expected, string_to_sign("", &req).unwrap());
+    // path-style and virtual-hosted requests to the same object sign identically
+    let virtual_hosted = req!(Method::GET, "https://bucket.obs.region.example.com/dir/object.txt?acl",
+        "Date" => "Tue, 15 Oct 2015 07:20:09 GMT"
+    );
+    let path_style = req!(Method::GET, "http://localhost:9000/bucket/dir/object.txt?acl",
+        "Date" => "Tue, 15 Oct 2015 07:20:09 GMT"
+    );
+    let expected = "GET\n\n\nTue, 15 Oct 2015 07:20:09 GMT\n/bucket/dir/object.txt?acl";
+    assert_eq!(expected, string_to_sign("bucket", &virtual_hosted).unwrap());
+    assert_eq!(expected, string_to_sign("", &path_style).unwrap());
+    // temporary credentials: the token is a signed header, or a signed sub-resource of a pre-signed url
+    let mut req = req!(Method::GET, "https://bucket.endpoint/object.txt",
+        "Host" => "bucket.obs.region.example.com"
+    );
+    let dt = time::macros::datetime!(2015-10-15 07:20:09 UTC);
+    time_stamp_and_sign("bucket", &mut req, dt, "AK", "SK", Some("TOKEN")).unwrap();
+    assert_eq!(req.headers().get(X_OBS_SECURITY_TOKEN).unwrap(), "TOKEN");
+    let expected = "GET\n\n\nThu, 15 Oct 2015 07:20:09 GMT\nx-obs-security-token:TOKEN\n/bucket/object.txt";
+    assert_eq!(expected, string_to_sign("bucket", &req).unwrap());
+
+    let req = req!(Method::GET, "https://bucket.endpoint/object.txt",
+        "Host" => "bucket.obs.region.example.com"
+    );
+    let url = presign("bucket", &req, 1532779451, "AK", "SK", Some("TOKEN")).unwrap();
+    let expected = "GET\n\n\n1532779451\n/bucket/object.txt?x-obs-security-token=TOKEN";
+    let sig = signature(expected, "SK").unwrap();
+    let query: Vec<(String, String)> = url.query_pairs().map(|(k, v)| (k.into_owned(), v.into_owned())).collect();
+    assert_eq!(query, [
+        ("x-obs-security-token".to_owned(), "TOKEN".to_owned()), 
+        ("AccessKeyId".to_owned(), "AK".to_owned()), 
+        ("Expires".to_owned(), "1532779451".to_owned()), 
+        ("Signature".to_owned(), sig)
+    ]);
+}
@@ -1,10 +1,116 @@
-use http::{HeaderMap, Method};
+use std::{fs, io::{Read, Seek, SeekFrom}, ops::{Bound, RangeBounds}, path::{Path, PathBuf}, time::UNIX_EPOCH};
+
+use http::{header::{CACHE_CONTROL, CONTENT_DISPOSITION, CONTENT_ENCODING, CONTENT_TYPE}, HeaderMap, HeaderName, HeaderValue, Method};
+use serde::Serialize;
 use url::Url;
 
-use crate::{model::obs::{BucketMeta, ObjectMeta}, CloudRuError, Result};
+use super::timetools::parse_utc_time;
+use crate::{error::ParameterKind, model::obs::{AddressingStyle, BucketMeta, CompletedPart, CreateBucketOptions, CustomerKey, ServerSideEncryption, PolicyCondition, PostPolicy, DeleteObjectsRequest, ObjectIdentifier, ListObjectVersionsResult, ListEntry, ListObjectsContents, ListObjectsRequest, ListObjectsResult, ObjectConditions, ObjectMeta, ObjectVersionEntry, PutObjectOptions, SyncAction, SyncDirection, SyncOptions, SyncReport, PartInfo, UploadCheckpoint, MULTIPART_MAX_PARTS, MULTIPART_MIN_PART_SIZE}, CloudRuError, Cx, Result};
 
 use super::urltools::WithVar;
 
+/// Base url, `Host` header and canonicalized resource bucket of the requests to a bucket in an [AddressingStyle]
+#[derive(Debug, Clone)]
+pub struct BucketAddress {
+    pub base_url: Url,
+    pub host: String,
+    /// bucket of the canonicalized resource: empty for path-style urls, which carry the bucket in the path already, 
+    /// and the domain itself for a custom domain
+    pub signing_name: String,
+    path_prefix: String,
+}
+
+impl BucketAddress {
+    pub fn new(bucket_name: &str, obs_endpoint: &str, addressing_style: AddressingStyle) -> Result<Self> {
+        let mut base_url: Url = obs_endpoint.parse()?;
+        let endpoint_host = base_url.host_str()
+            .ok_or(CloudRuError::Parameter(ParameterKind::S3BucketUrl))?
+            .to_owned();
+        let (host, signing_name, path_prefix) = match addressing_style {
+            AddressingStyle::VirtualHosted => {
+                let host = format!("{bucket_name}.{endpoint_host}");
+                base_url.set_host(Some(&host))?;
+                (host, bucket_name.to_owned(), String::new())
+            }
+            AddressingStyle::Path => {
+                base_url.set_path(&format!("/{bucket_name}/"));
+                (endpoint_host, String::new(), format!("/{bucket_name}"))
+            }
+            AddressingStyle::CustomDomain => (endpoint_host.clone(), endpoint_host, String::new()),
+        };
+        let host = match base_url.port() {
+            Some(port) => format!("{host}:{port}"),
+            None => host,
+        };
+        Ok(Self { base_url, host, signing_name, path_prefix })
+    }
+
+    /// url of `path` within the bucket
+    pub fn url(&self, path: &str) -> Url {
+        let mut url = self.base_url.clone();
+        if self.path_prefix.is_empty() {
+            url.set_path(path);
+        } else {
+            url.set_path(&format!("{}/{}", self.path_prefix, path.strip_prefix('/').unwrap_or(path)));
+        }
+        url
+    }
+}
+
+#[test]
+fn test_bucket_address() {
+    let a = BucketAddress::new("bucket", "https://obs.region.example.com", AddressingStyle::VirtualHosted).unwrap();
+    assert_eq!(a.host, "bucket.obs.region.example.com");
+    assert_eq!(a.signing_name, "bucket");
+    assert_eq!(a.url("dir/object.txt").as_str(), "https://bucket.obs.region.example.com/dir/object.txt");
+    assert_eq!(a.url("/").as_str(), "https://bucket.obs.region.example.com/");
+
+    let a = BucketAddress::new("my.bucket", "http://localhost:9000", AddressingStyle::Path).unwrap();
+    assert_eq!(a.host, "localhost:9000");
+    assert_eq!(a.signing_name, "");
+    assert_eq!(a.base_url.as_str(), "http://localhost:9000/my.bucket/");
+    assert_eq!(a.url("dir/object.txt").as_str(), "http://localhost:9000/my.bucket/dir/object.txt");
+    assert_eq!(a.url("/dir/object.txt").as_str(), "http://localhost:9000/my.bucket/dir/object.txt");
+    assert_eq!(a.url("/").as_str(), "http://localhost:9000/my.bucket/");
+
+    let a = BucketAddress::new("bucket", "https://obs.ccc.com:8443", AddressingStyle::CustomDomain).unwrap();
+    assert_eq!(a.host, "obs.ccc.com:8443");
+    assert_eq!(a.signing_name, "obs.ccc.com");
+    assert_eq!(a.url("object.txt").as_str(), "https://obs.ccc.com:8443/object.txt");
+
+    let a = BucketAddress::new("bucket", "https://obs.region.example.com:8443", AddressingStyle::VirtualHosted).unwrap();
+    assert_eq!(a.host, "bucket.obs.region.example.com:8443");
+
+    assert!(BucketAddress::new("bucket", "unix:/run/obs.sock", AddressingStyle::Path).is_err());
+}
+
+#[test]
+fn test_bucket_address_signing() {
+    use super::mauth_obs::{string_to_sign, RequestW};
+    struct R { r: reqwest::blocking::Request }
+    impl RequestW for R {
+        fn method(&self) -> &Method { self.r.method() }
+        fn headers(&self) -> &HeaderMap { self.r.headers() }
+        fn headers_mut(&mut self) -> &mut HeaderMap { self.r.headers_mut() }
+        fn url(&self) -> &Url { self.r.url() }
+    }
+    let s2s = |endpoint: &str, style: AddressingStyle| {
+        let address = BucketAddress::new("bucket", endpoint, style).unwrap();
+        let mut r = reqwest::blocking::Request::new(Method::GET, address.url("dir/object.txt").with_var_key("acl"));
+        r.headers_mut().insert("date", HeaderValue::from_static("Tue, 15 Oct 2015 07:20:09 GMT"));
+        string_to_sign(&address.signing_name, &R { r }).unwrap()
+    };
+
+    let expected = "GET\n\n\nTue, 15 Oct 2015 07:20:09 GMT\n/bucket/dir/object.txt?acl";
+    assert_eq!(s2s("https://obs.region.example.com", AddressingStyle::VirtualHosted), expected);
+    assert_eq!(s2s("http://localhost:9000", AddressingStyle::Path), expected);
+    assert_eq!(
+        s2s("https://obs.ccc.com", AddressingStyle::CustomDomain), 
+        "GET\n\n\nTue, 15 Oct 2015 07:20:09 GMT\n/obs.ccc.com/dir/object.txt?acl"
+    );
+}
+
+
 pub enum FsType {
     Obs,
     Pfs
@@ -112,6 +218,13 @@ pub fn extract_object_meta(headers: &HeaderMap) -> ObjectMeta {
     let content_type = get_str("content-type");
     //Last-Modified: WED, 01 Jul 2015 01:19:21 GMT
     let last_modified = get_str("last-modified");
+    let etag = get_str("etag");
+    let cache_control = get_str("cache-control");
+    let content_disposition = get_str("content-disposition");
+    let content_encoding = get_str("content-encoding");
+    let metadata = headers.iter()
+        .filter_map(|(hn, hv)| Some((hn.as_str().strip_prefix("x-obs-meta-")?.to_owned(), hv.to_str().ok()?.to_owned())))
+        .collect();
 
     let expiration = get_str("x-obs-expiration");
     let website_redirect_location = get_str("x-obs-website-redirect-location");
@@ -119,8 +232,1245 @@ pub fn extract_object_meta(headers: &HeaderMap) -> ObjectMeta {
     let object_type = get_str("x-obs-object-type");
     let next_append_position = get_int("x-obs-next-append-position");
     let storage_class = get_str("x-obs-storage-class");
+    let server_side_encryption = get_str("x-obs-server-side-encryption");
+    let sse_kms_key_id = get_str("x-obs-server-side-encryption-kms-key-id");
+    let sse_customer_algorithm = get_str("x-obs-server-side-encryption-customer-algorithm");
+    let sse_customer_key_md5 = get_str("x-obs-server-side-encryption-customer-key-md5");
+
+    ObjectMeta { content_length, content_type, last_modified, etag, cache_control, content_disposition, content_encoding, metadata, expiration, 
+        website_redirect_location, version_id, object_type, next_append_position, storage_class,
+        server_side_encryption, sse_kms_key_id, sse_customer_algorithm, sse_customer_key_md5,
+    }
+}
+
+/// headers of a bucket created with `options`
+pub fn create_bucket_headers(options: &CreateBucketOptions) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    if let Some(storage_class) = options.storage_class {
+        headers.insert("x-obs-storage-class", HeaderValue::from_static(storage_class.as_str()));
+    }
+    if options.file_interface {
+        headers.insert("x-obs-fs-file-interface", HeaderValue::from_static("Enabled"));
+    }
+    if let Some(acl) = options.acl {
+        headers.insert("x-obs-acl", HeaderValue::from_static(acl.as_str()));
+    }
+    headers
+}
+
+/// headers of an object put with `options`
+pub fn put_object_headers(options: &PutObjectOptions) -> Result<HeaderMap> {
+    let mut headers = match &options.encryption {
+        Some(sse) => sse_write_headers(sse)?,
+        None => HeaderMap::new(),
+    };
+    let content_headers = [
+        (CONTENT_TYPE, &options.content_type), 
+        (CACHE_CONTROL, &options.cache_control), 
+        (CONTENT_DISPOSITION, &options.content_disposition), 
+        (CONTENT_ENCODING, &options.content_encoding),
+    ];
+    for (name, value) in content_headers {
+        if let Some(value) = value {
+            headers.insert(name, HeaderValue::try_from(value)?);
+        }
+    }
+    for (name, value) in &options.metadata {
+        headers.insert(HeaderName::try_from(format!("x-obs-meta-{name}"))?, HeaderValue::try_from(value)?);
+    }
+    if let Some(storage_class) = options.storage_class {
+        headers.insert("x-obs-storage-class", HeaderValue::from_static(storage_class.as_str()));
+    }
+    Ok(headers)
+}
+
+/// headers of an object copied with `options`. The source object's content headers and metadata are kept
+/// unless `options` set any of them
+pub fn copy_object_headers(options: &PutObjectOptions) -> Result<HeaderMap> {
+    let mut headers = put_object_headers(options)?;
+    if options.replaces_metadata() {
+        headers.insert("x-obs-metadata-directive", HeaderValue::from_static("REPLACE"));
+    }
+    Ok(headers)
+}
+
+#[test]
+fn test_put_object_headers() {
+    use crate::model::obs::StorageClass;
+
+    let options = PutObjectOptions::new()
+        .content_type("text/plain")
+        .meta("Author", "me")
+        .storage_class(StorageClass::Cold);
+    let headers = put_object_headers(&options).unwrap();
+    assert_eq!(headers["content-type"], "text/plain");
+    assert_eq!(headers["x-obs-meta-author"], "me");
+    assert_eq!(headers["x-obs-storage-class"], "COLD");
+    assert_eq!(copy_object_headers(&options).unwrap()["x-obs-metadata-directive"], "REPLACE");
+    let options = PutObjectOptions::new().storage_class(StorageClass::Warm);
+    assert!(!copy_object_headers(&options).unwrap().contains_key("x-obs-metadata-directive"));
+
+    let meta = extract_object_meta(&headers);
+    assert_eq!(meta.metadata.get("author").map(String::as_str), Some("me"));
+    assert_eq!(meta.storage_class.as_deref(), Some("COLD"));
+
+    // a multipart copy carries over the content headers and metadata, but not the storage class
+    let copied = put_object_headers(&PutObjectOptions::from_meta(&meta)).unwrap();
+    assert_eq!(copied["content-type"], "text/plain");
+    assert_eq!(copied["x-obs-meta-author"], "me");
+    assert!(!copied.contains_key("x-obs-storage-class"));
+}
+
+/// headers of a request conditional on `conditions`: `If-*` for get and head, `x-obs-copy-source-if-*` for copy (`prefix`)
+pub fn condition_headers(conditions: &ObjectConditions, prefix: &str) -> Result<HeaderMap> {
+    let mut headers = HeaderMap::new();
+    let date = |d: Option<time::OffsetDateTime>| d.map(super::mauth_obs::obs_date).transpose();
+    let values = [
+        ("if-match", conditions.if_match.clone()),
+        ("if-none-match", conditions.if_none_match.clone()),
+        ("if-modified-since", date(conditions.if_modified_since)?),
+        ("if-unmodified-since", date(conditions.if_unmodified_since)?),
+    ];
+    for (name, value) in values {
+        if let Some(value) = value {
+            headers.insert(HeaderName::try_from(format!("{prefix}{name}"))?, HeaderValue::try_from(value)?);
+        }
+    }
+    Ok(headers)
+}
+
+#[test]
+fn test_condition_headers() {
+    let conditions = ObjectConditions::new()
+        .if_match("\"abc\"")
+        .if_unmodified_since(time::macros::datetime!(2015-10-15 07:20:09 UTC));
+    let headers = condition_headers(&conditions, "").unwrap();
+    assert_eq!(headers.len(), 2);
+    assert_eq!(headers["if-match"], "\"abc\"");
+    assert_eq!(headers["if-unmodified-since"], "Thu, 15 Oct 2015 07:20:09 GMT");
+    let headers = condition_headers(&conditions, "x-obs-copy-source-").unwrap();
+    assert_eq!(headers["x-obs-copy-source-if-match"], "\"abc\"");
+}
+
+/// SSE-C headers with the `prefix`
+fn sse_customer_headers(headers: &mut HeaderMap, prefix: &str, key: &CustomerKey) -> Result<()> {
+    use base64::{engine::general_purpose, Engine as _};
+    use md5::{Digest, Md5};
+
+    let mut insert = |name: &str, value: String| -> Result<()> {
+        headers.insert(HeaderName::try_from(format!("{prefix}{name}"))?, HeaderValue::try_from(value)?);
+        Ok(())
+    };
+    insert("server-side-encryption-customer-algorithm", "AES256".to_owned())?;
+    insert("server-side-encryption-customer-key", general_purpose::STANDARD.encode(key.0))?;
+    insert("server-side-encryption-customer-key-MD5", general_purpose::STANDARD.encode(Md5::digest(key.0)))?;
+    Ok(())
+}
+
+/// headers requesting `sse` for the object being written
+pub fn sse_write_headers(sse: &ServerSideEncryption) -> Result<HeaderMap> {
+    let mut headers = HeaderMap::new();
+    match sse {
+        ServerSideEncryption::Kms(key_id) => {
+            headers.insert("x-obs-server-side-encryption", HeaderValue::from_static("kms"));
+            if let Some(key_id) = key_id {
+                headers.insert("x-obs-server-side-encryption-kms-key-id", HeaderValue::try_from(key_id)?);
+            }
+        }
+        ServerSideEncryption::Obs => {
+            headers.insert("x-obs-server-side-encryption", HeaderValue::from_static("AES256"));
+        }
+        ServerSideEncryption::Customer(key) => sse_customer_headers(&mut headers, "x-obs-", key)?,
+    }
+    Ok(headers)
+}
+
+/// headers needed to read an object encrypted with `sse`; only SSE-C requires any
+pub fn sse_read_headers(sse: &ServerSideEncryption) -> Result<HeaderMap> {
+    let mut headers = HeaderMap::new();
+    if let ServerSideEncryption::Customer(key) = sse {
+        sse_customer_headers(&mut headers, "x-obs-", key)?;
+    }
+    Ok(headers)
+}
+
+/// headers needed to copy from an object encrypted with `sse`; only SSE-C requires any
+pub fn sse_copy_source_headers(sse: &ServerSideEncryption) -> Result<HeaderMap> {
+    let mut headers = HeaderMap::new();
+    if let ServerSideEncryption::Customer(key) = sse {
+        sse_customer_headers(&mut headers, "x-obs-copy-source-", key)?;
+    }
+    Ok(headers)
+}
+
+/// headers of an `ObjectIO` request over the object encrypted with `sse`.
+/// The encryption is requested when the object is created, later requests only need the SSE-C key
+pub fn object_io_sse_headers(sse: Option<&ServerSideEncryption>, creating: bool) -> Result<HeaderMap> {
+    match sse {
+        None => Ok(HeaderMap::new()),
+        Some(sse) if creating => sse_write_headers(sse),
+        Some(sse) => sse_read_headers(sse),
+    }
+}
+
+#[test]
+fn test_sse_headers() {
+    let headers = sse_write_headers(&ServerSideEncryption::Kms(Some("key".to_owned()))).unwrap();
+    assert_eq!(headers["x-obs-server-side-encryption"], "kms");
+    assert_eq!(headers["x-obs-server-side-encryption-kms-key-id"], "key");
+    assert!(sse_read_headers(&ServerSideEncryption::Obs).unwrap().is_empty());
+
+    let sse = ServerSideEncryption::Customer(CustomerKey([0; 32]));
+    let headers = sse_copy_source_headers(&sse).unwrap();
+    assert_eq!(headers["x-obs-copy-source-server-side-encryption-customer-algorithm"], "AES256");
+    assert_eq!(headers["x-obs-copy-source-server-side-encryption-customer-key"], "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+    assert_eq!(headers["x-obs-copy-source-server-side-encryption-customer-key-md5"], "cLyPS3KoaSFGi/joRB3OUQ==");
+    assert_eq!(sse_read_headers(&sse).unwrap().len(), 3);
+    assert_eq!(object_io_sse_headers(Some(&sse), true).unwrap().len(), 3);
+    assert!(object_io_sse_headers(Some(&ServerSideEncryption::Obs), false).unwrap().is_empty());
+    assert_eq!(object_io_sse_headers(Some(&ServerSideEncryption::Obs), true).unwrap()["x-obs-server-side-encryption"], "AES256");
+}
+
+#[test]
+fn test_bucket_encryption_xml() {
+    use crate::model::obs::BucketEncryption;
+
+    let config = BucketEncryption::kms(Some("key"));
+    let (body, _) = xml_body(&config).unwrap();
+    assert!(body.contains("<ServerSideEncryptionConfiguration><Rule><ApplyServerSideEncryptionByDefault>\
+        <SSEAlgorithm>kms</SSEAlgorithm><KMSMasterKeyID>key</KMSMasterKeyID>\
+        </ApplyServerSideEncryptionByDefault></Rule></ServerSideEncryptionConfiguration>"), "{body}");
+    let parsed: BucketEncryption = serde_xml_rs::from_str(&body).unwrap();
+    assert_eq!(parsed, config);
+}
+
+
+/// value of the `Content-MD5` header for `data`
+pub fn content_md5(data: &[u8]) -> String {
+    use base64::{engine::general_purpose, Engine as _};
+    use md5::{Digest, Md5};
+    general_purpose::STANDARD.encode(Md5::digest(data))
+}
+
+#[test]
+fn test_content_md5() {
+    assert_eq!(content_md5(b""), "1B2M2Y8AsgTpgAmY7PhCfg==");
+}
+
+/// hex-encoded MD5 digest carried by `etag`, `None` if the ETag is not an MD5 (e.g. the object is uploaded in parts)
+pub fn etag_md5(etag: &str) -> Option<String> {
+    let etag = etag.trim_matches('"');
+    if etag.len() != 32 || !etag.chars().all(|c| c.is_ascii_hexdigit()) { return None }
+    Some(etag.to_ascii_lowercase())
+}
+
+fn hex(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// hex-encoded MD5 digest of the file at `path`
+pub fn file_md5(path: &Path) -> Result<String> {
+    use md5::Digest;
+    let mut file = fs::File::open(path).cxd(|| format!("opening {}", path.display()))?;
+    let mut md5 = md5::Md5::new();
+    std::io::copy(&mut file, &mut md5).cxd(|| format!("reading {}", path.display()))?;
+    Ok(hex(&md5.finalize()))
+}
+
+/// Verifies the downloaded content of an object against the MD5 digest carried by its ETag
+pub struct EtagVerifier {
+    expected: String,
+    md5: md5::Md5,
+}
+
+impl EtagVerifier {
+    /// verifier for the object returned with `headers`, `None` if its ETag is not the MD5 of the content: 
+    /// the object is uploaded in parts, encrypted with SSE-KMS or SSE-C, or the ETag is missing
+    pub fn new(headers: &HeaderMap) -> Option<Self> {
+        use md5::Digest;
+        let encrypted = headers.get("x-obs-server-side-encryption").is_some_and(|v| v == "kms")
+            || headers.contains_key("x-obs-server-side-encryption-customer-algorithm");
+        if encrypted { return None }
+        let expected = etag_md5(headers.get("etag")?.to_str().ok()?)?;
+        Some(Self { expected, md5: md5::Md5::new() })
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        use md5::Digest;
+        self.md5.update(data)
+    }
 
-    ObjectMeta { content_length, content_type, last_modified, expiration, 
-        website_redirect_location, version_id, object_type, next_append_position, storage_class 
+    /// fails with [CloudRuError::ChecksumMismatch] if the data passed to `update` does not match the ETag
+    pub fn verify(self) -> Result<()> {
+        use md5::Digest;
+        let actual = hex(&self.md5.finalize());
+        if actual == self.expected {
+            Ok(())
+        } else {
+            Err(CloudRuError::ChecksumMismatch { expected: self.expected, actual })
+        }
     }
 }
+
+#[test]
+fn test_etag_verifier() {
+    let mut headers = HeaderMap::new();
+    headers.insert("etag", HeaderValue::from_static("\"D41D8CD98F00B204E9800998ECF8427E\""));
+    let verifier = EtagVerifier::new(&headers).unwrap();
+    verifier.verify().unwrap();
+
+    let mut verifier = EtagVerifier::new(&headers).unwrap();
+    verifier.update(b"x");
+    assert!(matches!(verifier.verify(), Err(CloudRuError::ChecksumMismatch { .. })));
+
+    headers.insert("etag", HeaderValue::from_static("\"d41d8cd98f00b204e9800998ecf8427e-2\""));
+    assert!(EtagVerifier::new(&headers).is_none());
+    headers.insert("etag", HeaderValue::from_static("\"d41d8cd98f00b204e9800998ecf8427e\""));
+    headers.insert("x-obs-server-side-encryption", HeaderValue::from_static("kms"));
+    assert!(EtagVerifier::new(&headers).is_none());
+}
+
+/// XML request body for `value`, along with its `Content-MD5`
+pub fn xml_body<T: Serialize>(value: &T) -> Result<(String, String)> {
+    let body = serde_xml_rs::to_string(value)?;
+    let md5 = content_md5(body.as_bytes());
+    Ok((body, md5))
+}
+
+/// multi-object delete request body for `objects`, along with its `Content-MD5`
+pub fn delete_objects_body(objects: &[ObjectIdentifier], quiet: bool) -> Result<(String, String)> {
+    xml_body(&DeleteObjectsRequest { quiet, objects: objects.to_vec() })
+}
+
+#[test]
+fn test_delete_objects_xml() {
+    use crate::model::obs::DeleteObjectsResult;
+
+    let (body, _) = delete_objects_body(&["a".into(), ObjectIdentifier { key: "b".to_owned(), version_id: Some("v".to_owned()) }], true).unwrap();
+    assert!(body.ends_with("<Delete><Quiet>true</Quiet><Object><Key>a</Key></Object><Object><Key>b</Key><VersionId>v</VersionId></Object></Delete>"));
+
+    let result: DeleteObjectsResult = serde_xml_rs::from_str(r#"<DeleteResult xmlns="http://obs.example.com/doc/2015-06-30/">
+        <Deleted><Key>a</Key></Deleted>
+        <Error><Key>b</Key><VersionId>v</VersionId><Code>AccessDenied</Code><Message>Access Denied</Message></Error>
+        <Deleted><Key>c</Key><DeleteMarker>true</DeleteMarker><DeleteMarkerVersionId>m</DeleteMarkerVersionId></Deleted>
+        </DeleteResult>"#).unwrap();
+    assert_eq!(result.deleted().map(|d| d.key.as_str()).collect::<Vec<_>>(), ["a", "c"]);
+    assert_eq!(result.errors().map(|e| e.code.as_str()).collect::<Vec<_>>(), ["AccessDenied"]);
+}
+
+#[test]
+fn test_copy_part_result_xml() {
+    use crate::model::obs::CopyPartResult;
+
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<CopyPartResult xmlns="http://obs.myhwclouds.com/doc/2015-06-30/">
+<LastModified>2018-11-26T07:52:19.000Z</LastModified>
+<ETag>"3b46eaf02d3b6b1206078bb86a7b7013"</ETag>
+</CopyPartResult>"#;
+    let result: CopyPartResult = serde_xml_rs::from_str(xml).unwrap();
+    assert_eq!(result.etag, "\"3b46eaf02d3b6b1206078bb86a7b7013\"");
+}
+
+#[test]
+fn test_buckets_xml() {
+    use crate::model::obs::*;
+
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ListAllMyBucketsResult xmlns="http://obs.myhwclouds.com/doc/2015-06-30/">
+<Owner><ID>783fc6652cf246c096ea836694f71855</ID></Owner>
+<Buckets>
+<Bucket><Name>examplebucket01</Name><CreationDate>2018-06-21T09:15:01.032Z</CreationDate><Location>ru-moscow-1</Location><BucketType>OBJECT</BucketType></Bucket>
+<Bucket><Name>examplebucket02</Name><CreationDate>2018-06-22T03:56:33.700Z</CreationDate></Bucket>
+</Buckets>
+</ListAllMyBucketsResult>"#;
+    let result: ListBucketsResult = serde_xml_rs::from_str(xml).unwrap();
+    let names: Vec<&str> = result.buckets.buckets.iter().map(|b| b.name.as_str()).collect();
+    assert_eq!(names, ["examplebucket01", "examplebucket02"]);
+    assert_eq!(result.buckets.buckets[0].location.as_deref(), Some("ru-moscow-1"));
+
+    let (body, _) = xml_body(&CreateBucketConfiguration { location: "ru-moscow-1".to_owned() }).unwrap();
+    assert!(body.ends_with("<CreateBucketConfiguration><Location>ru-moscow-1</Location></CreateBucketConfiguration>"), "{body}");
+
+    let options = CreateBucketOptions::new().storage_class(StorageClass::Warm).file_interface(true);
+    let headers = create_bucket_headers(&options);
+    assert_eq!(headers["x-obs-storage-class"], "WARM");
+    assert_eq!(headers["x-obs-fs-file-interface"], "Enabled");
+}
+
+#[test]
+fn test_lifecycle_xml() {
+    use crate::model::obs::*;
+
+    let config = LifecycleConfiguration { rules: vec![
+        LifecycleRule {
+            id: Some("logs".to_owned()),
+            prefix: "logs/".to_owned(),
+            status: RuleStatus::Enabled,
+            transitions: vec![
+                Transition { days: Some(30), date: None, storage_class: StorageClass::Warm },
+                Transition { days: Some(60), date: None, storage_class: StorageClass::Cold },
+            ],
+            expiration: Some(Expiration { days: Some(365), date: None }),
+            noncurrent_version_transitions: vec![],
+            noncurrent_version_expiration: Some(NoncurrentVersionExpiration { noncurrent_days: 10 }),
+        },
+        LifecycleRule {
+            id: None,
+            prefix: "tmp/".to_owned(),
+            status: RuleStatus::Disabled,
+            transitions: vec![],
+            expiration: Some(Expiration { days: Some(1), date: None }),
+            noncurrent_version_transitions: vec![NoncurrentVersionTransition { noncurrent_days: 5, storage_class: StorageClass::Cold }],
+            noncurrent_version_expiration: None,
+        },
+    ]};
+    let (body, _) = xml_body(&config).unwrap();
+    assert!(body.contains("<Rule><ID>logs</ID><Prefix>logs/</Prefix><Status>Enabled</Status>\
+        <Transition><Days>30</Days><StorageClass>WARM</StorageClass></Transition>\
+        <Transition><Days>60</Days><StorageClass>COLD</StorageClass></Transition>\
+        <Expiration><Days>365</Days></Expiration>\
+        <NoncurrentVersionExpiration><NoncurrentDays>10</NoncurrentDays></NoncurrentVersionExpiration></Rule>"), "{body}");
+    let parsed: LifecycleConfiguration = serde_xml_rs::from_str(&body).unwrap();
+    assert_eq!(parsed, config);
+}
+
+#[test]
+fn test_acl_xml() {
+    use crate::model::obs::*;
+
+    let acl: AccessControlPolicy = serde_xml_rs::from_str(r#"<?xml version="1.0" encoding="UTF-8"?>
+        <AccessControlPolicy xmlns="http://obs.example.com/doc/2015-06-30/">
+        <Owner><ID>domain</ID></Owner>
+        <AccessControlList>
+            <Grant><Grantee><ID>domain</ID></Grantee><Permission>FULL_CONTROL</Permission></Grant>
+            <Grant><Grantee><Canned>Everyone</Canned></Grantee><Permission>READ</Permission><Delivered>true</Delivered></Grant>
+        </AccessControlList>
+        </AccessControlPolicy>"#).unwrap();
+    assert_eq!(acl.owner.id, "domain");
+    assert_eq!(acl.access_control_list.grants, [
+        Grant { grantee: Grantee::user("domain"), permission: Permission::FullControl, delivered: None },
+        Grant { grantee: Grantee::everyone(), permission: Permission::Read, delivered: Some(true) },
+    ]);
+
+    let body = serde_xml_rs::to_string(&acl).unwrap();
+    assert!(body.ends_with("<AccessControlPolicy><Owner><ID>domain</ID></Owner><AccessControlList>\
+        <Grant><Grantee><ID>domain</ID></Grantee><Permission>FULL_CONTROL</Permission></Grant>\
+        <Grant><Grantee><Canned>Everyone</Canned></Grantee><Permission>READ</Permission><Delivered>true</Delivered></Grant>\
+        </AccessControlList></AccessControlPolicy>"), "{body}");
+}
+
+#[test]
+fn test_bucket_policy_json() {
+    use crate::model::obs::*;
+
+    let text = r#"{"Statement":[{"Sid":"public","Effect":"Allow","Principal":{"ID":["*"]},"Action":["GetObject"],"Resource":"bucket/*"}]}"#;
+    let policy: BucketPolicy = serde_json::from_str(text).unwrap();
+    let statement = &policy.statements[0];
+    assert_eq!(statement.effect, PolicyEffect::Allow);
+    assert_eq!(statement.action.iter().collect::<Vec<_>>(), ["GetObject"]);
+    assert_eq!(statement.resource.iter().collect::<Vec<_>>(), ["bucket/*"]);
+    assert!(statement.not_action.is_empty());
+    assert_eq!(serde_json::to_string(&policy).unwrap(), text);
+}
+
+/// Formats the value of the `Range` header requesting `range` of the object's bytes.
+/// 
+/// Returns `None` if the range is unbounded on both sides, i.e. the whole object is requested. 
+pub fn range_header(range: impl RangeBounds<u64>) -> Option<String> {
+    let first = match range.start_bound() {
+        Bound::Included(&n) => n,
+        Bound::Excluded(&n) => n + 1,
+        Bound::Unbounded => 0,
+    };
+    let last = match range.end_bound() {
+        Bound::Included(&n) => Some(n),
+        Bound::Excluded(&n) => Some(n.saturating_sub(1)),
+        Bound::Unbounded => None,
+    };
+    match (first, last) {
+        (0, None) => None,
+        (first, None) => Some(format!("bytes={first}-")),
+        (first, Some(last)) => Some(format!("bytes={first}-{last}")),
+    }
+}
+
+#[test]
+fn test_range_header() {
+    assert_eq!(range_header(..), None);
+    assert_eq!(range_header(0..), None);
+    assert_eq!(range_header(10..), Some("bytes=10-".to_owned()));
+    assert_eq!(range_header(10..20), Some("bytes=10-19".to_owned()));
+    assert_eq!(range_header(10..=20), Some("bytes=10-20".to_owned()));
+    assert_eq!(range_header(..20), Some("bytes=0-19".to_owned()));
+}
+
+/// Min read-ahead window used once the reads become sequential again 
+const READ_AHEAD_MIN_WINDOW: usize = 64 * 1024;
+
+/// Read-ahead buffer: fetches more data than requested and serves later reads from memory.
+/// 
+/// The window grows (up to `max_window`) while the reads are sequential and shrinks when they are not, so that random
+/// access does not fetch unneeded data.
+#[derive(Debug)]
+pub struct ReadAhead {
+    max_window: usize,
+    window: usize,
+    buf_start: u64,
+    buf: Vec<u8>,
+    /// object position right after the last fetch
+    next_fetch: Option<u64>,
+}
+
+impl ReadAhead {
+    pub fn new(max_window: usize) -> Self {
+        Self { max_window, window: max_window, buf_start: 0, buf: vec![], next_fetch: None }
+    }
+
+    pub fn max_window(&self) -> usize { self.max_window }
+
+    pub fn set_max_window(&mut self, max_window: usize) {
+        self.max_window = max_window;
+        self.window = self.window.min(max_window);
+    }
+
+    /// Copies the buffered data at `pos` into `out`. Returns the number of bytes copied, 0 if `pos` is not buffered.
+    pub fn read_buffered(&self, pos: u64, out: &mut [u8]) -> usize {
+        if pos < self.buf_start || pos >= self.buf_start + self.buf.len() as u64 {
+            return 0
+        }
+        let offset = (pos - self.buf_start) as usize;
+        let n = out.len().min(self.buf.len() - offset);
+        out[..n].copy_from_slice(&self.buf[offset..offset + n]);
+        n
+    }
+
+    /// Returns the number of bytes to fetch at `pos` to serve a read of `wanted` bytes, given that `remaining` bytes 
+    /// are left till the end of the object. Adapts the window to the access pattern.
+    pub fn plan_fetch(&mut self, pos: u64, wanted: usize, remaining: u64) -> usize {
+        if self.next_fetch.is_none_or(|next| next == pos) {
+            self.window = (self.window * 2).max(READ_AHEAD_MIN_WINDOW).min(self.max_window);
+        } else {
+            self.window /= 2;
+            if self.window < READ_AHEAD_MIN_WINDOW { self.window = 0 }
+        }
+        (wanted.max(self.window) as u64).min(remaining) as usize
+    }
+
+    /// Stores `data` fetched at `pos`
+    pub fn fill(&mut self, pos: u64, data: Vec<u8>) {
+        self.next_fetch = Some(pos + data.len() as u64);
+        self.buf_start = pos;
+        self.buf = data;
+    }
+
+    /// Drops the buffered data, e.g. after it was overwritten
+    pub fn invalidate(&mut self) {
+        self.buf.clear();
+    }
+}
+
+#[test]
+fn test_read_ahead() {
+    const K: usize = 1024;
+    let mut ra = ReadAhead::new(256 * K);
+    let mut out = [0u8; 8];
+
+    // sequential reads use the full window
+    assert_eq!(ra.read_buffered(0, &mut out), 0);
+    assert_eq!(ra.plan_fetch(0, 8, 1024 * K as u64), 256 * K);
+    ra.fill(0, (0..256 * K).map(|n| n as u8).collect());
+    assert_eq!(ra.read_buffered(8, &mut out), 8);
+    assert_eq!(out, [8, 9, 10, 11, 12, 13, 14, 15]);
+    assert_eq!(ra.read_buffered(256 * K as u64 - 4, &mut out), 4);
+    assert_eq!(ra.plan_fetch(256 * K as u64, 8, 1024 * K as u64), 256 * K);
+
+    // fetch is limited by the object's end
+    assert_eq!(ra.plan_fetch(256 * K as u64, 8, 100), 100);
+    ra.fill(256 * K as u64, vec![0; 100]);
+
+    // random access shrinks the window down to nothing
+    assert_eq!(ra.plan_fetch(10, 8, 1024 * K as u64), 128 * K);
+    assert_eq!(ra.plan_fetch(20, 8, 1024 * K as u64), 64 * K);
+    assert_eq!(ra.plan_fetch(30, 8, 1024 * K as u64), 8);
+
+    // and sequential access restores it
+    ra.fill(30, vec![0; 8]);
+    assert_eq!(ra.plan_fetch(38, 8, 1024 * K as u64), 64 * K);
+
+    ra.invalidate();
+    assert_eq!(ra.read_buffered(30, &mut out), 0);
+
+    // zero window disables read-ahead
+    let mut ra = ReadAhead::new(0);
+    assert_eq!(ra.plan_fetch(0, 8, 1024), 8);
+}
+
+/// A slice of the source data uploaded as a single part
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartRange {
+    pub part_number: u32,
+    pub offset: u64,
+    pub size: u64,
+}
+
+/// Returns `part_size` raised, if needed, to the allowed minimum and so that `len` bytes fit into [MULTIPART_MAX_PARTS] parts
+pub fn effective_part_size(len: u64, part_size: u64) -> u64 {
+    part_size
+        .max(MULTIPART_MIN_PART_SIZE)
+        .max(len.div_ceil(MULTIPART_MAX_PARTS))
+}
+
+/// Splits `len` bytes into parts of [effective_part_size] bytes each. The last part may be shorter.
+/// 
+/// Empty data yields a single empty part.
+pub fn plan_parts(len: u64, part_size: u64) -> Vec<PartRange> {
+    let part_size = effective_part_size(len, part_size);
+
+    (0..len.div_ceil(part_size).max(1))
+        .map(|n| {
+            let offset = n * part_size;
+            PartRange { part_number: n as u32 + 1, offset, size: part_size.min(len - offset) }
+        })
+        .collect()
+}
+
+/// Reads the data of `part` from `file`
+pub fn read_part<F: Read + Seek>(file: &mut F, part: &PartRange) -> Result<Vec<u8>> {
+    file.seek(SeekFrom::Start(part.offset))?;
+    let mut buf = vec![0; part.size as usize];
+    file.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+/// Returns size and modification time (seconds since UNIX epoch) of the local file
+pub fn file_size_and_mtime(path: &Path) -> Result<(u64, Option<u64>)> {
+    let meta = fs::metadata(path).cxd(|| format!("reading metadata of {}", path.display()))?;
+    let mtime = meta.modified().ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs());
+    Ok((meta.len(), mtime))
+}
+
+impl UploadCheckpoint {
+    /// Loads the checkpoint from `path`. Returns `None` if the file does not exist.
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() { return Ok(None) }
+        let text = fs::read_to_string(path).cxd(|| format!("reading checkpoint {}", path.display()))?;
+        Ok(Some(serde_json::from_str(&text).cxd(|| format!("parsing checkpoint {}", path.display()))?))
+    }
+
+    /// Saves the checkpoint to `path`. The file is replaced atomically, so that an interrupted save 
+    /// does not destroy the previous state.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut tmp_path = path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+        fs::write(&tmp_path, serde_json::to_vec(self)?).cxd(|| format!("writing checkpoint {}", path.display()))?;
+        fs::rename(&tmp_path, path).cxd(|| format!("writing checkpoint {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Checks whether the checkpoint describes the upload of the same (unchanged) file to the same object
+    pub fn matches(&self, bucket: &str, key: &str, file_size: u64, file_mtime: Option<u64>) -> bool {
+        self.bucket == bucket && self.key == key && self.file_size == file_size && self.file_mtime == file_mtime
+    }
+
+    /// Replaces the recorded parts with the parts actually stored by OBS, dropping the parts whose size
+    /// does not match the plan. Returns the parts that still need to be uploaded.
+    pub fn reconcile(&mut self, uploaded: &[PartInfo], plan: &[PartRange]) -> Vec<PartRange> {
+        self.parts = plan.iter()
+            .filter_map(|p| uploaded.iter()
+                .find(|u| u.part_number == p.part_number && u.size == p.size)
+                .map(|u| CompletedPart { part_number: u.part_number, etag: u.etag.clone() })
+            )
+            .collect();
+        self.missing_parts(plan)
+    }
+
+    /// Returns the parts of `plan` not recorded as uploaded
+    pub fn missing_parts(&self, plan: &[PartRange]) -> Vec<PartRange> {
+        plan.iter()
+            .filter(|p| !self.parts.iter().any(|c| c.part_number == p.part_number))
+            .copied()
+            .collect()
+    }
+
+    /// Records an uploaded part
+    pub fn add_part(&mut self, part: CompletedPart) {
+        self.parts.retain(|p| p.part_number != part.part_number);
+        self.parts.push(part);
+    }
+}
+
+#[test]
+fn test_plan_parts() {
+    const K: u64 = 1024;
+    assert_eq!(plan_parts(0, 200 * K), vec![PartRange { part_number: 1, offset: 0, size: 0 }]);
+    assert_eq!(plan_parts(1, 200 * K), vec![PartRange { part_number: 1, offset: 0, size: 1 }]);
+    assert_eq!(plan_parts(450 * K, 200 * K), vec![
+        PartRange { part_number: 1, offset: 0, size: 200 * K },
+        PartRange { part_number: 2, offset: 200 * K, size: 200 * K },
+        PartRange { part_number: 3, offset: 400 * K, size: 50 * K },
+    ]);
+    // part size is raised to the allowed minimum
+    assert_eq!(plan_parts(150 * K, 1).len(), 2);
+    // part count never exceeds the limit
+    let parts = plan_parts(MULTIPART_MAX_PARTS * MULTIPART_MIN_PART_SIZE * 3 + 1, MULTIPART_MIN_PART_SIZE);
+    assert!(parts.len() as u64 <= MULTIPART_MAX_PARTS);
+    assert_eq!(parts.iter().map(|p| p.size).sum::<u64>(), MULTIPART_MAX_PARTS * MULTIPART_MIN_PART_SIZE * 3 + 1);
+}
+
+#[test]
+fn test_checkpoint_reconcile() {
+    const K: u64 = 1024;
+    let plan = plan_parts(450 * K, 200 * K);
+    let mut checkpoint = UploadCheckpoint {
+        bucket: "b".to_owned(), key: "k".to_owned(), upload_id: "u".to_owned(),
+        file_size: 450 * K, file_mtime: Some(1), part_size: 200 * K, 
+        parts: vec![CompletedPart { part_number: 2, etag: "stale".to_owned() }],
+    };
+    assert!(checkpoint.matches("b", "k", 450 * K, Some(1)));
+    assert!(!checkpoint.matches("b", "k", 450 * K, Some(2)));
+
+    let part = |part_number, size| PartInfo { part_number, last_modified: None, etag: format!("e{part_number}"), size };
+    // part 3 is truncated on the server
+    let missing = checkpoint.reconcile(&[part(1, 200 * K), part(3, 10 * K)], &plan);
+    assert_eq!(checkpoint.parts, vec![CompletedPart { part_number: 1, etag: "e1".to_owned() }]);
+    assert_eq!(missing, plan[1..].to_vec());
+
+    checkpoint.add_part(CompletedPart { part_number: 3, etag: "e3".to_owned() });
+    assert_eq!(checkpoint.missing_parts(&plan), vec![plan[1]]);
+
+    let path = std::env::temp_dir().join(format!("cloudru_test_checkpoint_{}.json", std::process::id()));
+    checkpoint.save(&path).unwrap();
+    let loaded = UploadCheckpoint::load(&path).unwrap().unwrap();
+    assert_eq!(loaded.parts, checkpoint.parts);
+    fs::remove_file(&path).unwrap();
+    assert!(UploadCheckpoint::load(&path).unwrap().is_none());
+}
+
+
+/// Owned state of a paged listing: the request parameters and the markers of the next page
+#[derive(Debug, Default)]
+pub struct ListPager {
+    prefix: Option<String>,
+    delimiter: Option<String>,
+    max_keys: Option<u32>,
+    marker: Option<String>,
+    key_marker: Option<String>,
+    version_id_marker: Option<String>,
+    done: bool,
+}
+
+impl ListPager {
+    pub fn new(request: &ListObjectsRequest<'_>) -> Self {
+        Self {
+            prefix: request.prefix.map(str::to_owned),
+            delimiter: request.delimiter.map(str::to_owned),
+            max_keys: request.max_keys,
+            marker: request.marker.map(str::to_owned),
+            key_marker: request.key_marker.map(str::to_owned),
+            version_id_marker: request.version_id_marker.map(str::to_owned),
+            done: false,
+        }
+    }
+
+    /// no more pages to fetch
+    pub fn is_done(&self) -> bool { self.done }
+
+    pub fn stop(&mut self) { self.done = true }
+
+    /// request for the next page
+    pub fn request(&self) -> ListObjectsRequest<'_> {
+        ListObjectsRequest {
+            prefix: self.prefix.as_deref(),
+            marker: self.marker.as_deref(),
+            max_keys: self.max_keys,
+            delimiter: self.delimiter.as_deref(),
+            key_marker: self.key_marker.as_deref(),
+            version_id_marker: self.version_id_marker.as_deref(),
+        }
+    }
+
+    /// Moves to the next page of an object listing, returning the objects and common prefixes of the current one, 
+    /// ordered by name.
+    /// `NextMarker` is only guaranteed with a delimiter, so the last name is used as a fallback.
+    pub fn advance_entries(&mut self, result: ListObjectsResult) -> Vec<ListEntry> {
+        let mut entries: Vec<ListEntry> = result.contents.unwrap_or_default().into_iter().map(ListEntry::Object)
+            .chain(result.common_prefixes.unwrap_or_default().into_iter().map(|p| ListEntry::Prefix(p.prefix)))
+            .collect();
+        entries.sort_by(|a, b| a.name().cmp(b.name()));
+        let next_marker = if result.is_truncated == Some(true) {
+            result.next_marker.or_else(|| entries.last().map(|e| e.name().to_owned()))
+        } else {
+            None
+        };
+        self.done = next_marker.is_none() || next_marker == self.marker;
+        self.marker = next_marker;
+        entries
+    }
+
+    /// Same as [Self::advance_entries], but skips common prefixes
+    pub fn advance_objects(&mut self, result: ListObjectsResult) -> Vec<ListObjectsContents> {
+        self.advance_entries(result).into_iter()
+            .filter_map(|e| match e { ListEntry::Object(c) => Some(c), ListEntry::Prefix(_) => None })
+            .collect()
+    }
+
+    /// Moves to the next page of a version listing, returning the entries of the current one
+    pub fn advance_versions(&mut self, result: ListObjectVersionsResult) -> Vec<ObjectVersionEntry> {
+        let next = if result.is_truncated == Some(true) {
+            result.next_key_marker.map(|k| (k, result.next_version_id_marker))
+        } else {
+            None
+        };
+        match next {
+            Some((key_marker, version_id_marker)) 
+                if (Some(&key_marker), &version_id_marker) != (self.key_marker.as_ref(), &self.version_id_marker) => 
+            {
+                self.key_marker = Some(key_marker);
+                self.version_id_marker = version_id_marker;
+            }
+            _ => self.done = true
+        }
+        result.version.unwrap_or_default().into_iter().map(ObjectVersionEntry::Version)
+            .chain(result.delete_marker.unwrap_or_default().into_iter().map(ObjectVersionEntry::DeleteMarker))
+            .collect()
+    }
+}
+
+#[test]
+fn test_list_pager() {
+    let mut pager = ListPager::new(&ListObjectsRequest { prefix: Some("a/"), max_keys: Some(2), ..Default::default() });
+    assert_eq!(pager.request().marker, None);
+
+    let page: ListObjectsResult = serde_xml_rs::from_str(r#"<ListBucketResult>
+        <Name>b</Name><Prefix>a/</Prefix><IsTruncated>true</IsTruncated>
+        <Contents><Key>a/1</Key><LastModified>-</LastModified><ETag>e</ETag><Size>1</Size><StorageClass>STANDARD</StorageClass></Contents>
+        <Contents><Key>a/2</Key><LastModified>-</LastModified><ETag>e</ETag><Size>1</Size><StorageClass>STANDARD</StorageClass></Contents>
+        </ListBucketResult>"#).unwrap();
+    assert_eq!(pager.advance_objects(page).len(), 2);
+    assert!(!pager.is_done());
+    assert_eq!(pager.request().marker, Some("a/2"));
+    assert_eq!(pager.request().prefix, Some("a/"));
+
+    let page: ListObjectsResult = serde_xml_rs::from_str(r#"<ListBucketResult>
+        <Name>b</Name><Prefix>a/</Prefix><IsTruncated>false</IsTruncated>
+        </ListBucketResult>"#).unwrap();
+    assert!(pager.advance_objects(page).is_empty());
+    assert!(pager.is_done());
+
+    let mut pager = ListPager::new(&ListObjectsRequest { delimiter: Some("/"), ..Default::default() });
+    let page: ListObjectsResult = serde_xml_rs::from_str(r#"<ListBucketResult>
+        <Name>b</Name><Prefix></Prefix><Delimiter>/</Delimiter><IsTruncated>true</IsTruncated>
+        <Contents><Key>b.txt</Key><LastModified>-</LastModified><ETag>e</ETag><Size>1</Size><StorageClass>STANDARD</StorageClass></Contents>
+        <CommonPrefixes><Prefix>a/</Prefix></CommonPrefixes>
+        <CommonPrefixes><Prefix>c/</Prefix></CommonPrefixes>
+        </ListBucketResult>"#).unwrap();
+    let names: Vec<String> = pager.advance_entries(page).iter().map(|e| e.name().to_owned()).collect();
+    assert_eq!(names, ["a/", "b.txt", "c/"]);
+    assert_eq!(pager.request().marker, Some("c/"));
+    assert_eq!(pager.request().delimiter, Some("/"));
+
+    let mut pager = ListPager::default();
+    let page: ListObjectVersionsResult = serde_xml_rs::from_str(r#"<ListVersionsResult>
+        <Name>b</Name><Prefix></Prefix><IsTruncated>true</IsTruncated>
+        <NextKeyMarker>k</NextKeyMarker><NextVersionIdMarker>v</NextVersionIdMarker>
+        <Version><Key>k</Key><LastModified>-</LastModified><ETag>e</ETag><Size>1</Size><StorageClass>STANDARD</StorageClass><VersionId>v</VersionId></Version>
+        </ListVersionsResult>"#).unwrap();
+    assert!(matches!(&pager.advance_versions(page)[..], [ObjectVersionEntry::Version(_)]));
+    assert_eq!((pager.request().key_marker, pager.request().version_id_marker), (Some("k"), Some("v")));
+    assert!(!pager.is_done());
+}
+
+#[test]
+fn test_list_objects_xml() {
+    use crate::model::obs::Owner;
+
+    let result: ListObjectsResult = serde_xml_rs::from_str(r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<ListBucketResult xmlns="http://obs.hc.sbercloud.ru/doc/2015-06-30/">
+    <Name>rust-api-test</Name>
+    <Prefix></Prefix>
+    <KeyCount>2</KeyCount>
+    <MaxKeys>1000</MaxKeys>
+    <IsTruncated>false</IsTruncated>
+    <Contents>
+        <Key>test.txt</Key>
+        <LastModified>2023-08-20T11:04:49.119Z</LastModified>
+        <ETag>"bf0b7283b196369ba8723a79750d937b"</ETag>
+        <Size>35</Size>
+        <Owner><ID>b4bf1b36d9ca43d984fbcb9491b6fce9</ID><DisplayName>ead1c9</DisplayName></Owner>
+        <StorageClass>STANDARD</StorageClass>
+    </Contents>
+    <Contents>
+        <Key>test2.txt</Key>
+        <LastModified>2023-08-20T11:04:49.250Z</LastModified>
+        <ETag>"bf0b7283b196369ba8723a79750d937b"</ETag>
+        <Type>Appendable</Type>
+        <Size>35</Size>
+        <StorageClass>WARM</StorageClass>
+    </Contents>
+</ListBucketResult>"#).unwrap();
+    assert_eq!(result.name, "rust-api-test");
+    assert_eq!(result.prefix, "");
+    assert_eq!((result.key_count, result.max_keys, result.is_truncated), (Some(2), Some(1000), Some(false)));
+    assert_eq!(result.next_marker, None);
+
+    let contents = result.contents.unwrap();
+    assert_eq!(contents.len(), 2);
+    assert_eq!(contents[0].key, "test.txt");
+    assert_eq!(contents[0].last_modified, "2023-08-20T11:04:49.119Z");
+    assert_eq!(contents[0].etag, "\"bf0b7283b196369ba8723a79750d937b\"");
+    assert_eq!((contents[0].size, contents[0].storage_class.as_str()), (35, "STANDARD"));
+    assert_eq!(contents[0].owner, Some(Owner { id: "b4bf1b36d9ca43d984fbcb9491b6fce9".into(), display_name: Some("ead1c9".into()) }));
+    assert_eq!(contents[1].type_.as_deref(), Some("Appendable"));
+    assert_eq!((contents[1].owner.as_ref(), contents[1].storage_class.as_str()), (None, "WARM"));
+}
+
+#[test]
+fn test_list_object_versions_xml() {
+    let result: ListObjectVersionsResult = serde_xml_rs::from_str(r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<ListVersionsResult xmlns="http://obs.hc.sbercloud.ru/doc/2015-06-30/">
+    <Name>rust-api-test</Name>
+    <Prefix>test</Prefix>
+    <KeyMarker></KeyMarker>
+    <VersionIdMarker></VersionIdMarker>
+    <NextKeyMarker>test2.txt</NextKeyMarker>
+    <NextVersionIdMarker>G001117FCE89978B0000401205D5DC9A</NextVersionIdMarker>
+    <MaxKeys>2</MaxKeys>
+    <IsTruncated>true</IsTruncated>
+    <Version>
+        <Key>test.txt</Key>
+        <VersionId>G001117FCE89978B0000401205D5DC9B</VersionId>
+        <IsLatest>true</IsLatest>
+        <LastModified>2023-08-20T11:04:49.119Z</LastModified>
+        <ETag>"bf0b7283b196369ba8723a79750d937b"</ETag>
+        <Size>35</Size>
+        <Owner><ID>b4bf1b36d9ca43d984fbcb9491b6fce9</ID></Owner>
+        <StorageClass>STANDARD</StorageClass>
+    </Version>
+    <DeleteMarker>
+        <Key>test2.txt</Key>
+        <VersionId>G001117FCE89978B0000401205D5DC9A</VersionId>
+        <IsLatest>true</IsLatest>
+        <LastModified>2023-08-21T09:12:03.512Z</LastModified>
+        <Owner><ID>b4bf1b36d9ca43d984fbcb9491b6fce9</ID></Owner>
+    </DeleteMarker>
+</ListVersionsResult>"#).unwrap();
+    assert_eq!((result.name.as_str(), result.prefix.as_str()), ("rust-api-test", "test"));
+    assert_eq!(result.next_key_marker.as_deref(), Some("test2.txt"));
+    assert_eq!(result.next_version_id_marker.as_deref(), Some("G001117FCE89978B0000401205D5DC9A"));
+    assert_eq!((result.max_keys, result.is_truncated), (Some(2), Some(true)));
+
+    let versions = result.version.unwrap();
+    assert_eq!(versions.len(), 1);
+    assert_eq!(versions[0].key, "test.txt");
+    assert_eq!(versions[0].version_id.as_deref(), Some("G001117FCE89978B0000401205D5DC9B"));
+    assert_eq!((versions[0].is_latest, versions[0].size), (Some(true), 35));
+    assert_eq!(versions[0].owner.as_ref().map(|o| o.id.as_str()), Some("b4bf1b36d9ca43d984fbcb9491b6fce9"));
+
+    let markers = result.delete_marker.unwrap();
+    assert_eq!(markers.len(), 1);
+    assert_eq!(markers[0].key, "test2.txt");
+    assert_eq!((markers[0].etag.as_str(), markers[0].size), ("", 0));
+    assert_eq!(markers[0].last_modified, "2023-08-21T09:12:03.512Z");
+}
+
+
+const POLICY_EXPIRATION: &[time::format_description::FormatItem<'static>] =
+    time::macros::format_description!("[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:3]Z");
+
+/// JSON policy document of a browser-based POST upload to `bucket_name`
+pub fn post_policy_document(bucket_name: &str, policy: &PostPolicy, expiration: time::OffsetDateTime, security_token: Option<&str>) -> Result<String> {
+    use serde_json::{json, Value};
+
+    let conditions = std::iter::once(json!({ "bucket": bucket_name }))
+        .chain(security_token.map(|token| json!({ "x-obs-security-token": token })))
+        .chain(policy.conditions.iter().map(|c| match c {
+            PolicyCondition::Eq(field, value) => json!(["eq", format!("${field}"), value]),
+            PolicyCondition::StartsWith(field, prefix) => json!(["starts-with", format!("${field}"), prefix]),
+            PolicyCondition::ContentLengthRange(min, max) => json!(["content-length-range", min, max]),
+        }))
+        .collect::<Vec<Value>>();
+    let expiration = expiration.to_offset(time::UtcOffset::UTC).format(POLICY_EXPIRATION)?;
+
+    Ok(json!({ "expiration": expiration, "conditions": conditions }).to_string())
+}
+
+/// Form fields of a browser-based POST upload to `bucket_name`, including the base64-encoded policy and its signature
+pub fn post_policy_fields(
+    bucket_name: &str, 
+    policy: &PostPolicy, 
+    expiration: time::OffsetDateTime, 
+    ak: &str, 
+    sk: &str, 
+    security_token: Option<&str>
+) -> Result<Vec<(String, String)>> {
+    use base64::{engine::general_purpose, Engine as _};
+
+    let document = post_policy_document(bucket_name, policy, expiration, security_token)?;
+    let encoded = general_purpose::STANDARD.encode(document);
+    let signature = super::mauth_obs::signature(&encoded, sk)?;
+
+    let mut fields = policy.fields.clone();
+    fields.push(("AccessKeyId".to_owned(), ak.to_owned()));
+    fields.push(("policy".to_owned(), encoded));
+    fields.push(("signature".to_owned(), signature));
+    if let Some(security_token) = security_token {
+        fields.push(("x-obs-security-token".to_owned(), security_token.to_owned()));
+    }
+    Ok(fields)
+}
+
+#[test]
+fn test_post_policy() {
+    use std::time::Duration;
+    use base64::{engine::general_purpose, Engine as _};
+
+    let policy = PostPolicy::new(Duration::from_secs(3600))
+        .key_prefix("uploads/")
+        .content_length_range(1, 1024)
+        .content_type_prefix("image/")
+        .acl("public-read");
+    let expiration = time::macros::datetime!(2024-01-01 12:00 UTC);
+
+    let document = post_policy_document("bucket", &policy, expiration, None).unwrap();
+    assert_eq!(document, concat!(
+        r#"{"conditions":[{"bucket":"bucket"},["starts-with","$key","uploads/"],["content-length-range",1,1024],"#,
+        r#"["starts-with","$Content-Type","image/"],["eq","$acl","public-read"]],"expiration":"2024-01-01T12:00:00.000Z"}"#
+    ));
+
+    let fields = post_policy_fields("bucket", &policy, expiration, "AK", "SK", None).unwrap();
+    let encoded = general_purpose::STANDARD.encode(&document);
+    let signature = super::mauth_obs::signature(&encoded, "SK").unwrap();
+    let expected: Vec<(String, String)> = [
+        ("key", "uploads/${filename}"), ("acl", "public-read"), ("AccessKeyId", "AK"), 
+        ("policy", &encoded), ("signature", &signature)
+    ].into_iter().map(|(k, v)| (k.to_owned(), v.to_owned())).collect();
+    assert_eq!(fields, expected);
+    let document = post_policy_document("bucket", &policy, expiration, Some("TOKEN")).unwrap();
+    assert!(document.starts_with(r#"{"conditions":[{"bucket":"bucket"},{"x-obs-security-token":"TOKEN"},"#));
+    let fields = post_policy_fields("bucket", &policy, expiration, "AK", "SK", Some("TOKEN")).unwrap();
+    assert_eq!(fields.last().unwrap(), &("x-obs-security-token".to_owned(), "TOKEN".to_owned()));
+}
+
+
+/// File or object being synced, see [plan_sync]
+#[derive(Debug, Clone)]
+pub struct SyncEntry {
+    /// path relative to the local directory or the bucket prefix, `/`-separated
+    pub path: String,
+    pub size: u64,
+    /// modification time, seconds since UNIX epoch
+    pub mtime: Option<u64>,
+    /// hex-encoded MD5 of the content, known for objects only
+    pub md5: Option<String>,
+}
+
+/// fails if the objects copied from `source_prefix` to `target_prefix` within the same bucket could land under 
+/// `source_prefix` or overwrite the objects not copied yet: the listing would return the copies again, 
+/// and a move would delete them
+pub fn check_transfer_prefixes(source_bucket: &str, source_prefix: &str, target_bucket: &str, target_prefix: &str) -> Result<()> {
+    let overlapping = source_prefix.starts_with(target_prefix) || target_prefix.starts_with(source_prefix);
+    if source_bucket == target_bucket && overlapping {
+        return Err(CloudRuError::Parameter(ParameterKind::OverlappingPrefixes))
+    }
+    Ok(())
+}
+
+#[test]
+fn test_check_transfer_prefixes() {
+    let overlapping = |r: Result<()>| matches!(r, Err(CloudRuError::Parameter(ParameterKind::OverlappingPrefixes)));
+    assert!(overlapping(check_transfer_prefixes("b", "a/", "b", "a/b/")));
+    assert!(overlapping(check_transfer_prefixes("b", "a/b/", "b", "a/")));
+    assert!(overlapping(check_transfer_prefixes("b", "a/", "b", "a/")));
+    assert!(overlapping(check_transfer_prefixes("b", "", "b", "x/")));
+    assert!(overlapping(check_transfer_prefixes("b", "a", "b", "ab")));
+    check_transfer_prefixes("b", "a/", "b", "ab/").unwrap();
+    check_transfer_prefixes("b", "a/", "b", "c/a/").unwrap();
+    check_transfer_prefixes("b", "a/", "other", "a/b/").unwrap();
+}
+
+/// `prefix` as a "directory" the synced objects are put under: empty or ending with `/`
+pub fn sync_prefix(prefix: &str) -> String {
+    let prefix = prefix.trim_start_matches('/');
+    if prefix.is_empty() || prefix.ends_with('/') { prefix.to_owned() } else { format!("{prefix}/") }
+}
+
+/// suffix of the files being downloaded by a sync, renamed over the target once complete
+const SYNC_DOWNLOAD_SUFFIX: &str = ".obs-download";
+
+/// temporary file a sync downloads `local_path` to, next to it so that it can be renamed over `local_path`
+pub fn sync_download_path(local_path: &Path) -> PathBuf {
+    let mut path = local_path.as_os_str().to_owned();
+    path.push(SYNC_DOWNLOAD_SUFFIX);
+    PathBuf::from(path)
+}
+
+/// files under `root`, recursively, except the downloads left over by an interrupted sync. 
+/// Yields nothing if `root` does not exist
+pub fn local_sync_entries(root: &Path) -> Result<Vec<SyncEntry>> {
+    fn walk(dir: &Path, rel: &str, entries: &mut Vec<SyncEntry>) -> Result<()> {
+        for entry in fs::read_dir(dir).cxd(|| format!("reading directory {}", dir.display()))? {
+            let entry = entry?;
+            let Some(name) = entry.file_name().to_str().map(str::to_owned) else {
+                tracing::warn!(path=%entry.path().display(), "skipping non UTF-8 file name");
+                continue
+            };
+            let path = format!("{rel}{name}");
+            let meta = fs::metadata(entry.path()).cxd(|| format!("reading metadata of {}", entry.path().display()))?;
+            if meta.is_dir() {
+                walk(&entry.path(), &format!("{path}/"), entries)?;
+            } else if meta.is_file() && !name.ends_with(SYNC_DOWNLOAD_SUFFIX) {
+                let (size, mtime) = file_size_and_mtime(&entry.path())?;
+                entries.push(SyncEntry { path, size, mtime, md5: None });
+            }
+        }
+        Ok(())
+    }
+
+    let mut entries = vec![];
+    if root.exists() {
+        walk(root, "", &mut entries)?;
+    }
+    Ok(entries)
+}
+
+/// object listed under `prefix` (see [sync_prefix]), `None` for "directory" markers and the keys that would escape
+/// the local directory
+pub fn remote_sync_entry(prefix: &str, contents: &ListObjectsContents) -> Option<SyncEntry> {
+    let path = contents.key.strip_prefix(prefix)?;
+    if path.is_empty() || path.ends_with('/') { return None }
+    if path.split('/').any(|c| c.is_empty() || c == "." || c == "..") {
+        tracing::warn!(key=contents.key, "skipping object whose key can not be mapped to a local path");
+        return None
+    }
+    Some(SyncEntry { 
+        path: path.to_owned(), 
+        size: contents.size, 
+        mtime: parse_utc_time(&contents.last_modified).and_then(|t| u64::try_from(t.unix_timestamp()).ok()),
+        md5: etag_md5(&contents.etag),
+    })
+}
+
+/// steps to sync `local` files with `remote` objects in `direction`. 
+/// `local_md5` yields MD5 of the local file at the path, used if `options.checksum` is set
+pub fn plan_sync(
+    direction: SyncDirection, 
+    local: Vec<SyncEntry>, 
+    remote: Vec<SyncEntry>, 
+    options: &SyncOptions,
+    mut local_md5: impl FnMut(&str) -> Result<String>,
+) -> Result<SyncReport> {
+    use std::collections::BTreeMap;
+    let local: BTreeMap<String, SyncEntry> = local.into_iter().map(|e| (e.path.clone(), e)).collect();
+    let remote: BTreeMap<String, SyncEntry> = remote.into_iter().map(|e| (e.path.clone(), e)).collect();
+    let (source, target) = match direction {
+        SyncDirection::Upload => (&local, &remote),
+        SyncDirection::Download => (&remote, &local),
+    };
+
+    let transfer = |e: &SyncEntry| match direction {
+        SyncDirection::Upload => SyncAction::Upload { path: e.path.clone(), size: e.size },
+        SyncDirection::Download => SyncAction::Download { path: e.path.clone(), size: e.size },
+    };
+    let mut report = SyncReport { dry_run: options.dry_run, ..Default::default() };
+    for (path, s) in source {
+        let changed = match target.get(path) {
+            None => true,
+            Some(t) if t.size != s.size => true,
+            Some(t) => {
+                let remote_md5 = if direction == SyncDirection::Upload { &t.md5 } else { &s.md5 };
+                match remote_md5 {
+                    Some(remote_md5) if options.checksum => *remote_md5 != local_md5(path)?,
+                    _ => match (s.mtime, t.mtime) {
+                        (Some(s), Some(t)) => s > t,
+                        _ => true,
+                    }
+                }
+            }
+        };
+        if changed {
+            report.actions.push(transfer(s));
+        } else {
+            report.unchanged += 1;
+        }
+    }
+    if options.delete {
+        for path in target.keys().filter(|path| !source.contains_key(*path)) {
+            report.actions.push(match direction {
+                SyncDirection::Upload => SyncAction::DeleteObject { path: path.clone() },
+                SyncDirection::Download => SyncAction::DeleteFile { path: path.clone() },
+            });
+        }
+    }
+    Ok(report)
+}
+
+#[test]
+fn test_plan_sync() {
+    let entry = |path: &str, size, mtime, md5: Option<&str>| SyncEntry { path: path.to_owned(), size, mtime: Some(mtime), md5: md5.map(str::to_owned) };
+    let local = || vec![
+        entry("a", 1, 100, None), 
+        entry("b", 2, 100, None), 
+        entry("c", 3, 300, None), 
+        entry("d/e", 4, 100, None),
+    ];
+    let remote = || vec![
+        entry("a", 1, 200, Some("00")), 
+        entry("b", 5, 200, Some("00")), 
+        entry("c", 3, 200, Some("11")), 
+        entry("f", 6, 200, None),
+    ];
+    let options = SyncOptions::default();
+    let md5 = |path: &str| Ok(if path == "c" { "11".to_owned() } else { "22".to_owned() });
+
+    let report = plan_sync(SyncDirection::Upload, local(), remote(), &options, md5).unwrap();
+    assert_eq!(report.actions, vec![
+        SyncAction::Upload { path: "b".to_owned(), size: 2 },
+        SyncAction::Upload { path: "c".to_owned(), size: 3 },
+        SyncAction::Upload { path: "d/e".to_owned(), size: 4 },
+    ]);
+    assert_eq!(report.unchanged, 1);
+
+    let options = SyncOptions { delete: true, checksum: true, ..Default::default() };
+    let report = plan_sync(SyncDirection::Upload, local(), remote(), &options, md5).unwrap();
+    assert_eq!(report.actions, vec![
+        SyncAction::Upload { path: "a".to_owned(), size: 1 },
+        SyncAction::Upload { path: "b".to_owned(), size: 2 },
+        SyncAction::Upload { path: "d/e".to_owned(), size: 4 },
+        SyncAction::DeleteObject { path: "f".to_owned() },
+    ]);
+
+    let options = SyncOptions { delete: true, ..Default::default() };
+    let report = plan_sync(SyncDirection::Download, local(), remote(), &options, md5).unwrap();
+    assert_eq!(report.actions, vec![
+        SyncAction::Download { path: "a".to_owned(), size: 1 },
+        SyncAction::Download { path: "b".to_owned(), size: 5 },
+        SyncAction::Download { path: "f".to_owned(), size: 6 },
+        SyncAction::DeleteFile { path: "d/e".to_owned() },
+    ]);
+    assert_eq!(report.transferred_bytes(), 12);
+}
+
+#[test]
+fn test_sync_paths() {
+    assert_eq!(sync_prefix(""), "");
+    assert_eq!(sync_prefix("/a/b"), "a/b/");
+    assert_eq!(sync_prefix("a/b/"), "a/b/");
+    assert_eq!(sync_download_path(Path::new("dir/file.txt")), Path::new("dir/file.txt.obs-download"));
+}
+
+#[test]
+fn test_local_sync_entries() {
+    let dir = std::env::temp_dir().join(format!("cloudru-sync-{}", std::process::id()));
+    fs::create_dir_all(dir.join("sub")).unwrap();
+    fs::write(dir.join("a.txt"), b"a").unwrap();
+    fs::write(dir.join("sub/b.txt"), b"bb").unwrap();
+    fs::write(sync_download_path(&dir.join("sub/c.txt")), b"partial").unwrap();
+
+    let mut entries: Vec<(String, u64)> = local_sync_entries(&dir).unwrap().into_iter().map(|e| (e.path, e.size)).collect();
+    entries.sort();
+    assert_eq!(entries, [("a.txt".to_owned(), 1), ("sub/b.txt".to_owned(), 2)]);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -1,7 +1,249 @@
+use std::{fmt, path::PathBuf, sync::{Arc, Mutex, PoisonError}, time::{Duration, SystemTime}};
+
 use serde_derive::Deserialize;
+use tracing::warn;
+use crate::*;
+use super::config::{read_credentials, tildeexpand};
 
 #[derive(Clone, Deserialize, Debug)]
 pub struct Credentials {
     pub ak: String,
     pub sk: String,
+    /// Security token of temporary credentials
+    #[serde(default)]
+    pub security_token: Option<String>,
+}
+
+impl Credentials {
+    pub fn new(ak: String, sk: String) -> Self { Self { ak, sk, security_token: None } }
+
+    pub fn with_security_token(mut self, security_token: String) -> Self {
+        self.security_token = Some(security_token);
+        self
+    }
+}
+
+/// Source of the credentials, queried by the service clients before each signature
+pub trait CredentialsProvider: Send + Sync + fmt::Debug {
+    fn credentials(&self) -> Result<Credentials>;
+}
+
+/// Static keys
+impl CredentialsProvider for Credentials {
+    fn credentials(&self) -> Result<Credentials> { Ok(self.clone()) }
+}
+
+/// Credentials provider shared by the service clients
+#[derive(Clone, Debug)]
+pub struct SharedCredentialsProvider(Arc<dyn CredentialsProvider>);
+
+impl SharedCredentialsProvider {
+    pub fn new(provider: impl CredentialsProvider + 'static) -> Self { Self(Arc::new(provider)) }
+}
+
+impl CredentialsProvider for SharedCredentialsProvider {
+    fn credentials(&self) -> Result<Credentials> { self.0.credentials() }
+}
+
+impl From<Credentials> for SharedCredentialsProvider {
+    fn from(credentials: Credentials) -> Self { Self::new(credentials) }
+}
+
+/// Environment variable `name` with at most two optional prefixes, see `ClientBuilder::from_environment`
+pub(crate) fn env_var(env_prefix: Option<&str>, env_flavor_prefix: Option<&str>, name: &str) -> std::result::Result<String, std::env::VarError> {
+    use std::env::var;
+    match (env_prefix, env_flavor_prefix) {
+        (None, None) => var(name),
+        (Some(p), None) => var(format!("{p}_{name}")),
+        (None, Some(f)) => var(format!("{f}_{name}")).or_else(|_| var(name)),
+        (Some(p), Some(f)) => var(format!("{p}_{f}_{name}")).or_else(|_| var(format!("{p}_{name}"))),
+    }
+}
+
+/// Keys read from the `AK`, `SK` and `SECURITY_TOKEN` environment variables on each query,
+/// with the prefixes of `ClientBuilder::from_environment`
+#[derive(Debug, Clone, Default)]
+pub struct EnvCredentialsProvider {
+    env_prefix: Option<String>,
+    env_flavor_prefix: Option<String>,
+}
+
+impl EnvCredentialsProvider {
+    pub fn new(env_prefix: Option<&str>, env_flavor_prefix: Option<&str>) -> Self {
+        Self { env_prefix: env_prefix.map(|s| s.to_owned()), env_flavor_prefix: env_flavor_prefix.map(|s| s.to_owned()) }
+    }
+}
+
+impl CredentialsProvider for EnvCredentialsProvider {
+    fn credentials(&self) -> Result<Credentials> {
+        let pe = |name| env_var(self.env_prefix.as_deref(), self.env_flavor_prefix.as_deref(), name);
+        match (pe("AK"), pe("SK")) {
+            (Ok(ak), Ok(sk)) => Ok(Credentials { ak, sk, security_token: pe("SECURITY_TOKEN").ok() }),
+            _ => Err(CloudRuError::MissingCredentials("AK and SK environment variables".to_owned())),
+        }
+    }
+}
+
+/// Profile `id` of an ini credentials file, re-read whenever the file is modified. 
+/// If the modified file cannot be read, e.g. while it is being rewritten, the last good credentials are served
+#[derive(Debug)]
+pub struct IniCredentialsProvider {
+    path: String,
+    id: String,
+    cached: Mutex<(Option<SystemTime>, Credentials)>,
+}
+
+impl IniCredentialsProvider {
+    /// Reads the profile, failing if it cannot be loaded
+    pub fn new(path: String, id: String) -> Result<Self> {
+        let modified = Self::modified(&path);
+        let credentials = read_credentials(path.clone(), id.clone())?;
+        Ok(Self { path, id, cached: Mutex::new((modified, credentials)) })
+    }
+
+    fn modified(path: &str) -> Option<SystemTime> {
+        std::fs::metadata(PathBuf::from(tildeexpand(path.to_owned()))).and_then(|m| m.modified()).ok()
+    }
+}
+
+impl CredentialsProvider for IniCredentialsProvider {
+    fn credentials(&self) -> Result<Credentials> {
+        let mut cached = self.cached.lock().unwrap_or_else(PoisonError::into_inner);
+        let modified = Self::modified(&self.path);
+        if modified != cached.0 {
+            match read_credentials(self.path.clone(), self.id.clone()) {
+                Ok(credentials) => *cached = (modified, credentials),
+                Err(e) => warn!("keeping the last good credentials: {e}"),
+            }
+        }
+        Ok(cached.1.clone())
+    }
+}
+
+type RefreshFn = dyn Fn() -> Result<(Credentials, time::OffsetDateTime)> + Send + Sync;
+
+/// Expiring temporary credentials, obtained with `refresh` once missing or within `refresh_ahead` of the expiration. 
+/// 
+/// `refresh` runs synchronously on the thread that signs the request, one refresh at a time. With the nonblocking 
+/// clients this is an executor thread, so `refresh` must be quick and must not use `reqwest::blocking`, which panics 
+/// inside an async runtime. Slow sources should instead be polled by an async task that passes the credentials to 
+/// [RefreshingCredentialsProvider::update] ahead of the expiration, leaving `refresh` as a fallback
+pub struct RefreshingCredentialsProvider {
+    refresh: Box<RefreshFn>,
+    refresh_ahead: Duration,
+    cached: Mutex<Option<(Credentials, time::OffsetDateTime)>>,
+    refreshing: Mutex<()>,
+}
+
+impl RefreshingCredentialsProvider {
+    /// `refresh` returns the credentials along with their expiration time
+    pub fn new(
+        refresh: impl Fn() -> Result<(Credentials, time::OffsetDateTime)> + Send + Sync + 'static,
+        refresh_ahead: Duration
+    ) -> Self {
+        Self { refresh: Box::new(refresh), refresh_ahead, cached: Mutex::new(None), refreshing: Mutex::new(()) }
+    }
+
+    /// Replaces the credentials, e.g. from an async task refreshing them in the background
+    pub fn update(&self, credentials: Credentials, expiration: time::OffsetDateTime) {
+        *self.cached.lock().unwrap_or_else(PoisonError::into_inner) = Some((credentials, expiration));
+    }
+
+    fn fresh(&self) -> Option<Credentials> {
+        let now = time::OffsetDateTime::now_utc();
+        self.cached.lock().unwrap_or_else(PoisonError::into_inner).as_ref()
+            .filter(|(_, expiration)| now + self.refresh_ahead < *expiration)
+            .map(|(credentials, _)| credentials.clone())
+    }
+}
+
+impl fmt::Debug for RefreshingCredentialsProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RefreshingCredentialsProvider")
+            .field("refresh_ahead", &self.refresh_ahead)
+            .field("expiration", &self.cached.lock().unwrap_or_else(PoisonError::into_inner).as_ref().map(|(_, expiration)| *expiration))
+            .finish()
+    }
+}
+
+impl CredentialsProvider for RefreshingCredentialsProvider {
+    fn credentials(&self) -> Result<Credentials> {
+        if let Some(credentials) = self.fresh() {
+            return Ok(credentials);
+        }
+        // signers are not blocked on the cache while refreshing, and a concurrent refresh is reused
+        let _refreshing = self.refreshing.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(credentials) = self.fresh() {
+            return Ok(credentials);
+        }
+        let (credentials, expiration) = (self.refresh)()?;
+        self.update(credentials.clone(), expiration);
+        Ok(credentials)
+    }
+}
+
+#[test]
+fn test_refreshing_credentials_provider() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let refreshes = Arc::new(AtomicUsize::new(0));
+    let provider = {
+        let refreshes = refreshes.clone();
+        RefreshingCredentialsProvider::new(move || {
+            let n = refreshes.fetch_add(1, Ordering::SeqCst);
+            // the first credentials are about to expire, the second ones are good for an hour
+            let lifetime = if n == 0 { Duration::from_secs(30) } else { Duration::from_secs(3600) };
+            let credentials = Credentials::new(format!("AK{n}"), "SK".to_owned()).with_security_token(format!("TOKEN{n}"));
+            Ok((credentials, time::OffsetDateTime::now_utc() + lifetime))
+        }, Duration::from_secs(60))
+    };
+    assert_eq!(provider.credentials().unwrap().ak, "AK0");
+    assert_eq!(provider.credentials().unwrap().ak, "AK1");
+    assert_eq!(provider.credentials().unwrap().security_token.as_deref(), Some("TOKEN1"));
+    assert_eq!(refreshes.load(Ordering::SeqCst), 2);
+
+    provider.update(Credentials::new("AK-pushed".to_owned(), "SK".to_owned()), time::OffsetDateTime::now_utc() + Duration::from_secs(3600));
+    assert_eq!(provider.credentials().unwrap().ak, "AK-pushed");
+    assert_eq!(refreshes.load(Ordering::SeqCst), 2);
+
+    // a panicking refresh does not poison later signatures
+    let provider = Arc::new(RefreshingCredentialsProvider::new(|| panic!("refresh failed"), Duration::from_secs(60)));
+    let panicking = provider.clone();
+    assert!(std::thread::spawn(move || panicking.credentials()).join().is_err());
+    provider.update(Credentials::new("AK".to_owned(), "SK".to_owned()), time::OffsetDateTime::now_utc() + Duration::from_secs(3600));
+    assert_eq!(provider.credentials().unwrap().ak, "AK");
+}
+
+#[test]
+fn test_ini_credentials_provider() {
+    let dir = std::env::temp_dir().join(format!("cloudru-credentials-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("credentials");
+    std::fs::write(&path, "[default]\naccess_key_id=AK1\nsecret_access_key=SK1\n").unwrap();
+
+    let provider = IniCredentialsProvider::new(path.to_str().unwrap().to_owned(), "default".to_owned()).unwrap();
+    assert_eq!(provider.credentials().unwrap().ak, "AK1");
+
+    // each rewrite gets a distinct mtime, regardless of the file system timestamp resolution
+    let rewrite = |content: &str, secs: u64| {
+        std::fs::write(&path, content).unwrap();
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(secs)).unwrap();
+    };
+
+    rewrite("[default]\naccess_key_id=AK2\nsecret_access_key=SK2\nsecurity_token=TOKEN\n", 10);
+    let credentials = provider.credentials().unwrap();
+    assert_eq!((credentials.ak.as_str(), credentials.security_token.as_deref()), ("AK2", Some("TOKEN")));
+
+    // a rotation caught half-way keeps the last good credentials
+    rewrite("[default]\naccess_key_id=AK3\n", 20);
+    assert_eq!(provider.credentials().unwrap().ak, "AK2");
+    rewrite("[other]\naccess_key_id=AK3\nsecret_access_key=SK3\n", 30);
+    assert_eq!(provider.credentials().unwrap().ak, "AK2");
+    assert!(matches!(read_credentials(path.to_str().unwrap().to_owned(), "default".to_owned()), Err(CloudRuError::MissingCredentials(_))));
+
+    rewrite("[default]\naccess_key_id=AK3\nsecret_access_key=SK3\n", 40);
+    assert_eq!(provider.credentials().unwrap().ak, "AK3");
+
+    std::fs::remove_dir_all(&dir).unwrap();
 }
@@ -0,0 +1,16 @@
+/// UTC time like `2015-07-01T01:19:21.000Z` (OBS listings) or `2024-01-02T12:00:00.000000Z` (IAM tokens), 
+/// the fraction of a second is dropped
+pub fn parse_utc_time(s: &str) -> Option<time::OffsetDateTime> {
+    let num = |r: std::ops::Range<usize>| s.get(r)?.parse::<u32>().ok();
+    let month = time::Month::try_from(num(5..7)? as u8).ok()?;
+    let date = time::Date::from_calendar_date(num(0..4)? as i32, month, num(8..10)? as u8).ok()?;
+    let time = time::Time::from_hms(num(11..13)? as u8, num(14..16)? as u8, num(17..19)? as u8).ok()?;
+    Some(time::PrimitiveDateTime::new(date, time).assume_utc())
+}
+
+#[test]
+fn test_parse_utc_time() {
+    assert_eq!(parse_utc_time("2015-07-01T01:19:21.000Z").map(|t| t.unix_timestamp()), Some(1435713561));
+    assert_eq!(parse_utc_time("2024-01-02T12:00:00.000000Z"), Some(time::macros::datetime!(2024-01-02 12:00 UTC)));
+    assert_eq!(parse_utc_time("garbage"), None);
+}
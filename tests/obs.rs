@@ -4,7 +4,7 @@
 mod blocking {
     use std::io::{Read, Write};
 
-    use cloudru::{*, blocking::{client::*, obs::Bucket}};
+    use cloudru::{*, blocking::{client::*, obs::{Bucket, BucketEncryption, ObsClient, CannedAcl, CopyPrefixOptions, CustomerKey, Expiration, LifecycleConfiguration, LifecycleRule, ListObjectsRequest, MultipartUploadOptions, ObjectConditions, ObjectVersionEntry, Permission, PutObjectOptions, RuleStatus, ServerSideEncryption, StorageClass, SyncAction, SyncDirection, SyncOptions, Transition, VersioningStatus, UploadCheckpoint, MULTIPART_MIN_PART_SIZE}}};
     
     
     use tracing::Level;
@@ -43,6 +43,31 @@ mod blocking {
         Ok(())
     }
     
+    fn object_io_read_ahead_test(bucket: Bucket) -> Result<()> {
+        use std::io::{BufReader, Seek, SeekFrom};
+
+        let data: Vec<u8> = b"Quick brown fox jumps over lazy dog".to_vec();
+        let () = bucket.put_object("test.txt", data.clone())?;
+
+        let mut io = bucket.object_io("test.txt")?;
+        io.set_read_ahead(16);
+        let mut reader = BufReader::with_capacity(4, io);
+        let mut data_read = vec![];
+        reader.read_to_end(&mut data_read)?;
+        assert_eq!(data, data_read);
+
+        let mut io = reader.into_inner();
+        let mut buf = [0; 5];
+        io.seek(SeekFrom::Start(6))?;
+        io.read_exact(&mut buf)?;
+        assert_eq!(buf, &data[6..11]);
+        assert_eq!(io.read(&mut buf)?, 5);
+        io.seek(SeekFrom::End(0))?;
+        assert_eq!(io.read(&mut buf)?, 0);
+
+        Ok(())
+    }
+    
     fn object_io_write_test(bucket: Bucket) -> Result<()> {
         let obj = "test_writer.txt";
         let data: Vec<u8> = b"Quick brown fox jumps over lazy dog".to_vec();
@@ -61,6 +86,27 @@ mod blocking {
         Ok(())
     }
 
+    fn object_io_write_buffer_test(bucket: Bucket) -> Result<()> {
+        let obj = "test_writer.txt";
+        let data: Vec<u8> = b"Quick brown fox jumps over lazy dog".to_vec();
+
+        bucket.delete_object(obj)?;
+
+        let mut writer = bucket.object_io(obj)?;
+        writer.set_write_buffer(16)?;
+        for chunk in data.chunks(3) {
+            writer.write_all(chunk)?;
+        }
+        assert_eq!(writer.pos(), data.len() as u64);
+        assert_eq!(writer.finish()?, data.len() as u64);
+
+        let mut data_read = vec![];
+        bucket.get_object(obj, &mut data_read)?;
+        assert_eq!(data, data_read);
+
+        Ok(())
+    }
+
     fn object_io_empty_write_test(bucket: Bucket) -> Result<()> {
         let obj = "test_writer.txt";
         let data = vec![];
@@ -92,6 +138,341 @@ mod blocking {
     
         Ok(())
     }
+
+    fn iter_objects_test(bucket: Bucket) -> Result<()> {
+        let keys = ["iter/a.txt", "iter/b.txt", "iter/c.txt"];
+        for key in keys {
+            bucket.put_object(key, b"x".to_vec())?;
+        }
+
+        let request = ListObjectsRequest { prefix: Some("iter/"), max_keys: Some(2), ..Default::default() };
+        let listed = bucket.iter_objects(request).map(|c| c.map(|c| c.key)).collect::<Result<Vec<_>>>()?;
+        assert_eq!(listed, keys);
+
+        let request = ListObjectsRequest { prefix: Some("iter/"), max_keys: Some(2), ..Default::default() };
+        let versions = bucket.iter_object_versions(request).collect::<Result<Vec<_>>>()?;
+        assert!(versions.len() >= keys.len());
+
+        bucket.put_object("iter/sub/d.txt", b"x".to_vec())?;
+        let request = ListObjectsRequest { prefix: Some("iter/"), delimiter: Some("/"), ..Default::default() };
+        let entries = bucket.iter_entries(request).map(|e| e.map(|e| e.name().to_owned())).collect::<Result<Vec<_>>>()?;
+        assert_eq!(entries, ["iter/a.txt", "iter/b.txt", "iter/c.txt", "iter/sub/"]);
+        bucket.delete_object("iter/sub/d.txt")?;
+
+        for key in keys {
+            bucket.delete_object(key)?;
+        }
+        Ok(())
+    }
+
+    fn delete_objects_test(bucket: Bucket) -> Result<()> {
+        for key in ["del/a.txt", "del/b.txt", "del/c/d.txt"] {
+            bucket.put_object(key, b"x".to_vec())?;
+        }
+
+        let result = bucket.delete_objects(["del/a.txt", "del/b.txt"], false)?;
+        assert_eq!(result.deleted().count(), 2);
+        assert_eq!(result.errors().count(), 0);
+
+        let result = bucket.delete_prefix("del/")?;
+        assert_eq!(result.errors().count(), 0);
+        assert!(bucket.list(Some("del/"))?.contents.is_none());
+        Ok(())
+    }
+
+    fn lifecycle_test(bucket: Bucket) -> Result<()> {
+        let config = LifecycleConfiguration { rules: vec![LifecycleRule {
+            id: Some("test".to_owned()),
+            prefix: "lifecycle/".to_owned(),
+            status: RuleStatus::Enabled,
+            transitions: vec![Transition { days: Some(30), date: None, storage_class: StorageClass::Warm }],
+            expiration: Some(Expiration { days: Some(60), date: None }),
+            noncurrent_version_transitions: vec![],
+            noncurrent_version_expiration: None,
+        }]};
+        bucket.put_lifecycle_configuration(&config)?;
+        assert_eq!(bucket.get_lifecycle_configuration()?, Some(config));
+        bucket.delete_lifecycle_configuration()?;
+        assert_eq!(bucket.get_lifecycle_configuration()?, None);
+        Ok(())
+    }
+
+    fn versioning_test(bucket: Bucket) -> Result<()> {
+        let obj = "versioned.txt";
+        bucket.set_versioning(VersioningStatus::Enabled)?;
+        assert_eq!(bucket.get_versioning()?, Some(VersioningStatus::Enabled));
+
+        bucket.put_object(obj, b"v1".to_vec())?;
+        bucket.put_object(obj, b"v2".to_vec())?;
+        let request = ListObjectsRequest { prefix: Some(obj), ..Default::default() };
+        let versions: Vec<ObjectVersionEntry> = bucket.iter_object_versions(request).collect::<Result<_>>()?;
+        let version_ids: Vec<String> = versions.iter().filter_map(|v| v.contents().version_id.clone()).collect();
+        assert_eq!(version_ids.len(), 2);
+        let v1 = versions.iter().find(|v| v.contents().is_latest == Some(false)).unwrap().contents().version_id.clone().unwrap();
+
+        let restored = bucket.restore_version(obj, &v1)?;
+        assert!(restored.is_some());
+        let mut data = vec![];
+        bucket.get_object(obj, &mut data)?;
+        assert_eq!(data, b"v1");
+
+        for version_id in version_ids.iter().chain(restored.iter()) {
+            bucket.delete_object_version(obj, version_id)?;
+        }
+        bucket.set_versioning(VersioningStatus::Suspended)?;
+        assert_eq!(bucket.get_versioning()?, Some(VersioningStatus::Suspended));
+        Ok(())
+    }
+
+    fn presign_test(bucket: Bucket) -> Result<()> {
+        let obj = "presigned.txt";
+        let data = b"Quick brown fox jumps over lazy dog".to_vec();
+        let http = reqwest::blocking::Client::new();
+
+        let url = bucket.presign_put(obj, std::time::Duration::from_secs(60))?;
+        assert!(http.put(url).body(data.clone()).send()?.status().is_success());
+
+        let url = bucket.presign_get(obj, std::time::Duration::from_secs(60))?;
+        let response = http.get(url).send()?;
+        assert!(response.status().is_success());
+        assert_eq!(response.bytes()?, data);
+        Ok(())
+    }
+
+    fn acl_test(bucket: Bucket) -> Result<()> {
+        let acl = bucket.get_bucket_acl()?;
+        bucket.put_bucket_acl(&acl)?;
+        assert_eq!(bucket.get_bucket_acl()?.access_control_list, acl.access_control_list);
+
+        let obj = "acl.txt";
+        bucket.put_object(obj, b"x".to_vec())?;
+        bucket.set_object_canned_acl(obj, CannedAcl::PublicRead)?;
+        let acl = bucket.get_object_acl(obj)?;
+        assert!(acl.access_control_list.grants.iter().any(|g| g.grantee.is_everyone() && g.permission == Permission::Read));
+        bucket.set_object_canned_acl(obj, CannedAcl::Private)?;
+        let acl = bucket.get_object_acl(obj)?;
+        assert!(!acl.access_control_list.grants.iter().any(|g| g.grantee.is_everyone()));
+        bucket.delete_object(obj)?;
+
+        if let Some(policy) = bucket.get_bucket_policy()? {
+            assert!(!policy.statements.is_empty());
+        }
+        Ok(())
+    }
+
+    fn put_options_test(bucket: Bucket, bucket_name: &str) -> Result<()> {
+        let (obj, copy) = ("options.txt", "options-copy.txt");
+        let options = PutObjectOptions::new()
+            .content_type("text/plain")
+            .cache_control("no-cache")
+            .content_disposition("attachment; filename=\"fox.txt\"")
+            .meta("author", "fox")
+            .storage_class(StorageClass::Warm);
+        bucket.put_object_with_options(obj, b"Quick brown fox".to_vec(), &options)?;
+        let meta = bucket.get_object_meta(obj)?;
+        assert_eq!(meta.content_type.as_deref(), Some("text/plain"));
+        assert_eq!(meta.cache_control.as_deref(), Some("no-cache"));
+        assert_eq!(meta.metadata.get("author").map(String::as_str), Some("fox"));
+        assert_eq!(meta.storage_class.as_deref(), Some("WARM"));
+
+        bucket.copy_object_with_options(copy, bucket_name, obj, &PutObjectOptions::new().storage_class(StorageClass::Standard))?;
+        let meta = bucket.get_object_meta(copy)?;
+        assert_eq!(meta.metadata.get("author").map(String::as_str), Some("fox"));
+        assert_eq!(meta.storage_class, None);
+
+        bucket.copy_object_with_options(copy, bucket_name, obj, &PutObjectOptions::new().meta("reviewer", "dog"))?;
+        let meta = bucket.get_object_meta(copy)?;
+        assert_eq!(meta.metadata.keys().collect::<Vec<_>>(), ["reviewer"]);
+        bucket.delete_object(obj)?;
+        bucket.delete_object(copy)?;
+        Ok(())
+    }
+
+    fn sync_test(bucket: Bucket) -> Result<()> {
+        let prefix = "sync-test/";
+        let (up, down) = (std::env::temp_dir().join("cloudru-sync-up-blocking"), std::env::temp_dir().join("cloudru-sync-down-blocking"));
+        _ = std::fs::remove_dir_all(&up);
+        _ = std::fs::remove_dir_all(&down);
+        std::fs::create_dir_all(up.join("sub"))?;
+        std::fs::write(up.join("a.txt"), "a")?;
+        std::fs::write(up.join("sub/b.txt"), "bb")?;
+
+        let report = bucket.sync(&up, prefix, SyncDirection::Upload, &SyncOptions::default())?;
+        assert_eq!(report.actions.len(), 2);
+        let options = SyncOptions { dry_run: true, checksum: true, ..Default::default() };
+        assert!(bucket.sync(&up, prefix, SyncDirection::Upload, &options)?.actions.is_empty());
+
+        std::fs::remove_file(up.join("a.txt"))?;
+        let options = SyncOptions { delete: true, ..Default::default() };
+        let report = bucket.sync(&up, prefix, SyncDirection::Upload, &options)?;
+        assert_eq!(report.actions, vec![SyncAction::DeleteObject { path: "a.txt".to_owned() }]);
+
+        let report = bucket.sync(&down, prefix, SyncDirection::Download, &SyncOptions::default())?;
+        assert_eq!(report.actions, vec![SyncAction::Download { path: "sub/b.txt".to_owned(), size: 2 }]);
+        assert_eq!(std::fs::read(down.join("sub/b.txt"))?, b"bb");
+
+        bucket.delete_prefix(prefix)?;
+        std::fs::remove_dir_all(&up)?;
+        std::fs::remove_dir_all(&down)?;
+        Ok(())
+    }
+
+    fn integrity_test(bucket: Bucket) -> Result<()> {
+        let (obj, appended) = ("checked.txt", "checked-append.txt");
+        let data = b"Quick brown fox jumps over lazy dog".to_vec();
+        let checked = bucket.clone().with_integrity_checks(true);
+
+        checked.put_object(obj, data.clone())?;
+        let mut data_read = vec![];
+        checked.get_object(obj, &mut data_read)?;
+        assert_eq!(data, data_read);
+
+        bucket.delete_object(appended)?;
+        let mut writer = checked.object_io(appended)?;
+        writer.write_all(&data)?;
+        let mut data_read = vec![];
+        bucket.get_object(appended, &mut data_read)?;
+        assert_eq!(data, data_read);
+
+        bucket.delete_object(obj)?;
+        bucket.delete_object(appended)?;
+        Ok(())
+    }
+
+    fn conditional_test(bucket: Bucket, bucket_name: &str) -> Result<()> {
+        let (obj, copy) = ("state.txt", "state-copy.txt");
+        bucket.put_object(obj, b"state".to_vec())?;
+        let etag = bucket.get_object_meta(obj)?.etag.unwrap();
+
+        assert!(bucket.get_object_conditional(obj, &mut vec![], &ObjectConditions::new().if_none_match(&etag)).unwrap_err().is_not_modified());
+        assert!(bucket.get_object_conditional(obj, &mut vec![], &ObjectConditions::new().if_match("\"0\"")).unwrap_err().is_precondition_failed());
+        bucket.get_object_conditional(obj, &mut vec![], &ObjectConditions::new().if_match(&etag))?;
+        let meta = bucket.get_object_meta_conditional(obj, &ObjectConditions::new().if_match(&etag))?;
+        assert_eq!(meta.etag.as_deref(), Some(etag.as_str()));
+
+        bucket.copy_object_conditional(copy, bucket_name, obj, &ObjectConditions::new().if_match(&etag))?;
+        let r = bucket.copy_object_conditional(copy, bucket_name, obj, &ObjectConditions::new().if_none_match(&etag));
+        assert!(r.unwrap_err().is_precondition_failed());
+        bucket.delete_object(obj)?;
+        bucket.delete_object(copy)?;
+        Ok(())
+    }
+
+    fn encryption_test(bucket: Bucket, bucket_name: &str) -> Result<()> {
+        let (obj, copy) = ("encrypted.txt", "encrypted-copy.txt");
+        let data = b"Quick brown fox jumps over lazy dog".to_vec();
+        let sse = ServerSideEncryption::Customer(CustomerKey([7; 32]));
+
+        bucket.put_object_encrypted(obj, data.clone(), &sse)?;
+        let meta = bucket.get_object_meta_encrypted(obj, &sse)?;
+        assert_eq!(meta.sse_customer_algorithm.as_deref(), Some("AES256"));
+        let mut buf = vec![];
+        bucket.get_object_encrypted(obj, &mut buf, &sse)?;
+        assert_eq!(buf, data);
+        assert!(bucket.get_object(obj, &mut vec![]).is_err());
+
+        bucket.copy_object_encrypted(copy, bucket_name, obj, Some(&ServerSideEncryption::Obs), Some(&sse))?;
+        let meta = bucket.get_object_meta(copy)?;
+        assert_eq!(meta.server_side_encryption.as_deref(), Some("AES256"));
+        bucket.delete_object(obj)?;
+        bucket.delete_object(copy)?;
+
+        bucket.put_bucket_encryption(&BucketEncryption::obs())?;
+        assert_eq!(bucket.get_bucket_encryption()?, Some(BucketEncryption::obs()));
+        bucket.delete_bucket_encryption()?;
+        assert_eq!(bucket.get_bucket_encryption()?, None);
+        Ok(())
+    }
+
+    fn buckets_test(obs: &ObsClient, bucket_name: &str) -> Result<()> {
+        let result = obs.list_buckets()?;
+        assert!(result.buckets.buckets.iter().any(|b| b.name == bucket_name));
+        assert!(obs.bucket(bucket_name.to_owned())?.exists()?);
+
+        let missing = obs.bucket(format!("{bucket_name}-missing-3f9a1c"))?;
+        assert!(!missing.exists()?);
+        assert!(matches!(missing.get_bucket_meta(), Err(CloudRuError::NoSuchBucket(_))));
+        Ok(())
+    }
+
+    fn copy_prefix_test(bucket: Bucket, bucket_name: &str) -> Result<()> {
+        for key in ["copy-src/a.txt", "copy-src/sub/b.txt"] {
+            bucket.put_object(key, key.as_bytes().to_vec())?;
+        }
+        let options = CopyPrefixOptions::default();
+        let report = bucket.copy_prefix(&bucket, "copy-src/", "copy-dst/", &options)?;
+        assert!(report.is_complete());
+        assert_eq!(report.copied.len(), 2);
+        assert_eq!({ let mut data = vec![]; bucket.get_object("copy-dst/sub/b.txt", &mut data)?; data }, b"copy-src/sub/b.txt");
+
+        let report = bucket.move_prefix(&bucket, "copy-dst/", "copy-moved/", &options)?;
+        assert!(report.is_complete());
+        let request = ListObjectsRequest { prefix: Some("copy-dst/"), ..Default::default() };
+        assert!(bucket.list_objects(request)?.contents.unwrap_or_default().is_empty());
+
+        let data: Vec<u8> = (0..MULTIPART_MIN_PART_SIZE * 5 / 2).map(|n| (n % 251) as u8).collect();
+        bucket.put_object("copy-src/large.bin", data.clone())?;
+        let multipart = MultipartUploadOptions { part_size: MULTIPART_MIN_PART_SIZE, concurrency: 2 };
+        bucket.copy_object_multipart("copy-moved/large.bin", bucket_name, "copy-src/large.bin", data.len() as u64, &multipart)?;
+        assert_eq!({ let mut data = vec![]; bucket.get_object("copy-moved/large.bin", &mut data)?; data }, data);
+
+        bucket.delete_prefix("copy-src/")?;
+        bucket.delete_prefix("copy-moved/")?;
+        Ok(())
+    }
+
+    fn multipart_upload_test(bucket: Bucket) -> Result<()> {
+        let obj = "test_multipart.bin";
+        let data: Vec<u8> = (0..MULTIPART_MIN_PART_SIZE * 5 / 2).map(|n| (n % 251) as u8).collect();
+        let local_path = std::env::temp_dir().join("cloudru_test_multipart.bin");
+        std::fs::write(&local_path, &data)?;
+
+        let options = MultipartUploadOptions { part_size: MULTIPART_MIN_PART_SIZE, concurrency: 2 };
+        let _ = bucket.upload_file(obj, &local_path, &options)?;
+
+        let mut data_read = vec![];
+        bucket.get_object(obj, &mut data_read)?;
+        assert_eq!(data, data_read);
+
+        std::fs::remove_file(&local_path)?;
+        Ok(())
+    }
+
+    fn resumable_upload_test(bucket: Bucket, bucket_name: &str) -> Result<()> {
+        let obj = "test_multipart_resumable.bin";
+        let data: Vec<u8> = (0..MULTIPART_MIN_PART_SIZE * 5 / 2).map(|n| (n % 251) as u8).collect();
+        let local_path = std::env::temp_dir().join("cloudru_test_multipart_resumable.bin");
+        let checkpoint_path = std::env::temp_dir().join("cloudru_test_multipart_resumable.checkpoint");
+        std::fs::write(&local_path, &data)?;
+        let file_meta = std::fs::metadata(&local_path)?;
+        let file_mtime = file_meta.modified()?.duration_since(std::time::UNIX_EPOCH).ok().map(|d| d.as_secs());
+
+        // simulate an interrupted upload: part 1 is uploaded, but is not recorded in the checkpoint
+        let upload_id = bucket.initiate_multipart_upload(obj)?.upload_id;
+        let part_size = MULTIPART_MIN_PART_SIZE as usize;
+        let _etag = bucket.upload_part(obj, &upload_id, 1, data[0..part_size].to_vec())?;
+        let checkpoint = UploadCheckpoint {
+            bucket: bucket_name.to_owned(),
+            key: obj.to_owned(),
+            upload_id: upload_id.clone(),
+            file_size: data.len() as u64,
+            file_mtime,
+            part_size: MULTIPART_MIN_PART_SIZE,
+            parts: vec![],
+        };
+        checkpoint.save(&checkpoint_path)?;
+
+        let options = MultipartUploadOptions { part_size: MULTIPART_MIN_PART_SIZE, concurrency: 2 };
+        let _ = bucket.upload_file_resumable(obj, &local_path, &checkpoint_path, &options)?;
+        assert!(!checkpoint_path.exists());
+
+        let mut data_read = vec![];
+        bucket.get_object(obj, &mut data_read)?;
+        assert_eq!(data, data_read);
+
+        std::fs::remove_file(&local_path)?;
+        Ok(())
+    }
         
     
     #[ignore = "cloudru integration tests are ignored by default"]
@@ -115,9 +496,26 @@ mod blocking {
     
         basic_test(bucket.clone())?;
         object_io_read_test(bucket.clone())?;
+        object_io_read_ahead_test(bucket.clone())?;
+        object_io_write_buffer_test(bucket.clone())?;
         object_io_write_test(bucket.clone())?;
         object_io_empty_write_test(bucket.clone())?;
         object_empty_put_test(bucket.clone())?;
+        iter_objects_test(bucket.clone())?;
+        delete_objects_test(bucket.clone())?;
+        lifecycle_test(bucket.clone())?;
+        versioning_test(bucket.clone())?;
+        presign_test(bucket.clone())?;
+        acl_test(bucket.clone())?;
+        put_options_test(bucket.clone(), bucket_name)?;
+        sync_test(bucket.clone())?;
+        copy_prefix_test(bucket.clone(), bucket_name)?;
+        buckets_test(&obs, bucket_name)?;
+        integrity_test(bucket.clone())?;
+        conditional_test(bucket.clone(), bucket_name)?;
+        encryption_test(bucket.clone(), bucket_name)?;
+        multipart_upload_test(bucket.clone())?;
+        resumable_upload_test(bucket.clone(), bucket_name)?;
     
         Ok(())
     }
@@ -126,7 +524,8 @@ mod blocking {
 
 mod nonblocking {
     use bytes::Bytes;
-    use cloudru::{*, nonblocking::{client::*, obs::Bucket}};
+    use futures_util::TryStreamExt;
+    use cloudru::{*, nonblocking::{client::*, obs::{Bucket, BucketEncryption, ObsClient, CannedAcl, CopyPrefixOptions, CustomerKey, Expiration, LifecycleConfiguration, LifecycleRule, ListObjectsRequest, MultipartUploadOptions, ObjectConditions, ObjectVersionEntry, Permission, PutObjectOptions, RuleStatus, ServerSideEncryption, StorageClass, SyncAction, SyncDirection, SyncOptions, Transition, VersioningStatus, MULTIPART_MIN_PART_SIZE}}};
     
     
     use tracing::Level;
@@ -206,6 +605,346 @@ mod nonblocking {
     
         Ok(())
     }
+
+    async fn object_stream_test(bucket: Bucket) -> Result<()> {
+        let data = Bytes::from_static(b"Quick brown fox jumps over lazy dog");
+        let () = bucket.put_object("test.txt", data.clone()).await?;
+
+        let chunks: Vec<Bytes> = bucket.get_object_stream("test.txt").await?.try_collect().await?;
+        assert_eq!(data, chunks.concat());
+
+        let chunks: Vec<Bytes> = bucket.get_object_range_stream("test.txt", 6..11).await?.try_collect().await?;
+        assert_eq!(data.slice(6..11), chunks.concat());
+
+        Ok(())
+    }
+
+    #[cfg(feature = "tokio")]
+    async fn object_io_tokio_test(bucket: Bucket) -> Result<()> {
+        use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+
+        let obj = "test_writer.txt";
+        let data = Bytes::from_static(b"Quick brown fox jumps over lazy dog");
+
+        bucket.delete_object(obj).await?;
+
+        let mut writer = bucket.object_io(obj).await?;
+        tokio::io::copy(&mut &data[..], &mut writer).await?;
+        assert_eq!(AsyncWriteExt::write(&mut writer, b"!").await?, 1);
+        writer.flush().await?;
+        assert_eq!(writer.len(), data.len() as u64 + 1);
+        assert_eq!(writer.pos(), data.len() as u64 + 1);
+
+        let mut reader = bucket.object_io(obj).await?;
+        let mut buf = [0; 5];
+        reader.seek(std::io::SeekFrom::Start(6)).await?;
+        reader.read_exact(&mut buf).await?;
+        assert_eq!(&buf, b"brown");
+
+        reader.seek(std::io::SeekFrom::Start(0)).await?;
+        let mut data_read = vec![];
+        reader.read_to_end(&mut data_read).await?;
+        assert_eq!(data_read, [&data[..], b"!"].concat());
+
+        Ok(())
+    }
+
+    async fn iter_objects_test(bucket: Bucket) -> Result<()> {
+        let keys = ["iter/a.txt", "iter/b.txt", "iter/c.txt"];
+        for key in keys {
+            bucket.put_object(key, b"x".to_vec()).await?;
+        }
+
+        let request = ListObjectsRequest { prefix: Some("iter/"), max_keys: Some(2), ..Default::default() };
+        let listed: Vec<String> = bucket.iter_objects(request).map_ok(|c| c.key).try_collect().await?;
+        assert_eq!(listed, keys);
+
+        let request = ListObjectsRequest { prefix: Some("iter/"), max_keys: Some(2), ..Default::default() };
+        let versions: Vec<_> = bucket.iter_object_versions(request).try_collect().await?;
+        assert!(versions.len() >= keys.len());
+
+        bucket.put_object("iter/sub/d.txt", b"x".to_vec()).await?;
+        let request = ListObjectsRequest { prefix: Some("iter/"), delimiter: Some("/"), ..Default::default() };
+        let entries: Vec<String> = bucket.iter_entries(request).map_ok(|e| e.name().to_owned()).try_collect().await?;
+        assert_eq!(entries, ["iter/a.txt", "iter/b.txt", "iter/c.txt", "iter/sub/"]);
+        bucket.delete_object("iter/sub/d.txt").await?;
+
+        for key in keys {
+            bucket.delete_object(key).await?;
+        }
+        Ok(())
+    }
+
+    async fn delete_objects_test(bucket: Bucket) -> Result<()> {
+        for key in ["del/a.txt", "del/b.txt", "del/c/d.txt"] {
+            bucket.put_object(key, b"x".to_vec()).await?;
+        }
+
+        let result = bucket.delete_objects(["del/a.txt", "del/b.txt"], false).await?;
+        assert_eq!(result.deleted().count(), 2);
+        assert_eq!(result.errors().count(), 0);
+
+        let result = bucket.delete_prefix("del/").await?;
+        assert_eq!(result.errors().count(), 0);
+        assert!(bucket.list(Some("del/")).await?.contents.is_none());
+        Ok(())
+    }
+
+    async fn lifecycle_test(bucket: Bucket) -> Result<()> {
+        let config = LifecycleConfiguration { rules: vec![LifecycleRule {
+            id: Some("test".to_owned()),
+            prefix: "lifecycle/".to_owned(),
+            status: RuleStatus::Enabled,
+            transitions: vec![Transition { days: Some(30), date: None, storage_class: StorageClass::Warm }],
+            expiration: Some(Expiration { days: Some(60), date: None }),
+            noncurrent_version_transitions: vec![],
+            noncurrent_version_expiration: None,
+        }]};
+        bucket.put_lifecycle_configuration(&config).await?;
+        assert_eq!(bucket.get_lifecycle_configuration().await?, Some(config));
+        bucket.delete_lifecycle_configuration().await?;
+        assert_eq!(bucket.get_lifecycle_configuration().await?, None);
+        Ok(())
+    }
+
+    async fn versioning_test(bucket: Bucket) -> Result<()> {
+        let obj = "versioned.txt";
+        bucket.set_versioning(VersioningStatus::Enabled).await?;
+        assert_eq!(bucket.get_versioning().await?, Some(VersioningStatus::Enabled));
+
+        bucket.put_object(obj, b"v1".to_vec()).await?;
+        bucket.put_object(obj, b"v2".to_vec()).await?;
+        let request = ListObjectsRequest { prefix: Some(obj), ..Default::default() };
+        let versions: Vec<ObjectVersionEntry> = bucket.iter_object_versions(request).try_collect().await?;
+        let version_ids: Vec<String> = versions.iter().filter_map(|v| v.contents().version_id.clone()).collect();
+        assert_eq!(version_ids.len(), 2);
+        let v1 = versions.iter().find(|v| v.contents().is_latest == Some(false)).unwrap().contents().version_id.clone().unwrap();
+
+        let restored = bucket.restore_version(obj, &v1).await?;
+        assert!(restored.is_some());
+        let data = bucket.get_object(obj).await?;
+        assert_eq!(&data[..], b"v1");
+
+        for version_id in version_ids.iter().chain(restored.iter()) {
+            bucket.delete_object_version(obj, version_id).await?;
+        }
+        bucket.set_versioning(VersioningStatus::Suspended).await?;
+        assert_eq!(bucket.get_versioning().await?, Some(VersioningStatus::Suspended));
+        Ok(())
+    }
+
+    async fn presign_test(bucket: Bucket) -> Result<()> {
+        let obj = "presigned.txt";
+        let data = b"Quick brown fox jumps over lazy dog".to_vec();
+        let http = reqwest::Client::new();
+
+        let url = bucket.presign_put(obj, std::time::Duration::from_secs(60))?;
+        assert!(http.put(url).body(data.clone()).send().await?.status().is_success());
+
+        let url = bucket.presign_get(obj, std::time::Duration::from_secs(60))?;
+        let response = http.get(url).send().await?;
+        assert!(response.status().is_success());
+        assert_eq!(response.bytes().await?, data);
+        Ok(())
+    }
+
+    async fn acl_test(bucket: Bucket) -> Result<()> {
+        let acl = bucket.get_bucket_acl().await?;
+        bucket.put_bucket_acl(&acl).await?;
+        assert_eq!(bucket.get_bucket_acl().await?.access_control_list, acl.access_control_list);
+
+        let obj = "acl.txt";
+        bucket.put_object(obj, b"x".to_vec()).await?;
+        bucket.set_object_canned_acl(obj, CannedAcl::PublicRead).await?;
+        let acl = bucket.get_object_acl(obj).await?;
+        assert!(acl.access_control_list.grants.iter().any(|g| g.grantee.is_everyone() && g.permission == Permission::Read));
+        bucket.set_object_canned_acl(obj, CannedAcl::Private).await?;
+        let acl = bucket.get_object_acl(obj).await?;
+        assert!(!acl.access_control_list.grants.iter().any(|g| g.grantee.is_everyone()));
+        bucket.delete_object(obj).await?;
+
+        if let Some(policy) = bucket.get_bucket_policy().await? {
+            assert!(!policy.statements.is_empty());
+        }
+        Ok(())
+    }
+
+    async fn put_options_test(bucket: Bucket, bucket_name: &str) -> Result<()> {
+        let (obj, copy) = ("options.txt", "options-copy.txt");
+        let options = PutObjectOptions::new()
+            .content_type("text/plain")
+            .cache_control("no-cache")
+            .content_disposition("attachment; filename=\"fox.txt\"")
+            .meta("author", "fox")
+            .storage_class(StorageClass::Warm);
+        bucket.put_object_with_options(obj, b"Quick brown fox".to_vec(), &options).await?;
+        let meta = bucket.get_object_meta(obj).await?;
+        assert_eq!(meta.content_type.as_deref(), Some("text/plain"));
+        assert_eq!(meta.cache_control.as_deref(), Some("no-cache"));
+        assert_eq!(meta.metadata.get("author").map(String::as_str), Some("fox"));
+        assert_eq!(meta.storage_class.as_deref(), Some("WARM"));
+
+        bucket.copy_object_with_options(copy, bucket_name, obj, &PutObjectOptions::new().storage_class(StorageClass::Standard)).await?;
+        let meta = bucket.get_object_meta(copy).await?;
+        assert_eq!(meta.metadata.get("author").map(String::as_str), Some("fox"));
+        assert_eq!(meta.storage_class, None);
+
+        bucket.copy_object_with_options(copy, bucket_name, obj, &PutObjectOptions::new().meta("reviewer", "dog")).await?;
+        let meta = bucket.get_object_meta(copy).await?;
+        assert_eq!(meta.metadata.keys().collect::<Vec<_>>(), ["reviewer"]);
+        bucket.delete_object(obj).await?;
+        bucket.delete_object(copy).await?;
+        Ok(())
+    }
+
+    async fn sync_test(bucket: Bucket) -> Result<()> {
+        let prefix = "sync-test/";
+        let (up, down) = (std::env::temp_dir().join("cloudru-sync-up-nonblocking"), std::env::temp_dir().join("cloudru-sync-down-nonblocking"));
+        _ = std::fs::remove_dir_all(&up);
+        _ = std::fs::remove_dir_all(&down);
+        std::fs::create_dir_all(up.join("sub"))?;
+        std::fs::write(up.join("a.txt"), "a")?;
+        std::fs::write(up.join("sub/b.txt"), "bb")?;
+
+        let report = bucket.sync(&up, prefix, SyncDirection::Upload, &SyncOptions::default()).await?;
+        assert_eq!(report.actions.len(), 2);
+        let options = SyncOptions { dry_run: true, checksum: true, ..Default::default() };
+        assert!(bucket.sync(&up, prefix, SyncDirection::Upload, &options).await?.actions.is_empty());
+
+        std::fs::remove_file(up.join("a.txt"))?;
+        let options = SyncOptions { delete: true, ..Default::default() };
+        let report = bucket.sync(&up, prefix, SyncDirection::Upload, &options).await?;
+        assert_eq!(report.actions, vec![SyncAction::DeleteObject { path: "a.txt".to_owned() }]);
+
+        let report = bucket.sync(&down, prefix, SyncDirection::Download, &SyncOptions::default()).await?;
+        assert_eq!(report.actions, vec![SyncAction::Download { path: "sub/b.txt".to_owned(), size: 2 }]);
+        assert_eq!(std::fs::read(down.join("sub/b.txt"))?, b"bb");
+
+        bucket.delete_prefix(prefix).await?;
+        std::fs::remove_dir_all(&up)?;
+        std::fs::remove_dir_all(&down)?;
+        Ok(())
+    }
+
+    async fn integrity_test(bucket: Bucket) -> Result<()> {
+        let (obj, appended) = ("checked.txt", "checked-append.txt");
+        let data = Bytes::from_static(b"Quick brown fox jumps over lazy dog");
+        let checked = bucket.clone().with_integrity_checks(true);
+
+        checked.put_object(obj, data.clone()).await?;
+        assert_eq!(checked.get_object(obj).await?, data);
+
+        bucket.delete_object(appended).await?;
+        let mut writer = checked.object_io(appended).await?;
+        writer.write(data.clone()).await?;
+        assert_eq!(bucket.get_object(appended).await?, data);
+
+        bucket.delete_object(obj).await?;
+        bucket.delete_object(appended).await?;
+        Ok(())
+    }
+
+    async fn conditional_test(bucket: Bucket, bucket_name: &str) -> Result<()> {
+        let (obj, copy) = ("state.txt", "state-copy.txt");
+        bucket.put_object(obj, b"state".to_vec()).await?;
+        let etag = bucket.get_object_meta(obj).await?.etag.unwrap();
+
+        assert!(bucket.get_object_conditional(obj, &ObjectConditions::new().if_none_match(&etag)).await.unwrap_err().is_not_modified());
+        assert!(bucket.get_object_conditional(obj, &ObjectConditions::new().if_match("\"0\"")).await.unwrap_err().is_precondition_failed());
+        bucket.get_object_conditional(obj, &ObjectConditions::new().if_match(&etag)).await?;
+        let meta = bucket.get_object_meta_conditional(obj, &ObjectConditions::new().if_match(&etag)).await?;
+        assert_eq!(meta.etag.as_deref(), Some(etag.as_str()));
+
+        bucket.copy_object_conditional(copy, bucket_name, obj, &ObjectConditions::new().if_match(&etag)).await?;
+        let r = bucket.copy_object_conditional(copy, bucket_name, obj, &ObjectConditions::new().if_none_match(&etag)).await;
+        assert!(r.unwrap_err().is_precondition_failed());
+        bucket.delete_object(obj).await?;
+        bucket.delete_object(copy).await?;
+        Ok(())
+    }
+
+    async fn encryption_test(bucket: Bucket, bucket_name: &str) -> Result<()> {
+        let (obj, copy) = ("encrypted.txt", "encrypted-copy.txt");
+        let data = b"Quick brown fox jumps over lazy dog".to_vec();
+        let sse = ServerSideEncryption::Customer(CustomerKey([7; 32]));
+
+        bucket.put_object_encrypted(obj, data.clone(), &sse).await?;
+        let meta = bucket.get_object_meta_encrypted(obj, &sse).await?;
+        assert_eq!(meta.sse_customer_algorithm.as_deref(), Some("AES256"));
+        assert_eq!(bucket.get_object_encrypted(obj, &sse).await?, data);
+        assert!(bucket.get_object(obj).await.is_err());
+
+        bucket.copy_object_encrypted(copy, bucket_name, obj, Some(&ServerSideEncryption::Obs), Some(&sse)).await?;
+        let meta = bucket.get_object_meta(copy).await?;
+        assert_eq!(meta.server_side_encryption.as_deref(), Some("AES256"));
+        bucket.delete_object(obj).await?;
+        bucket.delete_object(copy).await?;
+
+        bucket.put_bucket_encryption(&BucketEncryption::obs()).await?;
+        assert_eq!(bucket.get_bucket_encryption().await?, Some(BucketEncryption::obs()));
+        bucket.delete_bucket_encryption().await?;
+        assert_eq!(bucket.get_bucket_encryption().await?, None);
+        Ok(())
+    }
+
+    async fn buckets_test(obs: &ObsClient, bucket_name: &str) -> Result<()> {
+        let result = obs.list_buckets().await?;
+        assert!(result.buckets.buckets.iter().any(|b| b.name == bucket_name));
+        assert!(obs.bucket(bucket_name.to_owned())?.exists().await?);
+
+        let missing = obs.bucket(format!("{bucket_name}-missing-3f9a1c"))?;
+        assert!(!missing.exists().await?);
+        assert!(matches!(missing.get_bucket_meta().await, Err(CloudRuError::NoSuchBucket(_))));
+        Ok(())
+    }
+
+    async fn copy_prefix_test(bucket: Bucket, bucket_name: &str) -> Result<()> {
+        for key in ["copy-src/a.txt", "copy-src/sub/b.txt"] {
+            bucket.put_object(key, key.as_bytes().to_vec()).await?;
+        }
+        let options = CopyPrefixOptions::default();
+        let report = bucket.copy_prefix(&bucket, "copy-src/", "copy-dst/", &options).await?;
+        assert!(report.is_complete());
+        assert_eq!(report.copied.len(), 2);
+        assert_eq!(bucket.get_object("copy-dst/sub/b.txt").await?, "copy-src/sub/b.txt");
+
+        let report = bucket.move_prefix(&bucket, "copy-dst/", "copy-moved/", &options).await?;
+        assert!(report.is_complete());
+        let request = ListObjectsRequest { prefix: Some("copy-dst/"), ..Default::default() };
+        assert!(bucket.list_objects(request).await?.contents.unwrap_or_default().is_empty());
+
+        let data: Vec<u8> = (0..MULTIPART_MIN_PART_SIZE * 5 / 2).map(|n| (n % 251) as u8).collect();
+        bucket.put_object("copy-src/large.bin", data.clone()).await?;
+        let multipart = MultipartUploadOptions { part_size: MULTIPART_MIN_PART_SIZE, concurrency: 2 };
+        bucket.copy_object_multipart("copy-moved/large.bin", bucket_name, "copy-src/large.bin", data.len() as u64, &multipart).await?;
+        assert_eq!(bucket.get_object("copy-moved/large.bin").await?, data);
+
+        bucket.delete_prefix("copy-src/").await?;
+        bucket.delete_prefix("copy-moved/").await?;
+        Ok(())
+    }
+
+    async fn multipart_upload_test(bucket: Bucket) -> Result<()> {
+        let obj = "test_multipart.bin";
+        let data: Vec<u8> = (0..MULTIPART_MIN_PART_SIZE * 5 / 2).map(|n| (n % 251) as u8).collect();
+        let local_path = std::env::temp_dir().join("cloudru_test_multipart_nb.bin");
+        let checkpoint_path = std::env::temp_dir().join("cloudru_test_multipart_nb.checkpoint");
+        std::fs::write(&local_path, &data)?;
+
+        let options = MultipartUploadOptions { part_size: MULTIPART_MIN_PART_SIZE, concurrency: 2 };
+        let _ = bucket.upload_file(obj, &local_path, &options).await?;
+        let data_read = bucket.get_object(obj).await?;
+        assert_eq!(data, data_read);
+
+        bucket.delete_object(obj).await?;
+        let _ = bucket.upload_file_resumable(obj, &local_path, &checkpoint_path, &options).await?;
+        assert!(!checkpoint_path.exists());
+        let data_read = bucket.get_object(obj).await?;
+        assert_eq!(data, data_read);
+
+        std::fs::remove_file(&local_path)?;
+        Ok(())
+    }
         
     
     #[ignore = "cloudru integration tests are ignored by default"]
@@ -232,6 +971,23 @@ mod nonblocking {
         object_io_write_test(bucket.clone()).await?;
         object_io_empty_write_test(bucket.clone()).await?;
         object_empty_put_test(bucket.clone()).await?;
+        object_stream_test(bucket.clone()).await?;
+        #[cfg(feature = "tokio")]
+        object_io_tokio_test(bucket.clone()).await?;
+        iter_objects_test(bucket.clone()).await?;
+        delete_objects_test(bucket.clone()).await?;
+        lifecycle_test(bucket.clone()).await?;
+        versioning_test(bucket.clone()).await?;
+        presign_test(bucket.clone()).await?;
+        acl_test(bucket.clone()).await?;
+        put_options_test(bucket.clone(), bucket_name).await?;
+        sync_test(bucket.clone()).await?;
+        copy_prefix_test(bucket.clone(), bucket_name).await?;
+        buckets_test(&obs, bucket_name).await?;
+        integrity_test(bucket.clone()).await?;
+        conditional_test(bucket.clone(), bucket_name).await?;
+        encryption_test(bucket.clone(), bucket_name).await?;
+        multipart_upload_test(bucket.clone()).await?;
     
         Ok(())
     }